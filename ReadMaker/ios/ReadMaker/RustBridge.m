@@ -1,13 +1,31 @@
 #import "RustBridge.h"
 #import <React/RCTUtils.h>
 
-// Rustの関数宣言（native/rust/src/lib.rsで定義済み）
-extern char* js_analyze_text(const char* input);
+// Rustの関数宣言（backend/core/src/ffi/mod.rsで定義済み。backend/core/include/readmaker.h と一致させること）
+// js_analyze_text は {"engine": "vibrato" など, "words": [...]} のJSONを返す
+extern char* js_analyze_text(const char* input, const uint8_t* dictionary_data, size_t dictionary_len);
 extern char* js_test_bridge(void);
 extern void js_free_string(char* ptr);
 
 @implementation RustBridge
 
+/**
+ * アプリに同梱した辞書（ipadic.vibrato）をメモリマップで読み込む
+ * 見つからなければnilを返し、Rust側で文字種フォールバックを使う
+ */
+static NSData* bundledDictionary(void)
+{
+    static NSData* dictionary = nil;
+    static dispatch_once_t once;
+    dispatch_once(&once, ^{
+        NSString* path = [[NSBundle mainBundle] pathForResource:@"ipadic" ofType:@"vibrato"];
+        if (path) {
+            dictionary = [NSData dataWithContentsOfFile:path options:NSDataReadingMappedIfSafe error:nil];
+        }
+    });
+    return dictionary;
+}
+
 // React Nativeモジュール名を設定（TypeScript側でNativeModules.ReadMakerRustBridgeとして参照）
 RCT_EXPORT_MODULE(ReadMakerRustBridge);
 
@@ -23,15 +41,20 @@ RCT_EXPORT_METHOD(analyzeText:(NSString *)input
 {
     // 入力値検証
     if (!input || input.length == 0) {
-        resolve(@"[]");  // 空配列のJSON
+        resolve(@"{\"engine\":\"char_class\",\"words\":[]}");  // 形態素なしのJSON
         return;
     }
     
     // NSString → C文字列変換
     const char* c_input = [input UTF8String];
     
+    // 辞書がなくてもNULLは渡せないため、空のバイト列を渡してフォールバックさせる
+    static const uint8_t empty_dictionary = 0;
+    NSData* dictionary = bundledDictionary();
+    const uint8_t* dictionary_data = dictionary.length > 0 ? dictionary.bytes : &empty_dictionary;
+    
     // Rust関数呼び出し
-    char* result = js_analyze_text(c_input);
+    char* result = js_analyze_text(c_input, dictionary_data, dictionary.length);
     
     if (result) {
         // C文字列 → NSString変換
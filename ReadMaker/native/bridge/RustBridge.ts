@@ -5,14 +5,25 @@
 
 import { NativeModules, Platform } from 'react-native';
 
+// 解析したエンジン（Rust側のEngineと同じ識別子）
+export type AnalysisEngine = 'vibrato' | 'char_class' | 'punctuation' | 'lindera';
+
+// 形態素解析の結果（js_analyze_textが返すJSON）
+export interface AnalysisResult {
+  engine: AnalysisEngine;
+  words: string[];
+}
+
 // TypeScript型定義
 export interface MorphologyAnalysis {
+  analyze(input: string): Promise<AnalysisResult>;
   analyzeText(input: string): Promise<string[]>;
   testBridge(): Promise<string>;
 }
 
 // ネイティブモジュール型定義
 interface RustBridgeNative {
+  // {"engine": ..., "words": [...]} のJSON文字列
   analyzeText(input: string): Promise<string>;
   testBridge(): Promise<string>;
 }
@@ -36,28 +47,28 @@ class RustBridge implements MorphologyAnalysis {
   }
 
   /**
-   * 形態素解析実行
+   * 形態素解析実行（解析したエンジン付き）
    * @param input 解析対象の日本語テキスト
-   * @returns 形態素解析結果の配列
-   * 
+   * @returns 形態素と、辞書で解析したかフォールバックかを示すエンジン
+   *
    * @example
    * ```typescript
    * const bridge = new RustBridge();
-   * const words = await bridge.analyzeText("吾輩は猫である。");
-   * console.log(words); // ["吾輩", "は", "猫", "で", "ある", "。"]
+   * const result = await bridge.analyze("吾輩は猫である。");
+   * console.log(result); // { engine: "vibrato", words: ["吾輩", "は", "猫", "で", "ある", "。"] }
    * ```
    */
-  async analyzeText(input: string): Promise<string[]> {
+  async analyze(input: string): Promise<AnalysisResult> {
     try {
       // バリデーション
       if (typeof input !== 'string') {
         throw new Error('Input must be a string');
       }
-      
+
       if (input.trim().length === 0) {
-        return [];
+        return { engine: 'punctuation', words: [] };
       }
-      
+
       // ネイティブ未利用時は早期フォールバック
       if (!this.available || !this.nativeModule) {
         return this.fallbackAnalysis(input);
@@ -65,34 +76,38 @@ class RustBridge implements MorphologyAnalysis {
 
       // Rustネイティブ関数呼び出し
       const jsonResult = await this.nativeModule.analyzeText(input);
-      
-      // JSON文字列をパース
-      import { analyzeText, getWords, textAnalyzer } from '../../src/services/textAnalyzer';
 
-// 既存のimportパスを維持しつつ、新実装に橋渡し
-export { analyzeText, getWords };
-
-// 追加のヘルパー関数
-export async function initializeAnalyzer(): Promise<boolean> {
-  return await textAnalyzer.initialize();
-}
-
-export async function getAnalyzerStatus() {
-  return textAnalyzer.getStatus();
-}
-
-
-      
-      return words;
-      
+      // JSON文字列をパース
+      const result = JSON.parse(jsonResult) as AnalysisResult;
+      if (typeof result?.engine !== 'string' || !Array.isArray(result.words)) {
+        throw new Error(`Unexpected analysis result: ${jsonResult}`);
+      }
+      return result;
     } catch (error) {
-      console.error('RustBridge.analyzeText error:', error);
-      
+      console.error('RustBridge.analyze error:', error);
+
       // フォールバック: 簡易分割
       return this.fallbackAnalysis(input);
     }
   }
 
+  /**
+   * 形態素解析実行
+   * @param input 解析対象の日本語テキスト
+   * @returns 形態素解析結果の配列
+   *
+   * @example
+   * ```typescript
+   * const bridge = new RustBridge();
+   * const words = await bridge.analyzeText("吾輩は猫である。");
+   * console.log(words); // ["吾輩", "は", "猫", "で", "ある", "。"]
+   * ```
+   */
+  async analyzeText(input: string): Promise<string[]> {
+    const result = await this.analyze(input);
+    return result.words;
+  }
+
   /**
    * ブリッジ接続テスト
    * @returns 接続確認メッセージ
@@ -115,11 +130,12 @@ export async function getAnalyzerStatus() {
    * @param input 入力テキスト
    * @returns 簡易分割結果
    */
-  private fallbackAnalysis(input: string): string[] {
+  private fallbackAnalysis(input: string): AnalysisResult {
     // 簡易実装: 句読点で分割
-    return input
+    const words = input
       .split(/([。、！？\s]+)/)
       .filter(word => word.trim().length > 0);
+    return { engine: 'punctuation', words };
   }
 }
 
@@ -137,6 +153,16 @@ export const getRustBridge = (): RustBridge => {
   return rustBridgeInstance;
 };
 
+/**
+ * 便利関数: 形態素解析実行（解析したエンジン付き）
+ * @param input 解析対象テキスト
+ * @returns 形態素解析結果とエンジン
+ */
+export const analyze = async (input: string): Promise<AnalysisResult> => {
+  const bridge = getRustBridge();
+  return bridge.analyze(input);
+};
+
 /**
  * 便利関数: 形態素解析実行
  * @param input 解析対象テキスト
@@ -1,43 +1,147 @@
+//! 形態素解析のベンチマーク
+//!
+//! 使用方法: cargo run --release --bin benchmark -- [オプション]
+//! 辞書の読み込み時間・スループット（文字/秒）・1文あたりのレイテンシ（p50/p95/p99）・ピークメモリを計測し、
+//! JSONで保存したベースラインと比べる。許容幅を超えて悪化した項目があれば終了コード1で終わる
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
 use std::time::Instant;
-use readmaker_core::analyze_text;
-
-fn main() {
-    println!("=== ReadMaker形態素解析 - パフォーマンステスト ===\n");
-    
-    // 辞書読み込みのウォームアップ（1回だけ）
-    println!("🔄 辞書読み込み中...");
-    let warmup_start = Instant::now();
-    let _ = analyze_text("テスト", &[]);
-    let warmup_time = warmup_start.elapsed();
-    println!("✅ 辞書読み込み完了: {:.0}ms\n", warmup_time.as_secs_f64() * 1000.0);
-    
-    // テストケース
-    let test_cases = vec![
-        ("短文（11文字）", "今日は良い天気ですね。"),
-        ("中文（39文字）", "昨日の夜、友人と一緒に新しくオープンしたレストランで美味しい料理を食べました。"),
-        ("長文（112文字）", "人工知能技術の発展により、自然言語処理分野においても大きな進歩が見られます。特に形態素解析や機械翻訳、文書要約などの技術は実用レベルに達しており、多くの企業や研究機関で活用されています。今後もさらなる技術革新が期待されます。"),
-    ];
-    
-    for (label, text) in test_cases {
-        println!("🔍 {}", label);
-        println!("テキスト: {}", text);
-        println!("文字数: {}文字", text.chars().count());
-        
-        // 複数回実行して最小時間を測定（純粋な解析時間）
-        let mut min_duration = std::time::Duration::from_secs(1);
-        let mut result = Vec::new();
-        let iterations = 100;
-        
-        for _ in 0..iterations {
-            let start = Instant::now();
-            result = analyze_text(text, &[]);
-            let duration = start.elapsed();
-            min_duration = min_duration.min(duration);
+use readmaker_core::dictionary_path;
+use readmaker_engine::bench::{compare_with_baseline, run_benchmark, BenchmarkResult, Corpus};
+use readmaker_engine::MorphAnalyzer;
+
+const DEFAULT_CORPUS: &str = "../../../backend/core/testdata/corpus/sample.txt";
+const DEFAULT_ITERATIONS: usize = 20;
+const DEFAULT_TOLERANCE: f64 = 0.10;
+
+const USAGE: &str = "使用方法: benchmark [オプション]
+
+  -d, --dict <PATH>         辞書ファイル（既定: READMAKER_DIC_PATH または dictionaries/ipadic.vibrato）
+  -c, --corpus <FILE>       コーパス（1行1文。複数指定可、既定: backend/core/testdata/corpus/sample.txt）
+  -n, --iterations <N>      各コーパスを解析する回数（既定: 20）
+  -o, --output <FILE>       結果をJSONで保存
+  -b, --baseline <FILE>     比べるベースライン（--output で保存したJSON）
+  -t, --tolerance <RATIO>   許容する悪化の割合（既定: 0.10 = 10%）
+  -h, --help                このヘルプを表示";
+
+struct Options {
+    dict: PathBuf,
+    corpora: Vec<PathBuf>,
+    iterations: usize,
+    output: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    tolerance: f64,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        dict: PathBuf::from(dictionary_path()),
+        corpora: Vec::new(),
+        iterations: DEFAULT_ITERATIONS,
+        output: None,
+        baseline: None,
+        tolerance: DEFAULT_TOLERANCE,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-d" | "--dict" => options.dict = PathBuf::from(value()?),
+            "-c" | "--corpus" => options.corpora.push(PathBuf::from(value()?)),
+            "-n" | "--iterations" => {
+                let n = value()?;
+                options.iterations = match n.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("--iterations には1以上の整数を指定してください: {}", n)),
+                };
+            }
+            "-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+            "-b" | "--baseline" => options.baseline = Some(PathBuf::from(value()?)),
+            "-t" | "--tolerance" => {
+                let t = value()?;
+                options.tolerance = match t.parse::<f64>() {
+                    Ok(t) if t >= 0.0 => t,
+                    _ => return Err(format!("--tolerance には0以上の数を指定してください: {}", t)),
+                };
+            }
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    if options.corpora.is_empty() {
+        options.corpora.push(PathBuf::from(DEFAULT_CORPUS));
+    }
+    Ok(Some(options))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
         }
-        
-        println!("解析結果: {:?}", &result[..std::cmp::min(5, result.len())]);
-        println!("語数: {}語", result.len());
-        println!("⏱️ 最速処理時間: {:.3}ms", min_duration.as_secs_f64() * 1000.0);
-        println!();
+    };
+
+    println!("=== ReadMaker形態素解析 - ベンチマーク ===\n");
+    let corpora = options.corpora.iter().map(Corpus::from_file).collect::<Result<Vec<_>, _>>()?;
+
+    // フォールバックの分割を計測しないよう、辞書が読めなければ終了する
+    println!("🔄 辞書読み込み中... ({})", options.dict.display());
+    let started = Instant::now();
+    let analyzer = MorphAnalyzer::from_path(&options.dict)?;
+    let load_time = started.elapsed();
+
+    let result = run_benchmark(&analyzer, load_time, &corpora, options.iterations)?;
+    print_result(&result);
+
+    if let Some(path) = &options.output {
+        fs::write(path, result.to_json()? + "\n")?;
+        println!("\n💾 結果を保存しました: {}", path.display());
+    }
+
+    if let Some(path) = &options.baseline {
+        let baseline = BenchmarkResult::from_json(&fs::read_to_string(path)?)?;
+        if baseline.dictionary != result.dictionary {
+            println!("\n⚠️ ベースラインと辞書が異なります（{} → {}）", baseline.dictionary, result.dictionary);
+        }
+        let regressions = compare_with_baseline(&baseline, &result, options.tolerance)?;
+        println!("\n=== ベースラインとの比較（{}、許容 {:.0}%） ===", path.display(), options.tolerance * 100.0);
+        if regressions.is_empty() {
+            println!("✅ 悪化した項目はありません");
+        } else {
+            for r in &regressions {
+                println!("❌ {}: {:.1} → {:.1}（{:.1}%悪化）", r.metric, r.baseline, r.current, r.change * 100.0);
+            }
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+fn print_result(result: &BenchmarkResult) {
+    println!("✅ 辞書読み込み: {:.0}ms（バージョン {}）\n", result.dictionary_load_ms, result.dictionary);
+    for corpus in &result.corpora {
+        println!("🔍 {}（{}文, {}文字 × {}回）", corpus.name, corpus.sentences, corpus.chars, result.iterations);
+        println!(
+            "   {:.0} 文字/秒  p50 {:.1}µs  p95 {:.1}µs  p99 {:.1}µs",
+            corpus.chars_per_sec, corpus.latency_us.p50, corpus.latency_us.p95, corpus.latency_us.p99
+        );
+    }
+    println!("\n=== 全体 ===");
+    println!("スループット: {:.0} 文字/秒", result.chars_per_sec);
+    println!(
+        "レイテンシ: p50 {:.1}µs  p95 {:.1}µs  p99 {:.1}µs  平均 {:.1}µs  最大 {:.1}µs",
+        result.latency_us.p50, result.latency_us.p95, result.latency_us.p99, result.latency_us.mean, result.latency_us.max
+    );
+    match result.peak_memory_bytes {
+        Some(bytes) => println!("ピークメモリ: {:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+        None => println!("ピークメモリ: 取得できません（このプラットフォームは未対応）"),
     }
 }
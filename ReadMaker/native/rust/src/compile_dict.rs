@@ -1,13 +1,116 @@
+//! MeCab IPADICのソースからVibrato辞書（`dictionaries/ipadic.vibrato`）を作るツール
+//!
+//! 使用方法: cargo run --release --features build-tools --bin compile_dict -- [オプション]
+//! 変換処理は`readmaker_engine::compile`にあり、Node.jsや外部のvibrato CLIは不要
+
 use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use readmaker_engine::compile::{write_dictionary, DictionarySource, SourceEncoding, DEFAULT_COMPRESSION_LEVEL};
+use vibrato::Dictionary;
+
+const DEFAULT_INPUT: &str = "dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801";
+const DEFAULT_OUTPUT: &str = "dictionaries/ipadic.vibrato";
+
+const USAGE: &str = "使用方法: compile_dict [オプション]
+
+  -i, --input <DIR>       IPADICのソース（*.csv・matrix.def・char.def・unk.def）のディレクトリ
+                          （既定: dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801）
+  -o, --output <PATH>     出力先（既定: dictionaries/ipadic.vibrato）
+  -l, --level <N>         zstdの圧縮レベル 1〜22（既定: 19）
+      --no-compress       圧縮せずに書き出す
+  -e, --encoding <ENC>    ソースの文字コード auto・utf-8・euc-jp（既定: auto）
+  -h, --help              このヘルプを表示";
+
+struct Options {
+    input: PathBuf,
+    output: PathBuf,
+    level: Option<i32>,
+    encoding: SourceEncoding,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        input: PathBuf::from(DEFAULT_INPUT),
+        output: PathBuf::from(DEFAULT_OUTPUT),
+        level: Some(DEFAULT_COMPRESSION_LEVEL),
+        encoding: SourceEncoding::Auto,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-i" | "--input" => options.input = PathBuf::from(value()?),
+            "-o" | "--output" => options.output = PathBuf::from(value()?),
+            "-l" | "--level" => {
+                let level = value()?;
+                match level.parse::<i32>() {
+                    Ok(level @ 1..=22) => options.level = Some(level),
+                    _ => return Err(format!("圧縮レベルは1〜22で指定してください: {}", level)),
+                }
+            }
+            "--no-compress" => options.level = None,
+            "-e" | "--encoding" => options.encoding = value()?.parse()?,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn write_file(dict: &Dictionary, path: &Path, level: Option<i32>) -> Result<u64, Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let size = write_dictionary(dict, &mut writer, level)?;
+    writer.flush()?;
+    Ok(size)
+}
 
-// 辞書ビルドは Node スクリプト + vibrato CLI を利用する方針に切替。
-// このbinは build-tools フィーチャを有効にして実行すると案内のみを表示します。
 fn main() -> Result<(), Box<dyn Error>> {
-    println!("ReadMaker: 辞書ビルド手順");
-    println!("1) npm run dic:build-lex で lex_utf8.csv / *_utf8.def を生成");
-    println!("2) vibrato の compile ツールで ipadic.vibrato を生成");
-    println!("   例: compile -l lex_utf8.csv -o dictionaries/ipadic.vibrato \");
-    println!("          --unk unk_utf8.def --char char_utf8.def --matrix matrix_utf8.def");
-    println!("\nこのバイナリは案内のみです。処理は行いません。");
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let started = Instant::now();
+    println!("読み込み中: {}（文字コード: {}）", options.input.display(), options.encoding);
+    let source = DictionarySource::from_dir(&options.input, options.encoding)?;
+    println!("  語彙: {}ファイル, {}語", source.lexicon_files.len(), source.lexicon.lines().count());
+
+    println!("組み立て中...");
+    let dict = source.build()?;
+
+    // 途中で失敗しても既存の辞書を壊さないよう、一時ファイルに書いてから置き換える
+    if let Some(parent) = options.output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = options.output.with_extension("vibrato.tmp");
+    match options.level {
+        Some(level) => println!("書き出し中: {}（zstd レベル{}）", options.output.display(), level),
+        None => println!("書き出し中: {}（非圧縮）", options.output.display()),
+    }
+    let size = match write_file(&dict, &temp, options.level) {
+        Ok(size) => size,
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+    };
+    fs::rename(&temp, &options.output)?;
+
+    println!(
+        "完了: {}（{:.1} MB, {:.1}秒）",
+        options.output.display(),
+        size as f64 / (1024.0 * 1024.0),
+        started.elapsed().as_secs_f64()
+    );
     Ok(())
 }
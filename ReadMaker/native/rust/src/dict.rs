@@ -0,0 +1,255 @@
+//! 辞書ファイルの保守ツール
+//!
+//! 使用方法: cargo run --bin dict -- <サブコマンド> [引数]
+//! inspect（形式・サイズ・語彙数・チェックサム）、compress/decompress、verify（読み込みと解析の確認）、
+//! lookup（表層形が一致する語彙）を行う
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use readmaker_engine::compile::{DictionarySource, SourceEncoding, DEFAULT_COMPRESSION_LEVEL};
+use readmaker_engine::{dictionary_digest, is_zstd_compressed, read_dictionary, MorphAnalyzer};
+use sha2::{Digest, Sha256};
+use vibrato::dictionary::LexType;
+use vibrato::Tokenizer;
+
+/// Vibrato辞書の先頭（形式とバージョン）
+const VIBRATO_MAGIC: &[u8] = b"VibratoTokenizer ";
+
+/// IPADICのソース（lookupで`--source`を省略したときに探す）
+const DEFAULT_SOURCE: &str = "dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801";
+
+/// verifyで解析する文
+const SMOKE_SENTENCE: &str = "吾輩は猫である。名前はまだ無い。";
+
+const USAGE: &str = "使用方法: dict <サブコマンド> [引数]
+
+  inspect <FILE> [--source DIR] [-e ENC]
+                                      形式・サイズ・語彙数・チェックサムを表示
+                                      （語彙数は --source のソースから組み立てた辞書と一致したときのみ）
+  compress <INPUT> <OUTPUT> [-l N]    zstdで圧縮（レベル 1〜22、既定: 19）
+  decompress <INPUT> <OUTPUT>         zstdを解凍して非圧縮の辞書にする
+  verify <FILE> [--text TEXT]         読み込んで文を解析できるか確かめる
+  lookup <FILE> <SURFACE> [--source DIR] [-e ENC]
+                                      表層形が一致するIPADICのソースの語彙と、辞書での解析結果を表示
+                                      （--source の既定: dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801）";
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// 位置引数とオプションに分けた引数
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    /// `value_options`に含まれるオプションは次の引数を値として取る
+    fn parse(args: impl IntoIterator<Item = String>, value_options: &[&str]) -> Result<Self, String> {
+        let mut parsed = Args { positional: Vec::new(), options: Vec::new() };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if value_options.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| format!("{} に値がありません", arg))?;
+                parsed.options.push((arg, value));
+            } else if arg.starts_with('-') && arg.len() > 1 {
+                return Err(format!("不明なオプション: {}", arg));
+            } else {
+                parsed.positional.push(arg);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, names: &[&str]) -> Option<&str> {
+        self.options.iter().rev().find(|(name, _)| names.contains(&name.as_str())).map(|(_, v)| v.as_str())
+    }
+
+    /// 位置引数がちょうど`N`個であることを確かめる
+    fn positional<const N: usize>(&self) -> Result<[&str; N], String> {
+        let values: Vec<&str> = self.positional.iter().map(String::as_str).collect();
+        values.try_into().map_err(|_| format!("引数は{}個必要です", N))
+    }
+}
+
+fn read_file(path: &str) -> CliResult<Vec<u8>> {
+    Ok(fs::read(path).map_err(|e| format!("読み込めません: {}: {}", path, e))?)
+}
+
+/// zstd圧縮されていれば解凍したデータを返す
+fn decompress(data: Vec<u8>) -> CliResult<Vec<u8>> {
+    if is_zstd_compressed(&data) {
+        Ok(zstd::decode_all(data.as_slice()).map_err(|e| format!("解凍できません: {}", e))?)
+    } else {
+        Ok(data)
+    }
+}
+
+/// 非圧縮の辞書データの先頭行（`VibratoTokenizer 0.5`）
+fn format_version(raw: &[u8]) -> Option<&str> {
+    if !raw.starts_with(VIBRATO_MAGIC) {
+        return None;
+    }
+    let line = raw.split(|&b| b == b'\n').next()?;
+    std::str::from_utf8(line).ok()
+}
+
+fn inspect(args: Args) -> CliResult<()> {
+    let [path] = args.positional()?;
+    let data = read_file(path)?;
+    let (size, compressed) = (data.len(), is_zstd_compressed(&data));
+    let checksum = Sha256::digest(&data).iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let raw = decompress(data)?;
+    let format = format_version(&raw).ok_or("Vibratoの辞書ではありません")?;
+    let version = dictionary_digest(&raw)?;
+
+    let started = Instant::now();
+    read_dictionary(&raw)?;
+    let load_secs = started.elapsed().as_secs_f64();
+
+    println!("ファイル: {}", path);
+    println!("形式: {}（{}）", format, if compressed { "zstd圧縮" } else { "非圧縮" });
+    println!("サイズ: {} バイト（非圧縮 {} バイト）", size, raw.len());
+    // Vibratoは語彙数を公開していないため、指定されたソースから組み立てた辞書がこのファイルと同じときだけ表示する
+    match args.option(&["--source"]) {
+        Some(dir) => {
+            let encoding: SourceEncoding = args.option(&["-e", "--encoding"]).unwrap_or("auto").parse()?;
+            let source = DictionarySource::from_dir(dir, encoding)?;
+            let built = MorphAnalyzer::from_dictionary(source.build()?);
+            if built.dictionary_version() == version {
+                println!("語彙数: {}（{}）", source.lexicon_len(), dir);
+            } else {
+                println!(
+                    "語彙数: 不明（{} から組み立てた辞書とは異なります。ソースの語彙数は {}）",
+                    dir,
+                    source.lexicon_len()
+                );
+            }
+        }
+        None => println!("語彙数: 不明（--source で辞書を組み立てたIPADICのソースを指定すると表示）"),
+    }
+    println!("SHA-256: {}", checksum);
+    println!("辞書のバージョン: {}", version);
+    println!("読み込み: {:.2}秒", load_secs);
+    Ok(())
+}
+
+fn compress(args: Args) -> CliResult<()> {
+    let [input, output] = args.positional()?;
+    let level = match args.option(&["-l", "--level"]) {
+        None => DEFAULT_COMPRESSION_LEVEL,
+        Some(level) => match level.parse::<i32>() {
+            Ok(level @ 1..=22) => level,
+            _ => return Err(format!("圧縮レベルは1〜22で指定してください: {}", level).into()),
+        },
+    };
+    // 圧縮済みの辞書は解凍してから指定のレベルで圧縮し直す
+    let raw = decompress(read_file(input)?)?;
+    format_version(&raw).ok_or("Vibratoの辞書ではありません")?;
+    let compressed = zstd::encode_all(raw.as_slice(), level)?;
+    fs::write(output, &compressed)?;
+    println!("圧縮完了: {} → {}（{} → {} バイト, レベル{}）", input, output, raw.len(), compressed.len(), level);
+    Ok(())
+}
+
+fn decompress_command(args: Args) -> CliResult<()> {
+    let [input, output] = args.positional()?;
+    let data = read_file(input)?;
+    let size = data.len();
+    let raw = decompress(data)?;
+    format_version(&raw).ok_or("Vibratoの辞書ではありません")?;
+    fs::write(output, &raw)?;
+    println!("解凍完了: {} → {}（{} → {} バイト）", input, output, size, raw.len());
+    Ok(())
+}
+
+fn verify(args: Args) -> CliResult<()> {
+    let [path] = args.positional()?;
+    let text = args.option(&["--text"]).unwrap_or(SMOKE_SENTENCE);
+    let started = Instant::now();
+    let dict = read_dictionary(&read_file(path)?)?;
+    let load_secs = started.elapsed().as_secs_f64();
+
+    let tokenizer = Tokenizer::new(dict);
+    let mut worker = tokenizer.new_worker();
+    worker.reset_sentence(text);
+    worker.tokenize();
+    println!("読み込み: {:.2}秒", load_secs);
+    for token in worker.token_iter() {
+        println!("{}\t{}", token.surface(), token.feature());
+    }
+    if worker.num_tokens() == 0 {
+        return Err("解析結果が空です".into());
+    }
+    let unknown = worker.token_iter().filter(|t| t.lex_type() == LexType::Unknown).count();
+    println!("OK: {}形態素（未知語 {}）", worker.num_tokens(), unknown);
+    Ok(())
+}
+
+fn lookup(args: Args) -> CliResult<()> {
+    let [path, surface] = args.positional()?;
+    let encoding: SourceEncoding = args.option(&["-e", "--encoding"]).unwrap_or("auto").parse()?;
+
+    let source_dir = args.option(&["--source"]).map(PathBuf::from);
+    match source_dir.or_else(|| Some(PathBuf::from(DEFAULT_SOURCE)).filter(|p| p.is_dir())) {
+        Some(dir) => {
+            let source = DictionarySource::from_dir(&dir, encoding)?;
+            let entries = source.lookup(surface);
+            println!("ソースの語彙（{}）: {}件", dir.display(), entries.len());
+            for entry in entries {
+                println!("  {}", entry);
+            }
+        }
+        None => println!("ソースの語彙: IPADICのソースがないため省略（--source で指定）"),
+    }
+
+    // 表層形だけを解析して、辞書がどの語を選ぶかを表示する
+    let tokenizer = Tokenizer::new(read_dictionary(&read_file(path)?)?);
+    let mut worker = tokenizer.new_worker();
+    worker.reset_sentence(surface);
+    worker.tokenize();
+    println!("辞書での解析（{}）:", path);
+    for token in worker.token_iter() {
+        let lex_type = match token.lex_type() {
+            LexType::System => "システム",
+            LexType::User => "ユーザー",
+            LexType::Unknown => "未知語",
+        };
+        println!(
+            "  {}\t{}\t左{} 右{} コスト{}\t{}",
+            token.surface(),
+            lex_type,
+            token.left_id(),
+            token.right_id(),
+            token.word_cost(),
+            token.feature()
+        );
+    }
+    Ok(())
+}
+
+fn run(command: &str, args: Vec<String>) -> CliResult<()> {
+    match command {
+        "inspect" => inspect(Args::parse(args, &["--source", "-e", "--encoding"])?),
+        "compress" => compress(Args::parse(args, &["-l", "--level"])?),
+        "decompress" => decompress_command(Args::parse(args, &[])?),
+        "verify" => verify(Args::parse(args, &["--text"])?),
+        "lookup" => lookup(Args::parse(args, &["--source", "-e", "--encoding"])?),
+        other => Err(format!("不明なサブコマンド: {}", other).into()),
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = match args.next() {
+        Some(command) if command != "-h" && command != "--help" => command,
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    if let Err(e) = run(&command, args.collect()) {
+        eprintln!("エラー: {}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
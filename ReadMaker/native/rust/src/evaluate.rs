@@ -0,0 +1,148 @@
+//! 分割の精度の評価
+//!
+//! 使用方法: cargo run --release --bin evaluate -- --corpus <FILE> [オプション]
+//! 正解の分割が付いたコーパス（MeCab形式）と解析結果を比べ、境界の適合率・再現率・F1、品詞と読みの正解率、
+//! 一致度の低い文を表示する。--dict を複数指定すると辞書ごとの結果を並べて比べられる
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use serde_json::json;
+use readmaker_core::dictionary_path;
+use readmaker_engine::annotated::AnnotatedCorpus;
+use readmaker_engine::eval::{evaluate, EvalOptions, Evaluation};
+use readmaker_engine::MorphAnalyzer;
+
+const DEFAULT_WORST: usize = 10;
+
+const USAGE: &str = "使用方法: evaluate --corpus <FILE> [オプション]
+
+  -c, --corpus <FILE>     正解の分割が付いたコーパス（MeCab形式。複数指定可）
+  -d, --dict <PATH>       辞書ファイル（複数指定可、既定: READMAKER_DIC_PATH または dictionaries/ipadic.vibrato）
+      --detailed-pos      品詞細分類まで一致したときだけ品詞を正解とする
+      --worst <N>         表示する一致度の低い文の数（既定: 10）
+  -f, --format <FORMAT>   出力形式 text・json（既定: text）
+  -h, --help              このヘルプを表示";
+
+struct Options {
+    corpora: Vec<PathBuf>,
+    dicts: Vec<PathBuf>,
+    eval: EvalOptions,
+    json: bool,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        corpora: Vec::new(),
+        dicts: Vec::new(),
+        eval: EvalOptions { max_worst: DEFAULT_WORST, ..EvalOptions::default() },
+        json: false,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-c" | "--corpus" => options.corpora.push(PathBuf::from(value()?)),
+            "-d" | "--dict" => options.dicts.push(PathBuf::from(value()?)),
+            "--detailed-pos" => options.eval.detailed_pos = true,
+            "--worst" => {
+                let n = value()?;
+                options.eval.max_worst = n.parse().map_err(|_| format!("--worst には整数を指定してください: {}", n))?;
+            }
+            "-f" | "--format" => {
+                options.json = match value()?.as_str() {
+                    "text" => false,
+                    "json" => true,
+                    other => return Err(format!("不明な出力形式: {}（text・json）", other)),
+                };
+            }
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    if options.corpora.is_empty() {
+        return Err("--corpus で正解の分割が付いたコーパスを指定してください".to_string());
+    }
+    if options.dicts.is_empty() {
+        options.dicts.push(PathBuf::from(dictionary_path()));
+    }
+    Ok(Some(options))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let mut corpus = AnnotatedCorpus::default();
+    for path in &options.corpora {
+        corpus.sentences.extend(AnnotatedCorpus::from_file(path)?.sentences);
+    }
+
+    for dict in &options.dicts {
+        // フォールバックの分割を評価しないよう、辞書が読めなければ終了する
+        let analyzer = MorphAnalyzer::from_path(dict)?;
+        let evaluation = evaluate(&analyzer, &corpus, &options.eval)?;
+        if options.json {
+            print_json(dict, analyzer.dictionary_version(), &evaluation)?;
+        } else {
+            print_text(dict, analyzer.dictionary_version(), &evaluation);
+        }
+    }
+    Ok(())
+}
+
+fn print_text(dict: &Path, version: &str, e: &Evaluation) {
+    let s = &e.segmentation;
+    println!("=== {}（バージョン {}） ===", dict.display(), version);
+    println!("文: {}, 正解 {}語, 解析 {}語, 境界の一致 {}語", e.sentences, s.gold, s.predicted, s.correct);
+    println!("境界: 適合率 {:.4}  再現率 {:.4}  F1 {:.4}", s.precision(), s.recall(), s.f1());
+    println!("品詞の正解率: {:.4}（{}/{}）", e.pos_accuracy(), e.pos_correct, s.correct);
+    println!("読みの正解率: {:.4}（{}/{}）", e.reading_accuracy(), e.reading_correct, e.reading_total);
+    if !e.worst.is_empty() {
+        println!("\n一致度の低い文:");
+        for w in &e.worst {
+            println!("  F1 {:.3}  {}", w.segmentation.f1(), w.text);
+            println!("    正解: {}", w.gold_surfaces.join(" | "));
+            println!("    解析: {}", w.predicted_surfaces.join(" | "));
+            for error in &w.tagging_errors {
+                let field = if error.field == "pos" { "品詞" } else { "読み" };
+                println!("    {}の{}: {} → {}", error.surface, field, error.expected, error.actual);
+            }
+        }
+    }
+    println!();
+}
+
+fn print_json(dict: &Path, version: &str, e: &Evaluation) -> Result<(), Box<dyn Error>> {
+    let s = &e.segmentation;
+    let value = json!({
+        "dictionary": dict.display().to_string(),
+        "version": version,
+        "sentences": e.sentences,
+        "gold": s.gold,
+        "predicted": s.predicted,
+        "correct": s.correct,
+        "precision": s.precision(),
+        "recall": s.recall(),
+        "f1": s.f1(),
+        "pos_accuracy": e.pos_accuracy(),
+        "reading_accuracy": e.reading_accuracy(),
+        "worst": e.worst.iter().map(|w| json!({
+            "text": w.text,
+            "f1": w.segmentation.f1(),
+            "gold": w.gold_surfaces,
+            "predicted": w.predicted_surfaces,
+            "tagging_errors": w.tagging_errors,
+        })).collect::<Vec<_>>(),
+    });
+    println!("{}", serde_json::to_string(&value)?);
+    Ok(())
+}
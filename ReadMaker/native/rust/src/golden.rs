@@ -0,0 +1,103 @@
+//! 分割のゴールデンテスト
+//!
+//! 使用方法: cargo run --release --bin golden -- [オプション]
+//! 保存した期待値（形態素の表層形・読み・品詞とまとまり）と現在の辞書での解析結果を比べ、
+//! 異なる文があれば終了コード1で終わる。意図した変更なら --bless で期待値を保存し直す
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use readmaker_core::dictionary_path;
+use readmaker_engine::golden::{bless_golden, check_golden, read_golden, read_inputs, write_golden};
+use readmaker_engine::MorphAnalyzer;
+
+const DEFAULT_GOLDEN: &str = "../../../backend/core/testdata/golden/ipadic.jsonl";
+const DEFAULT_INPUTS: &str = "../../../backend/core/testdata/golden/inputs.txt";
+
+const USAGE: &str = "使用方法: golden [オプション]
+
+  -d, --dict <PATH>     辞書ファイル（既定: READMAKER_DIC_PATH または dictionaries/ipadic.vibrato）
+  -g, --golden <FILE>   期待値のファイル（JSON Lines、既定: backend/core/testdata/golden/ipadic.jsonl）
+  -i, --inputs <FILE>   入力文（1行1文、既定: backend/core/testdata/golden/inputs.txt）
+      --bless           入力文を解析し直して期待値を保存する
+  -h, --help            このヘルプを表示";
+
+struct Options {
+    dict: PathBuf,
+    golden: PathBuf,
+    inputs: PathBuf,
+    bless: bool,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        dict: PathBuf::from(dictionary_path()),
+        golden: PathBuf::from(DEFAULT_GOLDEN),
+        inputs: PathBuf::from(DEFAULT_INPUTS),
+        bless: false,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-d" | "--dict" => options.dict = PathBuf::from(value()?),
+            "-g" | "--golden" => options.golden = PathBuf::from(value()?),
+            "-i" | "--inputs" => options.inputs = PathBuf::from(value()?),
+            "--bless" => options.bless = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // フォールバックの分割を期待値にしないよう、辞書が読めなければ終了する
+    let analyzer = MorphAnalyzer::from_path(&options.dict)?;
+    let inputs = read_inputs(
+        &fs::read_to_string(&options.inputs).map_err(|e| format!("入力文を読み込めません: {}: {}", options.inputs.display(), e))?,
+    );
+    println!("辞書: {}（バージョン {}）", options.dict.display(), analyzer.dictionary_version());
+
+    if options.bless {
+        let cases = bless_golden(&analyzer, &inputs)?;
+        fs::write(&options.golden, write_golden(&cases)?)?;
+        println!("💾 {}文の期待値を保存しました: {}", cases.len(), options.golden.display());
+        return Ok(());
+    }
+
+    let golden = fs::read_to_string(&options.golden).map_err(|e| {
+        format!("期待値を読み込めません（--bless で作成）: {}: {}", options.golden.display(), e)
+    })?;
+    let cases = read_golden(&golden)?;
+    let mismatches = check_golden(&analyzer, &cases)?;
+    for m in &mismatches {
+        println!("\n❌ {} [{}]", m.text, m.field);
+        println!("   期待: {}", m.expected);
+        println!("   結果: {}", m.actual);
+    }
+
+    let missing = inputs.iter().filter(|text| !cases.iter().any(|c| &c.text == *text)).count();
+    if missing > 0 {
+        println!("\n⚠️ 期待値のない入力文が{}文あります（--bless で追加）", missing);
+    }
+    if mismatches.is_empty() {
+        println!("✅ {}文すべて期待値と一致しました", cases.len());
+        Ok(())
+    } else {
+        println!("\n{}文中{}件が期待値と異なります（意図した変更なら --bless で保存し直す）", cases.len(), mismatches.len());
+        std::process::exit(1);
+    }
+}
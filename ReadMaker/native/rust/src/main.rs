@@ -1,117 +1,509 @@
-/// ReadMaker - 速読アプリ用形態素解析ネイティブモジュール
-/// Vibratoを用いた高速・高品質な形態素解析実装
-
-use std::fs::File;
-use std::env;
-use std::io;
-use serde_json;
-use vibrato::{Dictionary, Tokenizer};
-
-// ライブラリとしてもバイナリとしても使用可能
-pub fn main() {
-    println!("=== ReadMaker Core - Vibrato形態素解析エンジン ===");
-    
-    // Vibrato実装のテスト
-    let sample_texts = vec![
-        "これはテスト用の文章です。",
-        "任意の文章を処理できます。",
-    ];
-    
-    for (i, text) in sample_texts.iter().enumerate() {
-        println!("\n--- サンプル {} ---", i + 1);
-        println!("原文: {}", text);
-        
-        // Vibrato形態素解析を実行
-        match vibrato_analyze_text(text) {
-            Ok(words) => {
-                println!("Vibrato解析結果: {:?}", words);
-                
-                // JSON形式で返す（React Native側で使いやすく）
-                let json_result = words_to_json(&words);
-                println!("JSON出力: {}", json_result);
+//! ReadMaker - 読み物の下ごしらえ・確認用のコマンドラインツール
+//!
+//! 使用方法: cargo run --bin readmaker -- <サブコマンド> [FILE...] [オプション]
+//! ファイル（省略時・`-`は標準入力）を解析し、形態素・まとまり・ふりがな・統計・難易度・RSVPの表示スケジュールを
+//! テキスト・TSV・JSONで出力する
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use serde_json::json;
+use readmaker_core::dictionary_path;
+use readmaker_engine::segmenter::{script_of, Script};
+use readmaker_engine::{
+    analyze_report, estimate_difficulty, furigana, rsvp_schedule, rsvp_total_ms, ruby_segments, text_stats,
+    FallbackPolicy, MorphAnalyzer, Report, ReportOptions, RsvpOptions,
+};
+
+const USAGE: &str = "使用方法: readmaker <サブコマンド> [FILE...] [オプション]
+
+サブコマンド:
+  tokens        形態素（表層形・読み・品詞・原形）
+  chunks        表示用のまとまり
+  furigana      ふりがな（テキストでは青空文庫形式の ｜漢字《かんじ》）
+  stats         文字数・文数・推定読書時間など
+  difficulty    難易度（初級・中級・上級・超級）
+  rsvp          RSVP（1まとまりずつ表示）のスケジュール
+
+FILE を省略するか - を指定すると標準入力から読みます。
+
+オプション:
+  -f, --format <FMT>        出力形式 text・tsv・json（既定: text。json は入力ごとに1行）
+  -d, --dict <PATH>         辞書ファイル（既定: READMAKER_DIC_PATH または dictionaries/ipadic.vibrato）
+      --fallback <POLICY>   辞書が使えない場合 error・char_class・punctuation（既定: READMAKER_FALLBACK または error）
+  -s, --speed <CPM>         rsvp: 読む速さ（文字/分、既定: 300）
+      --clause-pause <MS>   rsvp: 読点の後の間（既定: 150）
+      --sentence-pause <MS> rsvp: 句点の後の間（既定: 300）
+      --min-frame <MS>      rsvp: 1まとまりの最短表示時間（既定: 120）
+  -h, --help                このヘルプを表示";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Tokens,
+    Chunks,
+    Furigana,
+    Stats,
+    Difficulty,
+    Rsvp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Tsv,
+    Json,
+}
+
+struct Options {
+    command: Command,
+    inputs: Vec<String>,
+    format: Format,
+    dict: PathBuf,
+    fallback: Option<FallbackPolicy>,
+    rsvp: RsvpOptions,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter();
+    let command = match args.next().as_deref() {
+        None | Some("-h" | "--help" | "help") => return Ok(None),
+        Some("tokens") => Command::Tokens,
+        Some("chunks") => Command::Chunks,
+        Some("furigana") => Command::Furigana,
+        Some("stats") => Command::Stats,
+        Some("difficulty") => Command::Difficulty,
+        Some("rsvp") => Command::Rsvp,
+        Some(other) => return Err(format!("不明なサブコマンド: {}", other)),
+    };
+    let mut options = Options {
+        command,
+        inputs: Vec::new(),
+        format: Format::Text,
+        dict: PathBuf::from(dictionary_path()),
+        fallback: None,
+        rsvp: RsvpOptions::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        let number = |name: &str, value: String| {
+            value.parse::<u64>().map_err(|_| format!("{} には0以上の整数を指定してください: {}", name, value))
+        };
+        match arg.as_str() {
+            "-f" | "--format" => {
+                options.format = match value()?.as_str() {
+                    "text" | "plain" => Format::Text,
+                    "tsv" => Format::Tsv,
+                    "json" => Format::Json,
+                    other => return Err(format!("不明な出力形式: {}（text・tsv・json）", other)),
+                }
             }
-            Err(e) => {
-                println!("⚠️ Vibrato解析エラー: {}", e);
-                println!("フォールバック実装を使用:");
-                
-                let words = analyze_text_fallback(text);
-                println!("フォールバック結果: {:?}", words);
-                
-                let json_result = words_to_json(&words);
-                println!("JSON出力: {}", json_result);
+            "-d" | "--dict" => options.dict = PathBuf::from(value()?),
+            "--fallback" => options.fallback = Some(value()?.parse()?),
+            "-s" | "--speed" => {
+                let speed = value()?;
+                options.rsvp.chars_per_minute = match speed.parse::<f64>() {
+                    Ok(s) if s > 0.0 => s,
+                    _ => return Err(format!("--speed には正の数を指定してください: {}", speed)),
+                };
             }
+            "--clause-pause" => options.rsvp.clause_pause_ms = number(&arg, value()?)?,
+            "--sentence-pause" => options.rsvp.sentence_pause_ms = number(&arg, value()?)?,
+            "--min-frame" => options.rsvp.min_frame_ms = number(&arg, value()?)?,
+            "-h" | "--help" => return Ok(None),
+            "-" => options.inputs.push(arg),
+            other if other.starts_with('-') => return Err(format!("不明なオプション: {}", other)),
+            _ => options.inputs.push(arg),
+        }
+    }
+    if options.inputs.is_empty() {
+        options.inputs.push("-".to_string());
+    }
+    Ok(Some(options))
+}
+
+fn read_input(input: &str) -> Result<String, Box<dyn Error>> {
+    if input == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        Ok(fs::read_to_string(input).map_err(|e| format!("読み込めません: {}: {}", input, e))?)
+    }
+}
+
+/// 辞書を読み込む（フォールバックが有効なら、読めなくても警告だけで続ける）
+fn load_analyzer(options: &Options, policy: FallbackPolicy) -> Result<Option<MorphAnalyzer>, Box<dyn Error>> {
+    match MorphAnalyzer::from_path(&options.dict) {
+        Ok(analyzer) => Ok(Some(analyzer)),
+        Err(e) if policy == FallbackPolicy::Error => Err(format!(
+            "{}\n辞書なしで実行する場合は --fallback char_class などを指定してください",
+            e
+        )
+        .into()),
+        Err(e) => {
+            eprintln!("⚠️ {}（{}で分割します）", e, policy_name(policy));
+            Ok(None)
         }
     }
-    
-    println!("\n=== Vibrato実装ステータス ===");
-    println!("✅ vibrato クレート追加完了");
-    println!("✅ IPADIC辞書ファイルダウンロード・展開完了");
-    println!("✅ vibrato_analyze_text()関数実装完了");
-    println!("⏳ TODO: パフォーマンス最適化");
-    println!("⏳ TODO: React Native bridge実装");
-    println!("⏳ TODO: WASM連携準備");
 }
 
-/// Vibrato実装の形態素解析関数
-fn vibrato_analyze_text(input: &str) -> Result<Vec<String>, io::Error> {
-    // 辞書ファイルパス（環境変数で上書き可能）
-    let dict_path = env::var("READMAKER_DIC_PATH")
-        .unwrap_or_else(|_| "dictionaries/ipadic-mecab-2_7_0/system.dic".to_string());
-    
-    // 辞書ファイルの読み込み
-    let dict_file = File::open(dict_path)?;
-    let dict = Dictionary::read(dict_file)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("辞書読み込みエラー: {}", e)))?;
-    
-    // トークナイザーの作成
-    let tokenizer = Tokenizer::new(dict);
-    let mut worker = tokenizer.new_worker();
-    
-    // 解析の実行
-    worker.reset_sentence(input);
-    worker.tokenize();
-    
-    // 結果の収集
-    let mut words = Vec::new();
-    for i in 0..worker.num_tokens() {
-        let token = worker.token(i);
-        words.push(token.surface().to_string());
-    }
-    
-    Ok(words)
+fn policy_name(policy: FallbackPolicy) -> &'static str {
+    match policy {
+        FallbackPolicy::Error => "error",
+        FallbackPolicy::CharClass => "char_class",
+        FallbackPolicy::Punctuation => "punctuation",
+    }
+}
+
+/// TSVの1項目（タブ・改行は空白にする）
+fn tsv(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+/// 1入力分の出力
+struct Output<'a> {
+    out: &'a mut dyn Write,
+    format: Format,
+    /// 複数の入力がある場合の入力名（TSVでは先頭の列、テキストでは見出しにする）
+    source: Option<&'a str>,
+    /// TSVの見出し行を出力済みか
+    header_written: &'a mut bool,
+}
+
+impl Output<'_> {
+    fn tsv_header(&mut self, columns: &[&str]) -> io::Result<()> {
+        if std::mem::replace(self.header_written, true) {
+            return Ok(());
+        }
+        let source = if self.source.is_some() { "source\t" } else { "" };
+        writeln!(self.out, "{}{}", source, columns.join("\t"))
+    }
+
+    fn tsv_row(&mut self, fields: &[String]) -> io::Result<()> {
+        let source = self.source.map(|s| format!("{}\t", tsv(s))).unwrap_or_default();
+        let fields: Vec<String> = fields.iter().map(|f| tsv(f)).collect();
+        writeln!(self.out, "{}{}", source, fields.join("\t"))
+    }
+
+    fn json(&mut self, mut value: serde_json::Value) -> io::Result<()> {
+        if let (Some(source), Some(object)) = (self.source, value.as_object_mut()) {
+            object.insert("source".to_string(), json!(source));
+        }
+        writeln!(self.out, "{}", value)
+    }
+}
+
+fn render(command: Command, text: &str, report: &Report, rsvp: &RsvpOptions, out: &mut Output) -> io::Result<()> {
+    let tokens = report.tokens.as_deref().unwrap_or_default();
+    let sentences = report.sentences.as_deref().unwrap_or_default();
+    let chunks = report.chunks.as_deref().unwrap_or_default();
+    let chars: Vec<char> = text.chars().collect();
+
+    match (command, out.format) {
+        (Command::Tokens, Format::Json) => out.json(json!({ "engine": report.engine, "tokens": tokens })),
+        (Command::Tokens, Format::Tsv) => {
+            out.tsv_header(&["surface", "reading", "pos", "pos_detail", "lemma", "start", "end"])?;
+            for t in tokens {
+                out.tsv_row(&[
+                    t.surface.clone(),
+                    t.reading.clone(),
+                    t.pos.clone(),
+                    t.pos_detail.join(","),
+                    t.lemma.clone(),
+                    t.start.to_string(),
+                    t.end.to_string(),
+                ])?;
+            }
+            Ok(())
+        }
+        // 1文を1行にして、形態素を「|」で区切る
+        (Command::Tokens, Format::Text) => {
+            for s in sentences {
+                let surfaces: Vec<&str> = tokens[s.token_start..s.token_end].iter().map(|t| t.surface.as_str()).collect();
+                writeln!(out.out, "{}", surfaces.join(" | "))?;
+            }
+            Ok(())
+        }
+
+        (Command::Chunks, Format::Json) => out.json(json!({ "engine": report.engine, "chunks": chunks })),
+        (Command::Chunks, Format::Tsv) => {
+            out.tsv_header(&["text", "start", "end"])?;
+            for c in chunks {
+                out.tsv_row(&[c.text.trim().to_string(), c.start.to_string(), c.end.to_string()])?;
+            }
+            Ok(())
+        }
+        // 1文を1行にして、まとまりを「/」で区切る（文の境界をまたぐまとまりは始まる文の行に出す）
+        (Command::Chunks, Format::Text) => {
+            let mut rest = chunks.iter().peekable();
+            for s in sentences {
+                let mut texts = Vec::new();
+                while let Some(c) = rest.next_if(|c| c.start < s.end) {
+                    let text = c.text.trim();
+                    if !text.is_empty() {
+                        texts.push(text);
+                    }
+                }
+                writeln!(out.out, "{}", texts.join(" / "))?;
+            }
+            Ok(())
+        }
+
+        (Command::Furigana, Format::Json) => out.json(json!({ "engine": report.engine, "ruby": furigana(tokens) })),
+        (Command::Furigana, Format::Tsv) => {
+            out.tsv_header(&["text", "reading", "start", "end"])?;
+            for r in furigana(tokens) {
+                let base: String = chars[r.start..r.end].iter().collect();
+                out.tsv_row(&[base, r.reading, r.start.to_string(), r.end.to_string()])?;
+            }
+            Ok(())
+        }
+        (Command::Furigana, Format::Text) => {
+            let mut annotated = String::new();
+            for segment in ruby_segments(text, &furigana(tokens)) {
+                match segment.reading {
+                    Some(reading) => {
+                        // 直前が漢字のときと、親文字が漢字だけでないときは開始位置を「｜」で示す
+                        let after_kanji = annotated.chars().last().is_some_and(|c| script_of(c) == Script::Kanji);
+                        let all_kanji = segment.text.chars().all(|c| script_of(c) == Script::Kanji);
+                        if after_kanji || !all_kanji {
+                            annotated.push('｜');
+                        }
+                        annotated.push_str(&format!("{}《{}》", segment.text, reading));
+                    }
+                    None => annotated.push_str(&segment.text),
+                }
+            }
+            writeln!(out.out, "{}", annotated.trim_end())
+        }
+
+        (Command::Stats, format) => {
+            let stats = text_stats(text);
+            let fields = [
+                ("chars", json!(chars.iter().filter(|c| !c.is_whitespace()).count()), "文字数（空白を除く）"),
+                ("japanese_chars", json!(stats.japanese_chars), "日本語の文字数"),
+                ("english_words", json!(stats.english_words), "英単語数"),
+                ("sentences", json!(sentences.len()), "文数"),
+                ("tokens", json!(tokens.len()), "形態素数"),
+                ("chunks", json!(chunks.len()), "まとまり数"),
+                ("reading_time_minutes", json!(stats.reading_time_minutes()), "推定読書時間（分）"),
+            ];
+            write_fields(out, format, report, &fields)
+        }
+
+        (Command::Difficulty, format) => {
+            let d = estimate_difficulty(text, sentences);
+            let fields = [
+                ("level", json!(d.level), "難易度"),
+                ("label", json!(d.level.label()), "表示名"),
+                ("score", json!(d.score), "点数（0〜100）"),
+                ("chars", json!(d.chars), "本文の文字数"),
+                ("sentences", json!(d.sentences), "文数"),
+                ("kanji_ratio", json!(d.kanji_ratio), "漢字の割合"),
+                ("avg_sentence_chars", json!(d.avg_sentence_chars), "1文の平均文字数"),
+            ];
+            write_fields(out, format, report, &fields)
+        }
+
+        (Command::Rsvp, format) => {
+            let frames = rsvp_schedule(chunks, rsvp);
+            let total_ms = rsvp_total_ms(&frames);
+            match format {
+                Format::Json => out.json(json!({
+                    "engine": report.engine,
+                    "chars_per_minute": rsvp.chars_per_minute,
+                    "total_ms": total_ms,
+                    "frames": frames,
+                })),
+                Format::Tsv => {
+                    out.tsv_header(&["index", "start_ms", "duration_ms", "text"])?;
+                    for (i, f) in frames.iter().enumerate() {
+                        out.tsv_row(&[i.to_string(), f.start_ms.to_string(), f.duration_ms.to_string(), f.text.clone()])?;
+                    }
+                    Ok(())
+                }
+                Format::Text => {
+                    for f in &frames {
+                        writeln!(out.out, "{:>8}ms {:>6}ms  {}", f.start_ms, f.duration_ms, f.text)?;
+                    }
+                    writeln!(
+                        out.out,
+                        "合計 {:.1}秒（{}まとまり, {}文字/分）",
+                        total_ms as f64 / 1000.0,
+                        frames.len(),
+                        rsvp.chars_per_minute
+                    )
+                }
+            }
+        }
+    }
 }
 
-/// 現在のフォールバック実装（辞書準備まで使用）
-fn analyze_text_fallback(text: &str) -> Vec<String> {
-    // 句読点・空白で分割（簡易フォールバック）
-    let is_sep = |c: char| c.is_whitespace() || matches!(c, '。' | '、' | '！' | '？' | '\n' | '\r');
-    let mut parts = Vec::new();
-    let mut buf = String::new();
-    for ch in text.chars() {
-        if is_sep(ch) {
-            if !buf.is_empty() {
-                parts.push(std::mem::take(&mut buf));
+/// 項目名・値・表示名の組を出力する（stats・difficulty）
+fn write_fields(
+    out: &mut Output,
+    format: Format,
+    report: &Report,
+    fields: &[(&str, serde_json::Value, &str)],
+) -> io::Result<()> {
+    let value_text = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => match n.as_f64() {
+            Some(f) if !n.is_u64() => format!("{:.2}", f),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    };
+    match format {
+        Format::Json => {
+            let mut object = serde_json::Map::new();
+            object.insert("engine".to_string(), json!(report.engine));
+            for (key, value, _) in fields {
+                object.insert(key.to_string(), value.clone());
             }
-        } else {
-            buf.push(ch);
+            out.json(serde_json::Value::Object(object))
+        }
+        Format::Tsv => {
+            out.tsv_header(&["key", "value"])?;
+            for (key, value, _) in fields {
+                out.tsv_row(&[key.to_string(), value_text(value)])?;
+            }
+            Ok(())
+        }
+        Format::Text => {
+            for (_, value, label) in fields {
+                writeln!(out.out, "{}: {}", label, value_text(value))?;
+            }
+            Ok(())
         }
     }
-    if !buf.is_empty() {
-        parts.push(buf);
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let policy = match options.fallback {
+        Some(policy) => policy,
+        None => FallbackPolicy::from_env()?,
+    };
+    let analyzer = load_analyzer(&options, policy)?;
+    let report_options = ReportOptions { tokens: true, sentences: true, chunks: true, fallback: policy };
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut header_written = false;
+    let multiple = options.inputs.len() > 1;
+    for (i, input) in options.inputs.iter().enumerate() {
+        let text = read_input(input)?;
+        let report = analyze_report(analyzer.as_ref(), &text, &report_options)?;
+        if multiple && options.format == Format::Text {
+            if i > 0 {
+                writeln!(stdout)?;
+            }
+            writeln!(stdout, "==> {} <==", input)?;
+        }
+        let mut out = Output {
+            out: &mut stdout,
+            format: options.format,
+            source: multiple.then_some(input.as_str()),
+            header_written: &mut header_written,
+        };
+        render(options.command, &text, &report, &options.rsvp, &mut out)?;
     }
-    parts
+    stdout.flush()?;
+    Ok(())
 }
 
-/// 形態素解析結果をJSON形式で返す（React Native用）
-pub fn words_to_json(words: &[String]) -> String {
-    serde_json::to_string(words).unwrap_or_else(|_| "[]".to_string())
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(options) {
+        // 出力先が閉じられた場合（| head など）は正常終了とする
+        if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) {
+            return;
+        }
+        eprintln!("エラー: {}", e);
+        std::process::exit(1);
+    }
 }
 
-/// 公開API: 形態素解析関数（Vibrato実装）
-pub fn analyze_text(input: &str) -> Vec<String> {
-    // Vibrato実装を試し、エラー時はフォールバック
-    match vibrato_analyze_text(input) {
-        Ok(words) => words,
-        Err(_) => analyze_text_fallback(input),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use readmaker_engine::report::Chunk;
+    use readmaker_engine::Token;
+
+    fn report(text: &str) -> Report {
+        let options = ReportOptions { tokens: true, sentences: true, chunks: true, fallback: FallbackPolicy::CharClass };
+        analyze_report(None, text, &options).unwrap()
+    }
+
+    /// `inputs`（入力名と本文の組）を順に出力する（入力が複数なら入力名を付ける）
+    fn render_all(command: Command, format: Format, inputs: &[(&str, &str, Report)]) -> String {
+        let mut buffer = Vec::new();
+        let mut header_written = false;
+        for (source, text, report) in inputs {
+            let mut out = Output {
+                out: &mut buffer,
+                format,
+                source: (inputs.len() > 1).then_some(*source),
+                header_written: &mut header_written,
+            };
+            render(command, text, report, &RsvpOptions::default(), &mut out).unwrap();
+        }
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_chunk_text_crossing_sentence_boundary() {
+        let text = "一文目です。二文目です。";
+        let mut report = report(text);
+        let chunk = |text: &str, start: usize, end: usize| Chunk {
+            text: text.to_string(),
+            start,
+            end,
+            token_start: 0,
+            token_end: 0,
+        };
+        // 2つ目のまとまりは文の境界（6文字目）をまたぐ
+        report.chunks = Some(vec![chunk("一文目", 0, 3), chunk("です。二文目", 3, 9), chunk("です。", 9, 12)]);
+        let output = render_all(Command::Chunks, Format::Text, &[("a", text, report)]);
+        assert_eq!(output, "一文目 / です。二文目\nです。\n");
+    }
+
+    #[test]
+    fn test_tsv_header_written_once() {
+        let inputs = [("a.txt", "今日は晴れ。", report("今日は晴れ。")), ("b.txt", "猫です。", report("猫です。"))];
+        let output = render_all(Command::Chunks, Format::Tsv, &inputs);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "source\ttext\tstart\tend");
+        assert_eq!(lines.iter().filter(|l| l.ends_with("\tstart\tend")).count(), 1);
+        assert!(lines[1].starts_with("a.txt\t"));
+        assert!(lines.last().unwrap().starts_with("b.txt\t"));
+    }
+
+    #[test]
+    fn test_furigana_text_marks_ruby_start() {
+        let text = "山田猫花子は山へ";
+        let mut report = report(text);
+        report.tokens = Some(vec![
+            Token::from_feature("山田", "名詞,固有名詞,人名,姓,*,*,山田,ヤマダ,ヤマダ", 0, 2),
+            Token::plain("猫", 2, 3),
+            Token::from_feature("花子", "名詞,固有名詞,人名,名,*,*,花子,ハナコ,ハナコ", 3, 5),
+            Token::from_feature("は", "助詞,係助詞,*,*,*,*,は,ハ,ワ", 5, 6),
+            Token::from_feature("山", "名詞,一般,*,*,*,*,山,ヤマ,ヤマ", 6, 7),
+            Token::from_feature("へ", "助詞,格助詞,一般,*,*,*,へ,ヘ,エ", 7, 8),
+        ]);
+        let output = render_all(Command::Furigana, Format::Text, &[("a", text, report)]);
+        // ルビのない漢字の直後だけ開始位置を「｜」で示す（文頭・ルビの直後・かなの直後には付けない）
+        assert_eq!(output, "山田《やまだ》猫｜花子《はなこ》は山《やま》へ\n");
     }
 }
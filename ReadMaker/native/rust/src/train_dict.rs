@@ -0,0 +1,189 @@
+//! 分野に合わせてコストを学習し直したVibrato辞書を作るツール
+//!
+//! 使用方法: cargo run --release --features train --bin train_dict -- --corpus <FILE> [オプション]
+//! 形態素解析済みのコーパス（MeCab形式）で語彙のコストと連接コストを学習し直し、
+//! 学習に使わなかった評価用の文で学習前後の分割のF1・品詞と読みの正解率を比べる
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use readmaker_engine::annotated::AnnotatedCorpus;
+use readmaker_engine::compile::{write_dictionary, DictionarySource, SourceEncoding, DEFAULT_COMPRESSION_LEVEL};
+use readmaker_engine::eval::{EvalOptions, Evaluation};
+use readmaker_engine::train::{train_and_evaluate, TrainOptions};
+
+const DEFAULT_INPUT: &str = "dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801";
+const DEFAULT_OUTPUT: &str = "dictionaries/ipadic-trained.vibrato";
+const DEFAULT_HELD_OUT: f64 = 0.1;
+/// MeCabと同じ未知語のまとめ方にする場合の最大文字数
+const MECAB_MAX_GROUPING_LEN: usize = 24;
+
+const USAGE: &str = "使用方法: train_dict --corpus <FILE> [オプション]
+
+  -c, --corpus <FILE>         形態素解析済みのコーパス（MeCab形式。複数指定可）
+  -i, --input <DIR>           元にするIPADICのソースのディレクトリ
+                              （既定: dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801）
+  -o, --output <PATH>         出力先（既定: dictionaries/ipadic-trained.vibrato）
+  -l, --level <N>             zstdの圧縮レベル 1〜22（既定: 19）
+      --no-compress           圧縮せずに書き出す
+  -e, --encoding <ENC>        ソースの文字コード auto・utf-8・euc-jp（既定: auto）
+      --held-out <RATIO>      評価用に取り分ける文の割合（既定: 0.1）
+      --max-iter <N>          学習の最大反復回数（既定: 100）
+      --regularization <C>    L1正則化の強さ（既定: 0.01）
+  -j, --threads <N>           学習のスレッド数（既定: CPUの数）
+      --feature-def <FILE>    素性テンプレート（既定: backend/core/training/feature.def）
+      --rewrite-def <FILE>    素性の書き換え規則（既定: backend/core/training/rewrite.def）
+      --mecab-compat          未知語を24文字までにまとめる（MeCabと同じ）
+  -h, --help                  このヘルプを表示";
+
+struct Options {
+    corpora: Vec<PathBuf>,
+    input: PathBuf,
+    output: PathBuf,
+    level: Option<i32>,
+    encoding: SourceEncoding,
+    held_out: f64,
+    train: TrainOptions,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        corpora: Vec::new(),
+        input: PathBuf::from(DEFAULT_INPUT),
+        output: PathBuf::from(DEFAULT_OUTPUT),
+        level: Some(DEFAULT_COMPRESSION_LEVEL),
+        encoding: SourceEncoding::Auto,
+        held_out: DEFAULT_HELD_OUT,
+        train: TrainOptions {
+            num_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            ..TrainOptions::default()
+        },
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-c" | "--corpus" => options.corpora.push(PathBuf::from(value()?)),
+            "-i" | "--input" => options.input = PathBuf::from(value()?),
+            "-o" | "--output" => options.output = PathBuf::from(value()?),
+            "-l" | "--level" => {
+                let level = value()?;
+                match level.parse::<i32>() {
+                    Ok(level @ 1..=22) => options.level = Some(level),
+                    _ => return Err(format!("圧縮レベルは1〜22で指定してください: {}", level)),
+                }
+            }
+            "--no-compress" => options.level = None,
+            "-e" | "--encoding" => options.encoding = value()?.parse()?,
+            "--held-out" => {
+                let ratio = value()?;
+                options.held_out = match ratio.parse::<f64>() {
+                    Ok(r) if (0.0..1.0).contains(&r) => r,
+                    _ => return Err(format!("--held-out には0以上1未満の数を指定してください: {}", ratio)),
+                };
+            }
+            "--max-iter" => {
+                let n = value()?;
+                options.train.max_iter = match n.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("--max-iter には1以上の整数を指定してください: {}", n)),
+                };
+            }
+            "--regularization" => {
+                let c = value()?;
+                options.train.regularization_cost = match c.parse::<f64>() {
+                    Ok(c) if c >= 0.0 => c,
+                    _ => return Err(format!("--regularization には0以上の数を指定してください: {}", c)),
+                };
+            }
+            "-j" | "--threads" => {
+                let n = value()?;
+                options.train.num_threads = match n.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("--threads には1以上の整数を指定してください: {}", n)),
+                };
+            }
+            "--feature-def" => {
+                let path = value()?;
+                options.train.feature_def =
+                    fs::read_to_string(&path).map_err(|e| format!("素性テンプレートを読み込めません: {}: {}", path, e))?;
+            }
+            "--rewrite-def" => {
+                let path = value()?;
+                options.train.rewrite_def =
+                    fs::read_to_string(&path).map_err(|e| format!("書き換え規則を読み込めません: {}: {}", path, e))?;
+            }
+            "--mecab-compat" => options.train.max_grouping_len = Some(MECAB_MAX_GROUPING_LEN),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    if options.corpora.is_empty() {
+        return Err("--corpus で形態素解析済みのコーパスを指定してください".to_string());
+    }
+    Ok(Some(options))
+}
+
+fn print_score(label: &str, evaluation: &Evaluation) {
+    let score = &evaluation.segmentation;
+    println!(
+        "  {}: F1 {:.4}（適合率 {:.4}, 再現率 {:.4}）, 品詞 {:.4}, 読み {:.4}",
+        label,
+        score.f1(),
+        score.precision(),
+        score.recall(),
+        evaluation.pos_accuracy(),
+        evaluation.reading_accuracy()
+    );
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let started = Instant::now();
+    let mut corpus = AnnotatedCorpus::default();
+    for path in &options.corpora {
+        corpus.sentences.extend(AnnotatedCorpus::from_file(path)?.sentences);
+    }
+    println!("コーパス: {}文, {}語", corpus.sentences.len(), corpus.words());
+
+    println!("読み込み中: {}（文字コード: {}）", options.input.display(), options.encoding);
+    let base = DictionarySource::from_dir(&options.input, options.encoding)?;
+
+    // 学習の進み具合はvibratoが標準エラー出力に表示する
+    println!("学習中（最大{}回, スレッド{}）...", options.train.max_iter, options.train.num_threads);
+    let (trained, report) = train_and_evaluate(&base, &corpus, options.held_out, &options.train, &EvalOptions::default())?;
+
+    let mut bytes = Vec::new();
+    write_dictionary(trained.dictionary().ok_or("学習した辞書がありません")?, &mut bytes, options.level)?;
+    if let Some(parent) = options.output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&options.output, &bytes)?;
+
+    println!("\n== 評価（学習 {}文, 評価用 {}文） ==", report.train_sentences, report.held_out_sentences);
+    if report.held_out_sentences == 0 {
+        println!("  評価用の文がありません（--held-out を0より大きくする）");
+    } else {
+        print_score("学習前", &report.before);
+        print_score("学習後", &report.after);
+    }
+    println!(
+        "\n完了: {}（{:.1} MB, {:.1}秒）",
+        options.output.display(),
+        bytes.len() as f64 / (1024.0 * 1024.0),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! モバイル向けに縮小したVibrato辞書を作るツール
+//!
+//! 使用方法: cargo run --release --features build-tools --bin trim_dict -- [オプション]
+//! IPADICのソースから縮小前・縮小後の辞書を組み立て、サイズとサンプルコーパスでの分割の違いを表示する
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use readmaker_engine::compile::{write_dictionary, DictionarySource, SourceEncoding, DEFAULT_COMPRESSION_LEVEL};
+use readmaker_engine::trim::{segmentation_diff, word_frequencies, TrimOptions};
+use readmaker_engine::MorphAnalyzer;
+
+const DEFAULT_INPUT: &str = "dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801";
+const DEFAULT_OUTPUT: &str = "dictionaries/ipadic-trimmed.vibrato";
+const DEFAULT_CORPUS: &str = "../../../backend/core/testdata/corpus/sample.txt";
+const DEFAULT_EXAMPLES: usize = 10;
+
+const USAGE: &str = "使用方法: trim_dict [オプション]
+
+  -i, --input <DIR>           IPADICのソースのディレクトリ
+                              （既定: dictionaries/mecab-ipadic-2.7.0-20070801/mecab-ipadic-2.7.0-20070801）
+  -o, --output <PATH>         出力先（既定: dictionaries/ipadic-trimmed.vibrato）
+  -l, --level <N>             zstdの圧縮レベル 1〜22（既定: 19）
+      --no-compress           圧縮せずに書き出す
+  -e, --encoding <ENC>        ソースの文字コード auto・utf-8・euc-jp（既定: auto）
+      --keep-features         素性の列を落とさない（活用型・活用形・発音も残す）
+      --min-frequency <N>     頻度コーパスでの出現がN回未満の語を除く（付属語・記号は残す）
+      --frequency-corpus <F>  頻度を数えるコーパス（1行1文、既定: --corpus と同じ）
+      --corpus <FILE>         分割の違いを確かめるサンプルコーパス（1行1文）
+                              （既定: backend/core/testdata/corpus/sample.txt）
+      --examples <N>          表示する分割の違いの例の数（既定: 10）
+  -h, --help                  このヘルプを表示";
+
+struct Options {
+    input: PathBuf,
+    output: PathBuf,
+    level: Option<i32>,
+    encoding: SourceEncoding,
+    trim: TrimOptions,
+    frequency_corpus: Option<PathBuf>,
+    corpus: PathBuf,
+    examples: usize,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        input: PathBuf::from(DEFAULT_INPUT),
+        output: PathBuf::from(DEFAULT_OUTPUT),
+        level: Some(DEFAULT_COMPRESSION_LEVEL),
+        encoding: SourceEncoding::Auto,
+        trim: TrimOptions { drop_features: true, min_frequency: None },
+        frequency_corpus: None,
+        corpus: PathBuf::from(DEFAULT_CORPUS),
+        examples: DEFAULT_EXAMPLES,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-i" | "--input" => options.input = PathBuf::from(value()?),
+            "-o" | "--output" => options.output = PathBuf::from(value()?),
+            "-l" | "--level" => {
+                let level = value()?;
+                match level.parse::<i32>() {
+                    Ok(level @ 1..=22) => options.level = Some(level),
+                    _ => return Err(format!("圧縮レベルは1〜22で指定してください: {}", level)),
+                }
+            }
+            "--no-compress" => options.level = None,
+            "-e" | "--encoding" => options.encoding = value()?.parse()?,
+            "--keep-features" => options.trim.drop_features = false,
+            "--min-frequency" => {
+                let min = value()?;
+                options.trim.min_frequency =
+                    Some(min.parse().map_err(|_| format!("--min-frequency には整数を指定してください: {}", min))?);
+            }
+            "--frequency-corpus" => options.frequency_corpus = Some(PathBuf::from(value()?)),
+            "--corpus" => options.corpus = PathBuf::from(value()?),
+            "--examples" => {
+                let examples = value()?;
+                options.examples =
+                    examples.parse().map_err(|_| format!("--examples には整数を指定してください: {}", examples))?;
+            }
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn compile(source: &DictionarySource, level: Option<i32>) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    write_dictionary(&source.build()?, &mut bytes, level)?;
+    Ok(bytes)
+}
+
+fn megabytes(size: usize) -> f64 {
+    size as f64 / (1024.0 * 1024.0)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let started = Instant::now();
+    let corpus = fs::read_to_string(&options.corpus)
+        .map_err(|e| format!("サンプルコーパスを読み込めません: {}: {}", options.corpus.display(), e))?;
+
+    println!("読み込み中: {}（文字コード: {}）", options.input.display(), options.encoding);
+    let source = DictionarySource::from_dir(&options.input, options.encoding)?;
+    println!("縮小前の辞書を組み立て中...");
+    let full_bytes = compile(&source, options.level)?;
+    let full = MorphAnalyzer::from_bytes(&full_bytes)?;
+
+    let frequencies = match (options.trim.min_frequency, &options.frequency_corpus) {
+        (None, _) => Default::default(),
+        (Some(_), None) => word_frequencies(&full, &corpus)?,
+        (Some(_), Some(path)) => {
+            let text = fs::read_to_string(path)
+                .map_err(|e| format!("頻度コーパスを読み込めません: {}: {}", path.display(), e))?;
+            word_frequencies(&full, &text)?
+        }
+    };
+    let (trimmed, stats) = source.trim(&options.trim, &frequencies);
+    println!("縮小後の辞書を組み立て中...");
+    let trimmed_bytes = compile(&trimmed, options.level)?;
+    let diff = segmentation_diff(&full, &MorphAnalyzer::from_bytes(&trimmed_bytes)?, &corpus, options.examples)?;
+
+    if let Some(parent) = options.output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&options.output, &trimmed_bytes)?;
+
+    let compression = match options.level {
+        Some(level) => format!("zstd レベル{}", level),
+        None => "非圧縮".to_string(),
+    };
+    println!("\n== サイズ（{}） ==", compression);
+    println!(
+        "  {:.2} MB → {:.2} MB（{:.1}%削減）",
+        megabytes(full_bytes.len()),
+        megabytes(trimmed_bytes.len()),
+        100.0 * (1.0 - trimmed_bytes.len() as f64 / full_bytes.len().max(1) as f64)
+    );
+    println!("  語彙: {}語 → {}語", stats.entries_before, stats.entries_after);
+    println!("    低頻度で除外: {}語, 重複を統合: {}語", stats.removed_rare, stats.removed_duplicates);
+
+    println!("\n== 分割の違い（{}） ==", options.corpus.display());
+    println!(
+        "  分割が変わった文: {}/{}（{:.1}%）, 読み・品詞のみ変わった文: {}",
+        diff.changed,
+        diff.sentences,
+        100.0 * diff.changed_rate(),
+        diff.retagged
+    );
+    println!("  形態素数: {} → {}", diff.tokens_before, diff.tokens_after);
+    for example in &diff.examples {
+        println!("  - {}", example.sentence);
+        println!("      前: {}", example.before.join(" / "));
+        println!("      後: {}", example.after.join(" / "));
+    }
+
+    println!("\n完了: {}（{:.1}秒）", options.output.display(), started.elapsed().as_secs_f64());
+    Ok(())
+}
@@ -1,52 +1,100 @@
-/// C FFI Bridge詳細テスト用バイナリ（直接Rust関数を呼ぶ安全版）
-use serde_json;
-use readmaker_core::{analyze_text, words_to_json};
-
-fn main() {
-    println!("=== React Native Bridge - 直接呼び出しテスト ===\n");
-    println!("📡 テスト1: ライブラリ関数の基本動作確認");
-    let hello = analyze_text("今日は良い天気です。", &[]);
-    println!("✅ 結果: {}", words_to_json(&hello));
-    
-    // テスト2: 基本的な形態素解析
-    println!("\n🔍 テスト2: 基本的な形態素解析");
-    let test_inputs = vec![
-        "今日は晴れです。",
-        "学問のすすめ",
-        "吾輩は猫である。",
-        "React Nativeは素晴らしい。",
-    ];
-    
-    for (i, input) in test_inputs.iter().enumerate() {
-        println!("\n--- テスト2-{} ---", i + 1);
-        println!("入力: {}", input);
-        
-        let words = analyze_text(input, &[]);
-        let result_str = words_to_json(&words);
-        println!("✅ JSON結果: {}", result_str);
-        // JSONパース確認
-        if let Ok(words) = serde_json::from_str::<Vec<String>>(&result_str) {
-            println!("📋 解析語数: {}語", words.len());
-            println!("📋 単語リスト: {:?}", words);
-        } else {
-            println!("⚠️ JSONパースエラー");
+//! React Nativeブリッジの確認
+//!
+//! 使用方法: cargo run --release --bin test_bridge -- [オプション]
+//! ブリッジと同じ経路（辞書のバイト列 → `analyze_text` → JSON）で分割のゴールデンテストの入力文を解析し、
+//! 結果の形式（`{"engine","words"}`）と表層形が期待値と一致するか確かめる。異なる文があれば終了コード1で終わる
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use serde_json::Value;
+use readmaker_core::{analysis_to_json, analyze_text, dictionary_path, Engine, FallbackPolicy};
+use readmaker_engine::golden::read_golden;
+
+const DEFAULT_GOLDEN: &str = "../../../backend/core/testdata/golden/ipadic.jsonl";
+
+const USAGE: &str = "使用方法: test_bridge [オプション]
+
+  -d, --dict <PATH>     辞書ファイル（既定: READMAKER_DIC_PATH または dictionaries/ipadic.vibrato）
+  -g, --golden <FILE>   期待値のファイル（golden の出力、既定: backend/core/testdata/golden/ipadic.jsonl）
+  -h, --help            このヘルプを表示";
+
+struct Options {
+    dict: PathBuf,
+    golden: PathBuf,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options { dict: PathBuf::from(dictionary_path()), golden: PathBuf::from(DEFAULT_GOLDEN) };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} に値がありません", arg));
+        match arg.as_str() {
+            "-d" | "--dict" => options.dict = PathBuf::from(value()?),
+            "-g" | "--golden" => options.golden = PathBuf::from(value()?),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("不明なオプション: {}", other)),
         }
     }
-    
-    // テスト3: エラーハンドリング
-    println!("\n🛡️ テスト3: エラーハンドリング");
-    
-    // 空文字列テスト
-    println!("--- 空文字列テスト ---");
-    let empty_words = analyze_text("", &[]);
-    println!("✅ 空文字列結果: {}", words_to_json(&empty_words));
-    
-    // 長文テスト
-    println!("\n--- 長文テスト ---");
-    let long_input = "これは長い文章のテストです。形態素解析エンジンが長文に対してどのような動作をするかを確認します。パフォーマンスとメモリ使用量を観察することが重要です。";
-    let words = analyze_text(long_input, &[]);
-    println!("✅ 長文解析成功: {}語に分割", words.len());
-    println!("📋 最初の10語: {:?}", &words[..std::cmp::min(10, words.len())]);
-    
-    println!("\n=== C FFI Bridge テスト完了 ===");
+    Ok(Some(options))
+}
+
+/// ブリッジが返すJSONを解析して、期待する表層形と比べる（異なれば理由を返す）
+fn check(text: &str, dictionary_data: &[u8], expected: &[String]) -> Result<(), String> {
+    // フォールバックの結果を期待値と比べないよう、辞書が使えなければエラーにする
+    let analysis = analyze_text(text, dictionary_data, FallbackPolicy::Error).map_err(|e| e.to_string())?;
+    let json: Value = serde_json::from_str(&analysis_to_json(&analysis)).map_err(|e| format!("JSONとして読めません: {}", e))?;
+    if json["engine"] != Engine::Vibrato.as_str() {
+        return Err(format!("エンジンが異なります: {}", json["engine"]));
+    }
+    let words: Vec<String> =
+        serde_json::from_value(json["words"].clone()).map_err(|e| format!("wordsが文字列の配列ではありません: {}", e))?;
+    if words != expected {
+        return Err(format!("期待: {}\n   結果: {}", expected.join(" | "), words.join(" | ")));
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let dictionary_data =
+        fs::read(&options.dict).map_err(|e| format!("辞書を読み込めません: {}: {}", options.dict.display(), e))?;
+    let golden = fs::read_to_string(&options.golden).map_err(|e| {
+        format!("期待値を読み込めません（golden --bless で作成）: {}: {}", options.golden.display(), e)
+    })?;
+    let cases = read_golden(&golden)?;
+
+    let mut failures = 0;
+    for case in &cases {
+        let expected: Vec<String> = case.tokens.iter().map(|t| t.0.clone()).collect();
+        if let Err(reason) = check(&case.text, &dictionary_data, &expected) {
+            println!("\n❌ {}\n   {}", case.text, reason);
+            failures += 1;
+        }
+    }
+
+    // 空文字列は辞書があっても空の配列になる
+    if let Err(reason) = check("", &dictionary_data, &[]) {
+        println!("\n❌ （空文字列）\n   {}", reason);
+        failures += 1;
+    }
+
+    if failures == 0 {
+        println!("✅ {}文すべてブリッジの結果が期待値と一致しました", cases.len());
+        Ok(())
+    } else {
+        println!("\n{}文中{}件がブリッジの結果と異なります", cases.len() + 1, failures);
+        std::process::exit(1);
+    }
 }
@@ -1,27 +1,35 @@
-/// 統合テスト - ライブラリとして動作確認
-use readmaker_core::{analyze_text, words_to_json};
+//! 統合テスト - ライブラリとして動作確認
+use std::ffi::{CStr, CString};
+use std::ptr;
+use readmaker_core::{
+    analyze_text, rm_analyzer_analyze_report, rm_free_string, rm_report_schema_version, words_to_json, Engine,
+    FallbackPolicy, RmStatus, REPORT_SCHEMA_VERSION,
+};
 
 #[test]
 fn test_integrated_analysis() {
     println!("=== 統合テスト: 形態素解析 ===");
-    
-    let test_cases = vec![
+
+    let test_cases = [
         "今日は晴れです。",
         "学問のすすめ",
         "吾輩は猫である。",
         "これはテストです。",
     ];
-    
+
     for (i, input) in test_cases.iter().enumerate() {
         println!("\nテスト {}: {}", i + 1, input);
-        
-        let words = analyze_text(input);
-        let json_output = words_to_json(&words);
-        
+
+        // 辞書なしのため文字種フォールバックで解析される
+        let analysis = analyze_text(input, &[], FallbackPolicy::CharClass).unwrap();
+        let words = &analysis.words;
+        let json_output = words_to_json(words);
+
         println!("  形態素: {:?}", words);
         println!("  JSON: {}", json_output);
         println!("  語数: {}", words.len());
-        
+
+        assert_eq!(analysis.engine, Engine::CharClass);
         assert!(!words.is_empty(), "形態素解析結果が空です");
         assert!(json_output.starts_with('['), "JSON形式が正しくありません");
         assert!(json_output.ends_with(']'), "JSON形式が正しくありません");
@@ -31,10 +39,10 @@ fn test_integrated_analysis() {
 #[test]
 fn test_empty_input() {
     println!("=== 空文字列テスト ===");
-    
-    let words = analyze_text("");
+
+    let words = analyze_text("", &[], FallbackPolicy::CharClass).unwrap().words;
     println!("空文字列の結果: {:?}", words);
-    
+
     // 空文字列の場合、空配列または1文字の配列が期待される
     assert!(words.len() <= 1, "空文字列の解析結果が期待と異なります");
 }
@@ -42,17 +50,42 @@ fn test_empty_input() {
 #[test]
 fn test_long_text() {
     println!("=== 長文テスト ===");
-    
+
     let long_text = "これは長い文章のテストです。形態素解析エンジンが長文に対してどのような動作をするかを確認します。パフォーマンスとメモリ使用量を観察することが重要です。ReadMakerアプリケーションでは、このような長文の処理が頻繁に行われる可能性があります。";
-    
-    let words = analyze_text(long_text);
+
+    let words = analyze_text(long_text, &[], FallbackPolicy::CharClass).unwrap().words;
     let json_output = words_to_json(&words);
-    
+
     println!("長文解析結果:");
     println!("  語数: {}", words.len());
     println!("  最初の10語: {:?}", &words[..std::cmp::min(10, words.len())]);
     println!("  JSON長: {} bytes", json_output.len());
-    
+
     assert!(words.len() > 20, "長文の解析語数が少なすぎます");
     assert!(json_output.len() > 100, "JSON出力が短すぎます");
 }
+
+#[test]
+fn test_fallback_policies() {
+    let text = "今日は、良い天気です。";
+
+    // 既定ではフォールバックせずエラーになる
+    assert!(analyze_text(text, &[], FallbackPolicy::default()).is_err());
+
+    let analysis = analyze_text(text, &[], FallbackPolicy::Punctuation).unwrap();
+    assert_eq!(analysis.engine, Engine::Punctuation);
+    assert_eq!(analysis.words, vec!["今日は", "良い天気です"]);
+}
+
+#[test]
+fn test_ffi_reexported_from_core() {
+    // モバイル向けライブラリからcoreのC FFIを呼べる（辞書なしは文字種フォールバック）
+    let input = CString::new("今日は良い天気です。").unwrap();
+    let mut json = ptr::null_mut();
+    assert_eq!(rm_analyzer_analyze_report(ptr::null(), input.as_ptr(), ptr::null(), &mut json), RmStatus::Ok);
+    let value: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+    assert_eq!(value["engine"], "char_class");
+    assert_eq!(value["version"], REPORT_SCHEMA_VERSION);
+    assert_eq!(rm_report_schema_version(), REPORT_SCHEMA_VERSION);
+    rm_free_string(json);
+}
@@ -0,0 +1,90 @@
+//! ReadMaker Core - WebAssemblyバインディング
+//! Expo Webやブラウザから、辞書のバイト列を渡して形態素解析・まとまり分割・ふりがなを使う
+//!
+//! 結果のオフセットはコードポイント単位（JSの`string.length`のUTF-16単位とは異なる）
+
+use std::io::Read;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use readmaker_engine::{
+    analyze_report, furigana, is_zstd_compressed, ruby_segments, FallbackPolicy, MorphAnalyzer, Report,
+    ReportOptions,
+};
+
+/// 辞書を読み込み済みのアナライザー
+#[wasm_bindgen]
+pub struct Analyzer {
+    analyzer: MorphAnalyzer,
+}
+
+#[wasm_bindgen]
+impl Analyzer {
+    /// 辞書データ（zstd圧縮/非圧縮の`.vibrato`）から作成
+    #[wasm_bindgen(constructor)]
+    pub fn new(dictionary: &[u8]) -> Result<Analyzer, JsError> {
+        let analyzer = if is_zstd_compressed(dictionary) {
+            MorphAnalyzer::from_bytes(&decompress(dictionary)?)
+        } else {
+            MorphAnalyzer::from_bytes(dictionary)
+        }
+        .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self { analyzer })
+    }
+
+    /// 詳細な解析結果（`{version, engine, tokens, sentences, chunks}`）
+    ///
+    /// `options`は省略するか`{tokens, sentences, chunks, fallback}`の一部を指定する
+    pub fn analyze(&self, text: &str, options: JsValue) -> Result<JsValue, JsError> {
+        let options = if options.is_undefined() || options.is_null() {
+            ReportOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&format!("オプションが不正です: {}", e)))?
+        };
+        to_js(&self.report(text, &options)?)
+    }
+
+    /// 表示用のまとまり（`[{text, start, end, token_start, token_end}]`）
+    pub fn chunk(&self, text: &str) -> Result<JsValue, JsError> {
+        let options = ReportOptions { tokens: false, sentences: false, ..ReportOptions::default() };
+        to_js(&self.report(text, &options)?.chunks.unwrap_or_default())
+    }
+
+    /// ふりがな付きの区間（`[{text, reading?}]`、テキスト全体を隙間なく覆う）
+    pub fn furigana(&self, text: &str) -> Result<JsValue, JsError> {
+        let options = ReportOptions { sentences: false, chunks: false, ..ReportOptions::default() };
+        let tokens = self.report(text, &options)?.tokens.unwrap_or_default();
+        to_js(&ruby_segments(text, &furigana(&tokens)))
+    }
+}
+
+impl Analyzer {
+    fn report(&self, text: &str, options: &ReportOptions) -> Result<Report, JsError> {
+        analyze_report(Some(&self.analyzer), text, options).map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+/// 辞書なしで文字種により分割する（辞書の読み込み前の表示用）
+#[wasm_bindgen(js_name = analyzeWithoutDictionary)]
+pub fn analyze_without_dictionary(text: &str) -> Result<JsValue, JsError> {
+    let options = ReportOptions { fallback: FallbackPolicy::CharClass, ..ReportOptions::default() };
+    let report = analyze_report(None, text, &options).map_err(|e| JsError::new(&e.to_string()))?;
+    to_js(&report)
+}
+
+/// zstd圧縮された辞書を解凍する（Cライブラリを使わないruzstdで解凍）
+fn decompress(data: &[u8]) -> Result<Vec<u8>, JsError> {
+    let mut decoder = ruzstd::decoding::StreamingDecoder::new(data)
+        .map_err(|e| JsError::new(&format!("辞書の解凍エラー: {}", e)))?;
+    let mut raw = Vec::new();
+    decoder
+        .read_to_end(&mut raw)
+        .map_err(|e| JsError::new(&format!("辞書の解凍エラー: {}", e)))?;
+    Ok(raw)
+}
+
+/// JSのプレーンなオブジェクト・配列に変換する（`None`のフィールドは省く）
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,94 @@
+//! Node.js上で実行するWebAssemblyのテスト（`cargo test`でwasm-bindgen-test-runnerが使われる）
+#![cfg(target_arch = "wasm32")]
+
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+use vibrato::SystemDictionaryBuilder;
+use readmaker_wasm::{analyze_without_dictionary, Analyzer};
+
+const LEX_CSV: &str = include_str!("../../../../backend/core/testdata/mini-ipadic/lex.csv");
+const MATRIX_DEF: &str = include_str!("../../../../backend/core/testdata/mini-ipadic/matrix.def");
+const CHAR_DEF: &str = include_str!("../../../../backend/core/testdata/mini-ipadic/char.def");
+const UNK_DEF: &str = include_str!("../../../../backend/core/testdata/mini-ipadic/unk.def");
+
+fn mini_dictionary_bytes() -> Vec<u8> {
+    let dict = SystemDictionaryBuilder::from_readers(
+        LEX_CSV.as_bytes(),
+        MATRIX_DEF.as_bytes(),
+        CHAR_DEF.as_bytes(),
+        UNK_DEF.as_bytes(),
+    )
+    .unwrap();
+    let mut bytes = Vec::new();
+    dict.write(&mut bytes).unwrap();
+    bytes
+}
+
+fn to_json(value: JsValue) -> serde_json::Value {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn test_analyze_returns_js_object() {
+    let analyzer = Analyzer::new(&mini_dictionary_bytes()).unwrap();
+    let value = analyzer.analyze("今日は良い天気です。", JsValue::UNDEFINED).unwrap();
+    assert!(value.is_object());
+
+    let report = to_json(value);
+    assert_eq!(report["version"], readmaker_engine::REPORT_SCHEMA_VERSION);
+    assert_eq!(report["engine"], "vibrato");
+    assert_eq!(report["tokens"][0]["surface"], "今日");
+    assert_eq!(report["tokens"][0]["reading"], "キョウ");
+    assert_eq!(report["sentences"].as_array().unwrap().len(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_analyze_options() {
+    let analyzer = Analyzer::new(&mini_dictionary_bytes()).unwrap();
+    let options = js_object(r#"{"tokens":false}"#);
+    let report = to_json(analyzer.analyze("吾輩は猫である。", options).unwrap());
+    assert!(report.get("tokens").is_none());
+    assert!(report.get("chunks").is_some());
+
+    assert!(analyzer.analyze("吾輩は猫である。", JsValue::from_str("broken")).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_zstd_compressed_dictionary() {
+    let compressed = ruzstd::encoding::compress_to_vec(
+        mini_dictionary_bytes().as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+    let analyzer = Analyzer::new(&compressed).unwrap();
+    let chunks = to_json(analyzer.chunk("吾輩は猫である。").unwrap());
+    let texts: Vec<_> = chunks.as_array().unwrap().iter().map(|c| c["text"].as_str().unwrap()).collect();
+    assert_eq!(texts, vec!["吾輩は", "猫である。"]);
+
+    assert!(Analyzer::new(b"not a dictionary").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_furigana_segments() {
+    let analyzer = Analyzer::new(&mini_dictionary_bytes()).unwrap();
+    let segments = to_json(analyzer.furigana("今日は良い天気です。").unwrap());
+    let segments = segments.as_array().unwrap();
+    assert_eq!(segments[0]["text"], "今日");
+    assert_eq!(segments[0]["reading"], "きょう");
+    assert!(segments[1].get("reading").is_none());
+    let text: String = segments.iter().map(|s| s["text"].as_str().unwrap()).collect();
+    assert_eq!(text, "今日は良い天気です。");
+}
+
+#[wasm_bindgen_test]
+fn test_without_dictionary() {
+    let report = to_json(analyze_without_dictionary("今日は良い天気です。").unwrap());
+    assert_eq!(report["engine"], "char_class");
+    assert_eq!(report["tokens"][0]["surface"], "今日は");
+}
+
+/// JSONからJSのオブジェクトを作る
+fn js_object(json: &str) -> JsValue {
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).unwrap()
+}
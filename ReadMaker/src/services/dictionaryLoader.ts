@@ -61,6 +61,18 @@ class DictionaryLoader {
     }
   }
 
+  // 辞書ファイルのローカルパスを取得（rm_analyzer_new_from_pathに渡す。バイト列はJSに読み込まない）
+  async resolveDictionaryPath(): Promise<string> {
+    const dictionaryAsset = Asset.fromModule(
+      require('../../native/rust/dictionaries/ipadic.vibrato')
+    );
+    if (!dictionaryAsset.downloaded) {
+      await dictionaryAsset.downloadAsync();
+    }
+    const localUri = dictionaryAsset.localUri || dictionaryAsset.uri;
+    return localUri.replace(/^file:\/\//, '');
+  }
+
   // 辞書が読み込み済みかチェック
   isLoaded(): boolean {
     return this.initialized && this.dictionaryData !== null;
@@ -0,0 +1,16 @@
+//! 同期のCPU処理を非同期ランタイムのワーカーの外で実行する
+
+use readmaker_shared::{ReadMakerError, Result};
+
+/// `f`をブロッキング用のスレッドで実行する
+///
+/// 解析や取り込みなど、入力の大きさに比例して時間のかかる処理でワーカーを止めないために使う
+pub async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ReadMakerError::Internal(format!("処理が異常終了しました: {}", e)))?
+}
@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::AsyncCommands;
+use serde::Serialize;
+use readmaker_engine::{analyze_report, CacheKey, CacheMetrics, MorphAnalyzer, Report, ReportCache, ReportOptions};
+use readmaker_shared::Result;
+use crate::blocking::run_blocking;
+use crate::config::{CacheBackend, Config};
+
+/// Redisのキーの接頭辞
+const REDIS_KEY_PREFIX: &str = "readmaker:report";
+
+/// Redisへの接続・コマンドのタイムアウト（Redisが遅くても解析し直した方が早い）
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 解析結果のキャッシュ（プロセス内のLRUと、設定されていれば複数サーバーで共有するRedis）
+///
+/// Redisのエラーはログに出して無視し、解析し直した結果を返す
+pub struct AnalysisCache {
+    local: ReportCache,
+    redis: Option<ConnectionManager>,
+    ttl_secs: u64,
+    redis_hits: AtomicU64,
+    redis_misses: AtomicU64,
+    redis_errors: AtomicU64,
+}
+
+/// Redisの統計
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RedisMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub errors: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AnalysisCacheMetrics {
+    pub local: CacheMetrics,
+    /// Redisを使っていなければ`None`
+    pub redis: Option<RedisMetrics>,
+}
+
+impl AnalysisCache {
+    pub fn new(local: ReportCache, redis: Option<ConnectionManager>, ttl_secs: u64) -> Self {
+        Self {
+            local,
+            redis,
+            ttl_secs,
+            redis_hits: AtomicU64::new(0),
+            redis_misses: AtomicU64::new(0),
+            redis_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// 設定の上限でキャッシュを作る（Redisに接続できなければプロセス内のみで動く）
+    pub async fn from_config(config: &Config) -> Self {
+        let local = ReportCache::new(config.analysis_cache_max_entries, config.analysis_cache_max_bytes);
+        let redis = match config.analysis_cache_backend {
+            CacheBackend::Memory => None,
+            CacheBackend::Redis => match connect(&config.redis_url).await {
+                Ok(manager) => Some(manager),
+                Err(e) => {
+                    tracing::warn!("Redisに接続できないため、解析結果はプロセス内のみでキャッシュします: {}", e);
+                    None
+                }
+            },
+        };
+        Self::new(local, redis, config.analysis_cache_ttl_secs)
+    }
+
+    /// キャッシュにあればそれを、なければ解析して両方のキャッシュに保存した結果を返す
+    ///
+    /// 解析は同期処理なので、非同期ランタイムのワーカーを止めないようブロッキング用のスレッドで行う
+    pub async fn report(
+        &self,
+        analyzer: Option<Arc<MorphAnalyzer>>,
+        text: &str,
+        options: &ReportOptions,
+    ) -> Result<Arc<Report>> {
+        let key = CacheKey::new(analyzer.as_deref(), text, options);
+        if let Some(report) = self.local.get(&key) {
+            return Ok(report);
+        }
+
+        let redis_key = key.to_key_string(REDIS_KEY_PREFIX);
+        if let Some(report) = self.redis_get(&redis_key).await {
+            let report = Arc::new(report);
+            self.local.insert(key, Arc::clone(&report));
+            return Ok(report);
+        }
+
+        let (text, options) = (text.to_string(), *options);
+        let report = Arc::new(run_blocking(move || analyze_report(analyzer.as_deref(), &text, &options)).await?);
+        self.redis_set(&redis_key, &report).await;
+        self.local.insert(key, Arc::clone(&report));
+        Ok(report)
+    }
+
+    pub fn metrics(&self) -> AnalysisCacheMetrics {
+        AnalysisCacheMetrics {
+            local: self.local.metrics(),
+            redis: self.redis.as_ref().map(|_| RedisMetrics {
+                hits: self.redis_hits.load(Ordering::Relaxed),
+                misses: self.redis_misses.load(Ordering::Relaxed),
+                errors: self.redis_errors.load(Ordering::Relaxed),
+            }),
+        }
+    }
+
+    async fn redis_get(&self, key: &str) -> Option<Report> {
+        let mut redis = self.redis.clone()?;
+        let bytes: Option<Vec<u8>> = match redis.get(key).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.redis_errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Redisからの読み込みに失敗しました: {}", e);
+                return None;
+            }
+        };
+        // スキーマが合わない古い値はミスとして扱い、解析し直した結果で上書きする
+        match bytes.map(|b| Report::from_msgpack(&b)) {
+            Some(Ok(report)) => {
+                self.redis_hits.fetch_add(1, Ordering::Relaxed);
+                Some(report)
+            }
+            Some(Err(e)) => {
+                self.redis_misses.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Redisのキャッシュを読めません: {}", e);
+                None
+            }
+            None => {
+                self.redis_misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    async fn redis_set(&self, key: &str, report: &Report) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let bytes = match report.to_msgpack() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                return;
+            }
+        };
+        if let Err(e) = redis.set_ex::<_, _, ()>(key, bytes, self.ttl_secs).await {
+            self.redis_errors.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("Redisへの書き込みに失敗しました: {}", e);
+        }
+    }
+}
+
+async fn connect(url: &str) -> redis::RedisResult<ConnectionManager> {
+    let client = redis::Client::open(url)?;
+    let config = ConnectionManagerConfig::new()
+        .set_number_of_retries(1)
+        .set_connection_timeout(REDIS_TIMEOUT)
+        .set_response_timeout(REDIS_TIMEOUT);
+    ConnectionManager::new_with_config(client, config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(backend: CacheBackend, redis_url: &str) -> Config {
+        Config {
+            database_url: String::new(),
+            redis_url: redis_url.to_string(),
+            jwt_secret: String::new(),
+            port: 0,
+            fetch_timeout_secs: 10,
+            fetch_user_agent: String::new(),
+            fetch_max_bytes: 0,
+            analysis_cache_backend: backend,
+            analysis_cache_max_entries: 16,
+            analysis_cache_max_bytes: 1 << 20,
+            analysis_cache_ttl_secs: 60,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache() {
+        let cache = AnalysisCache::from_config(&config(CacheBackend::Memory, "")).await;
+        let options = ReportOptions::default();
+        let first = cache.report(None, "今日は良い天気です。", &options).await.unwrap();
+        let second = cache.report(None, "今日は良い天気です。", &options).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.local.hits, metrics.local.misses), (1, 1));
+        assert!(metrics.redis.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_falls_back_to_memory() {
+        let cache = AnalysisCache::from_config(&config(CacheBackend::Redis, "redis://127.0.0.1:1")).await;
+        assert!(cache.metrics().redis.is_none());
+        let report = cache.report(None, "吾輩は猫である。", &ReportOptions::default()).await.unwrap();
+        assert!(report.chunks.is_some());
+    }
+}
@@ -7,6 +7,40 @@ pub struct Config {
     pub redis_url: String,
     pub jwt_secret: String,
     pub port: u16,
+    /// 記事取得のタイムアウト（秒）
+    pub fetch_timeout_secs: u64,
+    /// 記事取得時のUser-Agent
+    pub fetch_user_agent: String,
+    /// 取得する記事の最大サイズ（バイト）
+    pub fetch_max_bytes: usize,
+    /// 解析結果のキャッシュの保存先
+    pub analysis_cache_backend: CacheBackend,
+    /// プロセス内に保持する解析結果の最大件数
+    pub analysis_cache_max_entries: usize,
+    /// プロセス内に保持する解析結果の最大サイズ（推定バイト数）
+    pub analysis_cache_max_bytes: usize,
+    /// Redisに保存した解析結果の有効期間（秒）
+    pub analysis_cache_ttl_secs: u64,
+}
+
+/// 解析結果のキャッシュの保存先
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackend {
+    /// プロセス内のLRUのみ
+    Memory,
+    /// プロセス内のLRUに加えて、`redis_url`のRedisを複数のサーバーで共有する
+    Redis,
+}
+
+impl CacheBackend {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "memory" => Some(Self::Memory),
+            "redis" => Some(Self::Redis),
+            _ => None,
+        }
+    }
 }
 
 impl Config {
@@ -24,6 +58,33 @@ impl Config {
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .unwrap_or(3000),
+            fetch_timeout_secs: env::var("FETCH_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            fetch_user_agent: env::var("FETCH_USER_AGENT")
+                .unwrap_or_else(|_| "ReadMaker/0.1".to_string()),
+            fetch_max_bytes: env::var("FETCH_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5 * 1024 * 1024),
+            analysis_cache_backend: match env::var("ANALYSIS_CACHE_BACKEND") {
+                Ok(value) => CacheBackend::parse(&value)
+                    .ok_or_else(|| format!("ANALYSIS_CACHE_BACKENDが不正です: {}（memory または redis）", value))?,
+                Err(_) => CacheBackend::Memory,
+            },
+            analysis_cache_max_entries: env::var("ANALYSIS_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1024),
+            analysis_cache_max_bytes: env::var("ANALYSIS_CACHE_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(64 * 1024 * 1024),
+            analysis_cache_ttl_secs: env::var("ANALYSIS_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(24 * 60 * 60),
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,293 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{redirect, Url};
+use readmaker_engine::{html, Document};
+use readmaker_shared::{ReadMakerError, Result};
+use crate::blocking::run_blocking;
+use crate::config::Config;
+
+/// 追う転送（リダイレクト）の最大回数
+const MAX_REDIRECTS: usize = 10;
+
+/// 接続してよいIPアドレスかを判定する
+type AddressFilter = fn(IpAddr) -> bool;
+
+/// Webページを取得して本文を抽出する
+///
+/// サーバー内部のサービスを取得させない（SSRF対策）ため、最初のURL・転送先・名前解決の結果の
+/// いずれでも公開されていないアドレス（ループバック・プライベート・リンクローカルなど）には接続しない
+#[derive(Clone)]
+pub struct ArticleFetcher {
+    client: reqwest::Client,
+    max_bytes: usize,
+    is_allowed: AddressFilter,
+}
+
+impl ArticleFetcher {
+    /// `builder`に接続先の制限を加えてクライアントを作る
+    pub fn new(builder: reqwest::ClientBuilder, max_bytes: usize, is_allowed: AddressFilter) -> Result<Self> {
+        let client = builder
+            // プロキシを通すと接続先のアドレスを確かめられない
+            .no_proxy()
+            .dns_resolver(Arc::new(CheckedResolver { is_allowed }))
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("転送が多すぎます")
+                } else if let Err(e) = check_url(attempt.url(), is_allowed) {
+                    attempt.error(e)
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .map_err(|e| ReadMakerError::Internal(format!("HTTPクライアント初期化エラー: {}", e)))?;
+        Ok(Self { client, max_bytes, is_allowed })
+    }
+
+    /// 設定のタイムアウト・User-Agent・最大サイズでクライアントを作る
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.fetch_timeout_secs))
+            .user_agent(config.fetch_user_agent.clone());
+        Self::new(builder, config.fetch_max_bytes, is_public_address)
+    }
+
+    /// URLのページを取得し、本文を構造化ドキュメントにする
+    pub async fn fetch(&self, url: &str) -> Result<Document> {
+        let url = Url::parse(url).map_err(|e| ReadMakerError::Validation(format!("無効なURLです: {}", e)))?;
+        check_url(&url, self.is_allowed).map_err(ReadMakerError::Validation)?;
+
+        let mut response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ReadMakerError::Internal(format!("記事の取得に失敗しました: {}", e)))?;
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        if let Some(content_type) = &content_type {
+            if !content_type.contains("html") {
+                return Err(ReadMakerError::Validation(format!("HTMLではありません: {}", content_type)));
+            }
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ReadMakerError::Internal(format!("記事の取得に失敗しました: {}", e)))?
+        {
+            if body.len() + chunk.len() > self.max_bytes {
+                return Err(ReadMakerError::Validation(format!("記事が大きすぎます（上限{}バイト）", self.max_bytes)));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        // 本文の抽出は取得したHTMLの大きさに比例して時間がかかるため、ワーカーの外で行う
+        let document = run_blocking(move || Ok(html::extract_bytes(&body, content_type.as_deref()))).await?;
+        if document.chapters.is_empty() {
+            return Err(ReadMakerError::Validation("本文が見つかりません".to_string()));
+        }
+        Ok(document)
+    }
+}
+
+/// スキームと、IPアドレスで書かれたホストを確かめる（ホスト名は[`CheckedResolver`]が確かめる）
+fn check_url(url: &Url, is_allowed: AddressFilter) -> std::result::Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("http/https以外のURLは取得できません".to_string());
+    }
+    let host = url.host_str().ok_or_else(|| "ホストのないURLは取得できません".to_string())?;
+    // IPv6アドレスは`[]`で囲まれている（IPv4アドレスの10進以外の表記は正規化済み）
+    let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() else {
+        return Ok(());
+    };
+    if is_allowed(ip) {
+        Ok(())
+    } else {
+        Err(format!("公開されていないアドレスには接続できません: {}", ip))
+    }
+}
+
+/// 名前解決の結果から、接続してはいけないアドレスを除く
+///
+/// 確かめたアドレスにそのまま接続するため、確認後に名前解決の結果が変わっても（DNSリバインディング）迂回されない
+struct CheckedResolver {
+    is_allowed: AddressFilter,
+}
+
+impl Resolve for CheckedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let is_allowed = self.is_allowed;
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_allowed(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("公開されていないアドレスには接続できません: {}", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// インターネットに公開されたアドレスか（ループバック・プライベート・リンクローカル・予約済みなどを除く）
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // 100.64.0.0/10（キャリアグレードNAT）
+        || (a == 100 && (64..128).contains(&b))
+        // 192.0.0.0/24（IETFプロトコル割り当て）
+        || (a == 192 && b == 0 && c == 0)
+        // 198.18.0.0/15（ベンチマーク用）
+        || (a == 198 && (b == 18 || b == 19))
+        // 240.0.0.0/4（予約済み）
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // NAT64（64:ff9b::/96）は埋め込まれたIPv4アドレスで判定する
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., a, b, c, d] = ip.octets();
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // 2001:db8::/32（文書用）
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+        // fec0::/10（サイトローカル、廃止済み）
+        || (segments[0] & 0xffc0) == 0xfec0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::header, response::Redirect, routing::get, Router};
+    use std::net::SocketAddr;
+
+    const ARTICLE: &str = r#"<html><head><title>テスト記事 | モック</title></head><body>
+<nav><a href="/">ホーム</a><a href="/news">ニュース</a></nav>
+<article><h1>テスト記事</h1>
+<p>これはモックサーバーから配信される記事の本文です。十分な長さがあり、読点も含まれています。</p>
+<p><a href="/more">続きを読む</a></p>
+</article></body></html>"#;
+
+    /// Webの代わりにローカルのモックサーバーを立てる
+    async fn spawn_mock_server() -> SocketAddr {
+        let app = Router::new()
+            .route("/article", get(|| async { ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], ARTICLE) }))
+            .route(
+                "/sjis",
+                get(|| async {
+                    let (bytes, _, _) = encoding_rs::SHIFT_JIS
+                        .encode("<html><body><p>シフトJISで書かれた記事の本文です。文字化けせずに読めるはずです。</p></body></html>");
+                    ([(header::CONTENT_TYPE, "text/html; charset=Shift_JIS")], bytes.into_owned())
+                }),
+            )
+            .route("/json", get(|| async { ([(header::CONTENT_TYPE, "application/json")], "{}") }))
+            .route("/large", get(|| async { ([(header::CONTENT_TYPE, "text/html")], "あ".repeat(10_000)) }))
+            .route("/moved", get(|| async { Redirect::temporary("/article") }))
+            .route("/metadata", get(|| async { Redirect::temporary("http://169.254.169.254/latest/meta-data/") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    /// モックサーバーに接続できるよう、ループバックだけを許す
+    fn fetcher() -> ArticleFetcher {
+        ArticleFetcher::new(reqwest::Client::builder(), 16 * 1024, |ip| ip.is_loopback()).unwrap()
+    }
+
+    fn public_fetcher() -> ArticleFetcher {
+        ArticleFetcher::new(reqwest::Client::builder(), 16 * 1024, is_public_address).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_extracts_article() {
+        let addr = spawn_mock_server().await;
+        let doc = fetcher().fetch(&format!("http://{}/article", addr)).await.unwrap();
+        assert_eq!(doc.title.as_deref(), Some("テスト記事"));
+        assert_eq!(doc.paragraphs().count(), 1);
+        assert!(doc.plain_text().starts_with("これはモックサーバーから"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decodes_charset() {
+        let addr = spawn_mock_server().await;
+        let doc = fetcher().fetch(&format!("http://{}/sjis", addr)).await.unwrap();
+        assert!(doc.plain_text().contains("シフトJIS"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_invalid_responses() {
+        let addr = spawn_mock_server().await;
+        assert!(fetcher().fetch(&format!("http://{}/json", addr)).await.is_err());
+        assert!(fetcher().fetch(&format!("http://{}/large", addr)).await.is_err());
+        assert!(fetcher().fetch(&format!("http://{}/missing", addr)).await.is_err());
+        assert!(fetcher().fetch("file:///etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_private_addresses() {
+        let addr = spawn_mock_server().await;
+        let fetcher = public_fetcher();
+        assert!(fetcher.fetch(&format!("http://{}/article", addr)).await.is_err());
+        assert!(fetcher.fetch(&format!("http://localhost:{}/article", addr.port())).await.is_err());
+        assert!(fetcher.fetch("http://169.254.169.254/latest/meta-data/").await.is_err());
+        assert!(fetcher.fetch("http://[::ffff:10.0.0.1]/").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_redirects() {
+        let addr = spawn_mock_server().await;
+        let doc = fetcher().fetch(&format!("http://{}/moved", addr)).await.unwrap();
+        assert_eq!(doc.title.as_deref(), Some("テスト記事"));
+        // 転送先も同じように確かめる
+        assert!(fetcher().fetch(&format!("http://{}/metadata", addr)).await.is_err());
+    }
+
+    #[test]
+    fn test_is_public_address() {
+        let public = |s: &str| is_public_address(s.parse().unwrap());
+        assert!(public("8.8.8.8"));
+        assert!(public("2606:4700:4700::1111"));
+        for ip in [
+            "127.0.0.1", "10.0.0.1", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+            "255.255.255.255", "::1", "::", "fe80::1", "fd00::1", "::ffff:127.0.0.1", "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+}
@@ -6,19 +6,24 @@ use axum::{
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
-use tracing::{info, Level};
-use tracing_subscriber;
+use tracing::{info, warn, Level};
 use readmaker_shared::JwtService;
+use readmaker_engine::MorphAnalyzer;
 
+mod blocking;
+mod cache;
 mod routes;
 mod middleware;
 mod models;
 mod database;
 mod config;
+mod fetcher;
 
+use crate::cache::AnalysisCache;
 use crate::config::Config;
 use crate::database::DatabaseConnection;
-use crate::routes::{auth, users, reading, health};
+use crate::fetcher::ArticleFetcher;
+use crate::routes::{auth, users, reading, library, health};
 
 #[tokio::main]
 async fn main() {
@@ -39,15 +44,34 @@ async fn main() {
     // JWT サービス初期化
     let jwt_service = Arc::new(JwtService::new(&config.jwt_secret));
 
+    // 形態素解析器初期化（辞書がない場合は各リクエストのフォールバック方針に従う）
+    let analyzer: Option<Arc<MorphAnalyzer>> = match MorphAnalyzer::new() {
+        Ok(analyzer) => Some(Arc::new(analyzer)),
+        Err(e) => {
+            warn!("辞書の読み込みに失敗しました: {}", e);
+            None
+        }
+    };
+
+    // 解析結果キャッシュ初期化（ANALYSIS_CACHE_BACKEND=redisならREDIS_URLのRedisを共有する）
+    let analysis_cache = Arc::new(AnalysisCache::from_config(&config).await);
+
+    // 記事取得クライアント初期化
+    let fetcher = ArticleFetcher::from_config(&config).expect("HTTPクライアントの初期化に失敗しました");
+
     // ルーター設定
     let app = Router::new()
         .route("/health", get(health::health_check))
         .nest("/auth", auth::routes())
         .nest("/users", users::routes())
         .nest("/reading", reading::routes())
+        .nest("/library", library::routes())
         .layer(CorsLayer::permissive())
         .layer(Extension(db.pool()))
-        .layer(Extension(jwt_service));
+        .layer(Extension(jwt_service))
+        .layer(Extension(analyzer))
+        .layer(Extension(analysis_cache))
+        .layer(Extension(fetcher));
 
     // サーバー起動
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
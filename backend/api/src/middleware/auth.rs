@@ -5,12 +5,13 @@ use axum::{
     response::Response,
 };
 use axum::http::header::AUTHORIZATION;
-use readmaker_shared::{JwtService, Claims};
+use readmaker_shared::JwtService;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AuthState {
     pub user_id: uuid::Uuid,
+    #[allow(dead_code)]
     pub email: String,
 }
 
@@ -1,3 +1 @@
-pub mod auth;
-
-pub use auth::*;
\ No newline at end of file
+pub mod auth;
\ No newline at end of file
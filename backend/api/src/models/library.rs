@@ -0,0 +1,90 @@
+use sqlx::PgPool;
+use sqlx::types::Json;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use readmaker_engine::Document;
+use readmaker_shared::Result;
+
+/// `library_books.title`（VARCHAR(500)）の文字数
+const MAX_TITLE_CHARS: usize = 500;
+/// `library_books.author`（VARCHAR(255)）の文字数
+const MAX_AUTHOR_CHARS: usize = 255;
+
+/// 本文を除いたライブラリの1冊
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LibraryBook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub author: Option<String>,
+    pub source_format: String,
+    pub char_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LibraryBook {
+    /// 取り込んだ文書をユーザーのライブラリに追加
+    pub async fn create(pool: &PgPool, user_id: Uuid, document: &Document) -> Result<LibraryBook> {
+        // 電子書籍のメタデータは長さが決まっていないため、列に収まるよう文字数で切り詰める
+        let title = truncate_chars(document.title.as_deref().unwrap_or("無題"), MAX_TITLE_CHARS);
+        let author = document.author.as_deref().map(|a| truncate_chars(a, MAX_AUTHOR_CHARS));
+        let source_format = serde_json::to_value(document.format)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let char_count = i32::try_from(document.char_count()).unwrap_or(i32::MAX);
+        let now = Utc::now();
+
+        let book = sqlx::query_as::<_, LibraryBook>(
+            r#"
+            INSERT INTO library_books (id, user_id, title, author, source_format, content, char_count, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, user_id, title, author, source_format, char_count, created_at, updated_at
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(title)
+        .bind(author)
+        .bind(source_format)
+        .bind(Json(document))
+        .bind(char_count)
+        .bind(now)
+        .bind(now)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(book)
+    }
+
+    /// ユーザーのライブラリ（新しい順）
+    pub async fn list_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<LibraryBook>> {
+        let books = sqlx::query_as::<_, LibraryBook>(
+            "SELECT id, user_id, title, author, source_format, char_count, created_at, updated_at FROM library_books WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(books)
+    }
+}
+
+/// 先頭から`max_chars`文字まで
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    s.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("吾輩は猫である", 3), "吾輩は");
+        assert_eq!(truncate_chars("猫", 3), "猫");
+        assert_eq!(truncate_chars(&"あ".repeat(600), MAX_TITLE_CHARS).chars().count(), MAX_TITLE_CHARS);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod user;
+pub mod library;
 
-pub use user::*;
\ No newline at end of file
+pub use user::*;
+pub use library::*;
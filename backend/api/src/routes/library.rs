@@ -0,0 +1,75 @@
+use axum::{
+    routing::{get, post},
+    Router, Json, http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension},
+    middleware,
+};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use readmaker_engine::{epub, TextStats};
+use readmaker_shared::{create_error_response, create_success_response};
+use crate::blocking::run_blocking;
+use crate::middleware::auth::{AuthState, auth_middleware};
+use crate::models::LibraryBook;
+
+/// アップロードできる電子書籍の上限サイズ
+const UPLOAD_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+#[derive(Serialize)]
+pub struct UploadResponse {
+    pub book: LibraryBook,
+    pub chapters: usize,
+    pub stats: TextStats,
+}
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/", get(list_books))
+        .route("/epub", post(upload_epub).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)))
+        .layer(middleware::from_fn(auth_middleware))
+}
+
+async fn list_books(
+    Extension(pool): Extension<PgPool>,
+    Extension(auth_state): Extension<AuthState>,
+) -> Result<Json<Value>, StatusCode> {
+    match LibraryBook::list_by_user(&pool, auth_state.user_id).await {
+        Ok(books) => Ok(Json(serde_json::to_value(create_success_response(books)).unwrap())),
+        Err(e) => {
+            tracing::error!("ライブラリ取得エラー: {}", e);
+            Ok(Json(serde_json::to_value(create_error_response::<()>("ライブラリの取得に失敗しました".to_string())).unwrap()))
+        }
+    }
+}
+
+/// EPUBファイル（リクエストボディ）を取り込んでライブラリに追加
+async fn upload_epub(
+    Extension(pool): Extension<PgPool>,
+    Extension(auth_state): Extension<AuthState>,
+    body: Bytes,
+) -> Result<Json<Value>, StatusCode> {
+    let document = match run_blocking(move || epub::parse_bytes(&body)).await {
+        Ok(document) => document,
+        Err(e) => {
+            tracing::warn!("EPUB取り込み失敗: {}", e);
+            return Ok(Json(serde_json::to_value(create_error_response::<()>(e.to_string())).unwrap()));
+        }
+    };
+
+    match LibraryBook::create(&pool, auth_state.user_id, &document).await {
+        Ok(book) => {
+            let response = UploadResponse {
+                book,
+                chapters: document.chapters.len(),
+                stats: document.stats(),
+            };
+            Ok(Json(serde_json::to_value(create_success_response(response)).unwrap()))
+        }
+        Err(e) => {
+            tracing::error!("ライブラリ追加エラー: {}", e);
+            Ok(Json(serde_json::to_value(create_error_response::<()>("ライブラリへの追加に失敗しました".to_string())).unwrap()))
+        }
+    }
+}
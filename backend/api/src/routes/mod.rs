@@ -1,4 +1,5 @@
 pub mod auth;
 pub mod users;
 pub mod reading;
-pub mod health;
\ No newline at end of file
+pub mod library;
+pub mod health;
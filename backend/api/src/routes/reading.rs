@@ -1,19 +1,38 @@
 use axum::{
     routing::{get, post},
     Router, Json, http::StatusCode,
-    extract::Extension,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    middleware,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use readmaker_engine::{
+    aozora, analyze_with, layout, text_stats, Document, Engine, FallbackPolicy, LayoutOptions,
+    MorphAnalyzer, ReportOptions, TextStats, MSGPACK_CONTENT_TYPE,
+};
+use readmaker_shared::{create_error_response, create_success_response};
+use crate::blocking::run_blocking;
+use crate::cache::AnalysisCache;
+use crate::fetcher::ArticleFetcher;
+use crate::middleware::auth::auth_middleware;
 
 #[derive(Deserialize)]
 pub struct AnalyzeRequest {
     pub text: String,
+    /// 辞書が使えない場合の挙動（省略時はエラー）
+    #[serde(default)]
+    pub fallback: FallbackPolicy,
 }
 
 #[derive(Serialize)]
 pub struct AnalyzeResponse {
+    pub engine: Engine,
     pub words: Vec<WordInfo>,
     pub reading_stats: ReadingStats,
 }
@@ -29,31 +48,243 @@ pub struct WordInfo {
 pub struct ReadingStats {
     pub total_words: usize,
     pub unique_words: usize,
+    /// 日本語部分の文字数（空白・句読点を除く）
+    pub japanese_chars: usize,
+    /// 英語部分の単語数
+    pub english_words: usize,
     pub reading_time_estimate: f64,
 }
 
+#[derive(Deserialize)]
+pub struct ReportRequest {
+    pub text: String,
+    /// 出力する項目とフォールバック方針（省略時は全項目・文字種フォールバック）
+    #[serde(default)]
+    pub options: ReportOptions,
+}
+
+#[derive(Deserialize)]
+pub struct LayoutRequest {
+    pub text: String,
+    /// 1行の文字数・1ページの行数・組方向（省略時は既定値）
+    #[serde(default)]
+    pub options: LayoutOptions,
+}
+
+#[derive(Deserialize)]
+pub struct ImportUrlRequest {
+    pub url: String,
+}
+
+#[derive(Serialize)]
+pub struct ImportResponse {
+    pub document: Document,
+    pub stats: TextStats,
+    pub char_count: usize,
+}
+
+/// 取り込むファイルの上限サイズ（長編小説を想定）
+const IMPORT_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
 pub fn routes() -> Router {
     Router::new()
         .route("/analyze", post(analyze_text))
+        .route("/report", post(report_text))
+        .route("/cache", get(cache_metrics))
+        .route("/layout", post(layout_text))
+        .route("/import/aozora", post(import_aozora).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)))
+        // サーバーから外部へ接続させるため、ログインしたユーザーに限る
+        .route("/import/url", post(import_url).layer(middleware::from_fn(auth_middleware)))
         .route("/stats", get(get_reading_stats))
 }
 
 async fn analyze_text(
-    Extension(pool): Extension<PgPool>,
+    Extension(analyzer): Extension<Option<Arc<MorphAnalyzer>>>,
     Json(payload): Json<AnalyzeRequest>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: readmaker-core の形態素解析機能を統合
-    Ok(Json(json!({
-        "message": "Text analysis endpoint - 実装予定",
-        "text_length": payload.text.len()
-    })))
+    let analysis = match analyze_with(analyzer.as_deref(), &payload.text, payload.fallback) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            tracing::warn!("形態素解析失敗: {}", e);
+            return Ok(Json(serde_json::to_value(create_error_response::<()>(e.to_string())).unwrap()));
+        }
+    };
+
+    let unique_words = analysis.words.iter().map(|w| w.surface.as_str()).collect::<HashSet<_>>().len();
+    let stats = text_stats(&payload.text);
+    let reading_stats = ReadingStats {
+        total_words: analysis.words.len(),
+        unique_words,
+        japanese_chars: stats.japanese_chars,
+        english_words: stats.english_words,
+        reading_time_estimate: stats.reading_time_minutes(),
+    };
+    let response = AnalyzeResponse {
+        engine: analysis.engine,
+        words: analysis
+            .words
+            .into_iter()
+            .map(|w| WordInfo {
+                surface: w.surface,
+                reading: w.reading,
+                part_of_speech: w.part_of_speech,
+            })
+            .collect(),
+        reading_stats,
+    };
+
+    Ok(Json(serde_json::to_value(create_success_response(response)).unwrap()))
+}
+
+/// バージョン付きの詳細な解析結果（`Accept: application/msgpack`ならMessagePackで返す）
+///
+/// 同じテキスト・辞書・オプションの結果はキャッシュから返す
+async fn report_text(
+    Extension(analyzer): Extension<Option<Arc<MorphAnalyzer>>>,
+    Extension(cache): Extension<Arc<AnalysisCache>>,
+    headers: HeaderMap,
+    Json(payload): Json<ReportRequest>,
+) -> Response {
+    let report = match cache.report(analyzer, &payload.text, &payload.options).await {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::warn!("形態素解析失敗: {}", e);
+            return Json(create_error_response::<()>(e.to_string())).into_response();
+        }
+    };
+
+    if !accepts_msgpack(&headers) {
+        return ([(header::VARY, "Accept")], Json(create_success_response(&*report))).into_response();
+    }
+    match report.to_msgpack() {
+        Ok(bytes) => ([(header::CONTENT_TYPE, MSGPACK_CONTENT_TYPE), (header::VARY, "Accept")], bytes).into_response(),
+        Err(e) => Json(create_error_response::<()>(e.to_string())).into_response(),
+    }
+}
+
+/// 解析結果キャッシュのヒット・ミスなどの統計
+async fn cache_metrics(Extension(cache): Extension<Arc<AnalysisCache>>) -> Json<Value> {
+    Json(serde_json::to_value(create_success_response(cache.metrics())).unwrap())
+}
+
+/// AcceptヘッダーがMessagePackを受け付けるか（`q=0`は拒否として扱う）
+fn accepts_msgpack(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|item| {
+            let mut params = item.split(';').map(str::trim);
+            let media = params.next().unwrap_or_default();
+            let rejected = params.any(|p| p.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0));
+            !rejected && matches!(media, "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack")
+        })
+}
+
+/// 禁則処理つきでページ・行に組む
+async fn layout_text(Json(payload): Json<LayoutRequest>) -> Result<Json<Value>, StatusCode> {
+    let result = layout::layout(&payload.text, &payload.options);
+    Ok(Json(serde_json::to_value(create_success_response(result)).unwrap()))
+}
+
+/// 青空文庫テキスト（Shift_JIS/UTF-8）を構造化ドキュメントに変換
+async fn import_aozora(body: Bytes) -> Result<Json<Value>, StatusCode> {
+    let document = match run_blocking(move || Ok(aozora::parse_bytes(&body))).await {
+        Ok(document) => document,
+        Err(e) => {
+            tracing::error!("青空文庫テキスト取り込み失敗: {}", e);
+            return Ok(Json(serde_json::to_value(create_error_response::<()>(e.to_string())).unwrap()));
+        }
+    };
+    if document.chapters.is_empty() {
+        return Ok(Json(serde_json::to_value(create_error_response::<()>("本文が見つかりません".to_string())).unwrap()));
+    }
+
+    Ok(Json(serde_json::to_value(create_success_response(import_response(document))).unwrap()))
+}
+
+/// URLの記事を取得し、本文を抽出した構造化ドキュメントを返す
+async fn import_url(
+    Extension(fetcher): Extension<ArticleFetcher>,
+    Json(payload): Json<ImportUrlRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    match fetcher.fetch(&payload.url).await {
+        Ok(document) => Ok(Json(serde_json::to_value(create_success_response(import_response(document))).unwrap())),
+        Err(e) => {
+            tracing::warn!("記事取り込み失敗: {} ({})", e, payload.url);
+            Ok(Json(serde_json::to_value(create_error_response::<()>(e.to_string())).unwrap()))
+        }
+    }
+}
+
+fn import_response(document: Document) -> ImportResponse {
+    ImportResponse {
+        stats: document.stats(),
+        char_count: document.char_count(),
+        document,
+    }
 }
 
 async fn get_reading_stats(
-    Extension(pool): Extension<PgPool>,
+    Extension(_pool): Extension<PgPool>,
 ) -> Result<Json<Value>, StatusCode> {
     // TODO: ユーザーの読書統計取得
     Ok(Json(json!({
         "message": "Reading stats endpoint - 実装予定"
     })))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use readmaker_engine::{Report, ReportCache};
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn memory_cache() -> Arc<AnalysisCache> {
+        Arc::new(AnalysisCache::new(ReportCache::new(16, 1 << 20), None, 60))
+    }
+
+    async fn report_response(cache: Arc<AnalysisCache>, headers: HeaderMap) -> (Option<String>, Bytes) {
+        let request = ReportRequest { text: "今日は良い天気です。".to_string(), options: ReportOptions::default() };
+        let response = report_text(Extension(None), Extension(cache), headers, Json(request)).await;
+        let content_type = response.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+        (content_type, axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap())
+    }
+
+    #[test]
+    fn test_accepts_msgpack() {
+        assert!(accepts_msgpack(&accept("application/msgpack")));
+        assert!(accepts_msgpack(&accept("application/json;q=0.5, application/x-msgpack")));
+        assert!(!accepts_msgpack(&accept("application/msgpack;q=0")));
+        assert!(!accepts_msgpack(&accept("application/json")));
+        assert!(!accepts_msgpack(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_report_content_negotiation() {
+        let cache = memory_cache();
+        let (content_type, body) = report_response(cache.clone(), accept(MSGPACK_CONTENT_TYPE)).await;
+        assert_eq!(content_type.as_deref(), Some(MSGPACK_CONTENT_TYPE));
+        let report = Report::from_msgpack(&body).unwrap();
+        assert_eq!(report.engine, Engine::CharClass);
+        assert!(report.chunks.is_some());
+
+        let (content_type, body) = report_response(cache.clone(), HeaderMap::new()).await;
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        let value: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["success"], true);
+        assert_eq!(value["data"]["version"], readmaker_engine::REPORT_SCHEMA_VERSION);
+
+        // 2回目は形式が違ってもキャッシュした結果を使う
+        let metrics = cache_metrics(Extension(cache)).await;
+        assert_eq!(metrics["data"]["local"]["hits"], 1);
+        assert_eq!(metrics["data"]["local"]["misses"], 1);
+    }
+}
@@ -13,7 +13,7 @@ pub fn routes() -> Router {
 }
 
 async fn get_profile(
-    Extension(pool): Extension<PgPool>,
+    Extension(_pool): Extension<PgPool>,
 ) -> Result<Json<Value>, StatusCode> {
     // TODO: JWT認証実装後に実装
     Ok(Json(json!({
@@ -22,8 +22,8 @@ async fn get_profile(
 }
 
 async fn update_profile(
-    Extension(pool): Extension<PgPool>,
-    Json(payload): Json<Value>,
+    Extension(_pool): Extension<PgPool>,
+    Json(_payload): Json<Value>,
 ) -> Result<Json<Value>, StatusCode> {
     // TODO: JWT認証実装後に実装
     Ok(Json(json!({
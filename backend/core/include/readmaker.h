@@ -0,0 +1,87 @@
+/*
+ * ReadMaker Core C FFI
+ * Rust側（src/ffi/mod.rs, src/ffi/error.rs, src/ffi/job.rs）の定義と一致させること
+ */
+#ifndef READMAKER_H
+#define READMAKER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 結果コード */
+typedef enum RmStatus {
+    RM_OK = 0,
+    RM_NULL_POINTER = 1,
+    RM_INVALID_UTF8 = 2,
+    RM_INTERIOR_NUL = 3,
+    RM_DICTIONARY = 4,
+    RM_ANALYSIS = 5,
+    RM_INVALID_OPTIONS = 6,
+    RM_SERIALIZATION = 7,
+    RM_PANIC = 8,
+    RM_CANCELLED = 9,
+    RM_PENDING = 10,
+} RmStatus;
+
+/* ジョブの状態 */
+typedef enum RmJobState {
+    RM_JOB_RUNNING = 0,
+    RM_JOB_DONE = 1,
+    RM_JOB_FAILED = 2,
+    RM_JOB_CANCELLED = 3,
+} RmJobState;
+
+typedef struct RmAnalyzer RmAnalyzer;
+
+/* Rustが確保したバイト列（rm_free_buffer()で解放する） */
+typedef struct RmBuffer {
+    uint8_t *data;
+    size_t len;
+} RmBuffer;
+typedef struct RmJob RmJob;
+
+/* ジョブのスレッドから、解析前と1文ごとにRM_JOB_RUNNINGで、終了時に最終状態で呼ばれる */
+typedef void (*RmProgressCallback)(void *user_data, size_t processed, size_t total, RmJobState state);
+
+/* このスレッドで直前に失敗した関数のエラーメッセージ（なければNULL、解放不要） */
+const char *rm_last_error_message(void);
+
+RmStatus rm_analyzer_new(const uint8_t *dictionary_data, size_t dictionary_len, RmAnalyzer **out_handle);
+RmStatus rm_analyzer_new_from_path(const char *path, RmAnalyzer **out_handle);
+RmStatus rm_analyzer_analyze(const RmAnalyzer *handle, const char *input, char **out_json);
+RmStatus rm_analyzer_analyze_report(const RmAnalyzer *handle, const char *input, const char *options_json,
+                                    char **out_json);
+RmStatus rm_analyzer_analyze_report_msgpack(const RmAnalyzer *handle, const char *input, const char *options_json,
+                                            RmBuffer *out_buffer);
+void rm_analyzer_free(RmAnalyzer *handle);
+
+/* 結果のスキーマのバージョン（backend/core/REPORT_SCHEMA.md） */
+uint32_t rm_report_schema_version(void);
+void rm_free_buffer(RmBuffer buffer);
+
+/* 長文向けの解析ジョブ（handleはNULL可） */
+RmStatus rm_job_start(const RmAnalyzer *handle, const char *input, const char *options_json, RmJob **out_job);
+RmStatus rm_job_start_with_callback(const RmAnalyzer *handle, const char *input, const char *options_json,
+                                    RmProgressCallback callback, void *user_data, RmJob **out_job);
+RmStatus rm_job_progress(const RmJob *job, size_t *out_processed, size_t *out_total, RmJobState *out_state);
+RmStatus rm_job_cancel(const RmJob *job);
+RmStatus rm_job_result(const RmJob *job, bool wait, char **out_json);
+RmStatus rm_job_result_msgpack(const RmJob *job, bool wait, RmBuffer *out_buffer);
+void rm_job_free(RmJob *job);
+void rm_free_string(char *ptr);
+
+/* 旧API（失敗時はNULL、理由はrm_last_error_message()） */
+char *js_analyze_text(const char *input, const uint8_t *dictionary_data, size_t dictionary_len);
+void js_free_string(char *ptr);
+char *js_test_bridge(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* READMAKER_H */
@@ -0,0 +1,188 @@
+//! 形態素解析済みのコーパス（MeCab形式）
+//! 1行に「表層形<TAB>素性」を並べ、文の終わりに`EOS`を置く（`mecab`の既定の出力と同じ形式）。
+//! 辞書の再学習（`train`、feature `train`）と、分割の精度の評価（[`crate::eval`]）に使う
+
+use std::path::Path;
+use readmaker_shared::{ReadMakerError, Result};
+
+/// 正解の形態素
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedWord {
+    pub surface: String,
+    /// 素性（IPADICなら「品詞,品詞細分類1,…,読み,発音」）
+    pub feature: String,
+}
+
+/// 正解の分割が付いた1文
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnnotatedSentence {
+    pub words: Vec<AnnotatedWord>,
+}
+
+impl AnnotatedSentence {
+    /// 表層形をつなげた本文
+    pub fn text(&self) -> String {
+        self.words.iter().map(|w| w.surface.as_str()).collect()
+    }
+
+    /// 各形態素の文字オフセット（開始, 終了）
+    pub fn spans(&self) -> Vec<(usize, usize)> {
+        let mut start = 0;
+        self.words
+            .iter()
+            .map(|w| {
+                let end = start + w.surface.chars().count();
+                let span = (start, end);
+                start = end;
+                span
+            })
+            .collect()
+    }
+}
+
+/// 形態素解析済みのコーパス
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnnotatedCorpus {
+    pub sentences: Vec<AnnotatedSentence>,
+}
+
+impl AnnotatedCorpus {
+    /// MeCab形式のテキストを読む（空行は読み飛ばし、形態素のない文は除く）
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut sentences = Vec::new();
+        let mut words = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line == "EOS" {
+                if !words.is_empty() {
+                    sentences.push(AnnotatedSentence { words: std::mem::take(&mut words) });
+                }
+                continue;
+            }
+            match line.split_once('\t') {
+                Some((surface, feature)) if !surface.is_empty() && !feature.contains('\t') => {
+                    words.push(AnnotatedWord { surface: surface.to_string(), feature: feature.to_string() });
+                }
+                _ => {
+                    return Err(ReadMakerError::Validation(format!(
+                        "{}行目が「表層形<TAB>素性」でも EOS でもありません: {}",
+                        i + 1,
+                        line
+                    )))
+                }
+            }
+        }
+        if !words.is_empty() {
+            sentences.push(AnnotatedSentence { words });
+        }
+        Ok(Self { sentences })
+    }
+
+    /// ファイルから読む（UTF-8）
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ReadMakerError::Validation(format!("コーパスを読み込めません: {}: {}", path.display(), e)))?;
+        Self::parse(&text)
+    }
+
+    /// MeCab形式のテキストにする
+    pub fn to_mecab(&self) -> String {
+        let mut out = String::new();
+        for sentence in &self.sentences {
+            for word in &sentence.words {
+                out.push_str(&word.surface);
+                out.push('\t');
+                out.push_str(&word.feature);
+                out.push('\n');
+            }
+            out.push_str("EOS\n");
+        }
+        out
+    }
+
+    /// 形態素の数
+    pub fn words(&self) -> usize {
+        self.sentences.iter().map(|s| s.words.len()).sum()
+    }
+
+    /// 学習用と評価用に分ける（`held_out_ratio`の割合の文を、コーパス全体から等間隔に評価用にする）
+    ///
+    /// 乱数を使わないため、同じコーパスからは常に同じ分け方になる。学習用の文が残らない割合はエラー
+    pub fn split_held_out(&self, held_out_ratio: f64) -> Result<(Self, Self)> {
+        if !(0.0..1.0).contains(&held_out_ratio) {
+            return Err(ReadMakerError::Validation(format!(
+                "評価用の割合は0以上1未満で指定してください: {}",
+                held_out_ratio
+            )));
+        }
+        let len = self.sentences.len();
+        let held_out_len = (len as f64 * held_out_ratio).round() as usize;
+        if len > 0 && held_out_len >= len {
+            return Err(ReadMakerError::Validation(format!(
+                "評価用の割合が大きすぎて学習用の文が残りません: {}文中{}文",
+                len, held_out_len
+            )));
+        }
+        let (mut train, mut held_out) = (Self::default(), Self::default());
+        for (i, sentence) in self.sentences.iter().enumerate() {
+            // `held_out_len / len`の間隔で区切った各区間の最後の文を評価用にする
+            let is_held_out = (i + 1) * held_out_len / len > i * held_out_len / len;
+            let target = if is_held_out { &mut held_out } else { &mut train };
+            target.sentences.push(sentence.clone());
+        }
+        Ok((train, held_out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &str = "吾輩\t名詞,代名詞,一般,*,*,*,吾輩,ワガハイ,ワガハイ
+は\t助詞,係助詞,*,*,*,*,は,ハ,ワ
+猫\t名詞,一般,*,*,*,*,猫,ネコ,ネコ
+で\t助動詞,*,*,*,特殊・ダ,連用形,だ,デ,デ
+ある\t助動詞,*,*,*,五段・ラ行アル,基本形,ある,アル,アル
+EOS
+
+今日\t名詞,副詞可能,*,*,*,*,今日,キョウ,キョー
+は\t助詞,係助詞,*,*,*,*,は,ハ,ワ
+良い天気\t名詞,一般,*,*,*,*,*
+EOS
+EOS
+";
+
+    #[test]
+    fn test_parse_and_write() {
+        let corpus = AnnotatedCorpus::parse(CORPUS).unwrap();
+        assert_eq!((corpus.sentences.len(), corpus.words()), (2, 8));
+        assert_eq!(corpus.sentences[0].text(), "吾輩は猫である");
+        assert_eq!(corpus.sentences[1].spans(), vec![(0, 2), (2, 3), (3, 7)]);
+        assert_eq!(AnnotatedCorpus::parse(&corpus.to_mecab()).unwrap(), corpus);
+        assert!(AnnotatedCorpus::parse("猫 名詞\nEOS\n").is_err());
+    }
+
+    #[test]
+    fn test_split_held_out() {
+        let sentence = |s: &str| AnnotatedSentence { words: vec![AnnotatedWord { surface: s.into(), feature: "*".into() }] };
+        let corpus = AnnotatedCorpus { sentences: (0..10).map(|i| sentence(&i.to_string())).collect() };
+        let (train, held_out) = corpus.split_held_out(0.2).unwrap();
+        assert_eq!((train.sentences.len(), held_out.sentences.len()), (8, 2));
+        let texts: Vec<_> = held_out.sentences.iter().map(AnnotatedSentence::text).collect();
+        assert_eq!(texts, vec!["4", "9"]);
+        // 評価用の文数は割合から決める（0.4なら10文中4文）
+        let (train, held_out) = corpus.split_held_out(0.4).unwrap();
+        assert_eq!((train.sentences.len(), held_out.sentences.len()), (6, 4));
+        assert_eq!(corpus.split_held_out(0.3).unwrap().1.sentences.len(), 3);
+        // 学習用の文が残らない割合はエラー
+        assert!(corpus.split_held_out(0.96).is_err());
+        assert!(corpus.split_held_out(1.0).is_err());
+        assert!(corpus.split_held_out(-0.1).is_err());
+        assert!(AnnotatedCorpus { sentences: vec![sentence("0")] }.split_held_out(0.5).is_err());
+        assert_eq!(corpus.split_held_out(0.0).unwrap().1.sentences.len(), 0);
+    }
+}
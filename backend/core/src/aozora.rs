@@ -0,0 +1,267 @@
+//! 青空文庫テキストのインポーター
+//! 注記（［＃…］）・ルビ（｜《》）・ヘッダー/フッターを取り除き、ルビと見出しを保った構造化ドキュメントにする
+
+use encoding_rs::SHIFT_JIS;
+use crate::document::{Document, ParagraphBuilder, SourceFormat};
+use crate::segmenter::{script_of, Script};
+
+/// 外字注記（※［＃…］）の代わりに置く文字
+const GAIJI_PLACEHOLDER: char = '〓';
+
+/// バイト列を文字列にする（UTF-8でなければShift_JISとして読む）
+pub fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => SHIFT_JIS.decode(bytes).0.into_owned(),
+    };
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// 青空文庫形式のバイト列を読み込む
+pub fn parse_bytes(bytes: &[u8]) -> Document {
+    parse(&decode(bytes))
+}
+
+/// 青空文庫形式のテキストを構造化ドキュメントにする
+pub fn parse(text: &str) -> Document {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut doc = Document::new(SourceFormat::Aozora);
+
+    // ヘッダー: 先頭から最初の空行（または区切り線）まで
+    let header_end = lines
+        .iter()
+        .position(|l| l.trim().is_empty() || is_separator(l))
+        .unwrap_or(lines.len());
+    let header: Vec<String> = lines[..header_end]
+        .iter()
+        .map(|l| strip_inline(l))
+        .filter(|l| !l.is_empty())
+        .collect();
+    doc.title = header.first().cloned();
+    if header.len() >= 2 {
+        doc.author = header[1..]
+            .iter()
+            .rev()
+            .find(|l| !l.ends_with('訳') && !l.ends_with('編'))
+            .or(header.last())
+            .cloned();
+    }
+
+    // 「テキスト中に現れる記号について」の区切り線ブロックを飛ばす
+    let mut body_start = header_end;
+    let separators: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .skip(header_end)
+        .filter(|(_, l)| is_separator(l))
+        .map(|(i, _)| i)
+        .take(2)
+        .collect();
+    if let [_, second] = separators[..] {
+        body_start = second + 1;
+    }
+
+    // フッター: 「底本：」以降
+    let body_end = lines
+        .iter()
+        .enumerate()
+        .skip(body_start)
+        .find(|(_, l)| l.starts_with("底本：") || l.starts_with("底本:"))
+        .map(|(i, _)| i)
+        .unwrap_or(lines.len());
+
+    let mut builder = ParagraphBuilder::new();
+    for line in &lines[body_start..body_end] {
+        let heading = parse_line(line, &mut builder);
+        let paragraph = builder.finish();
+        if paragraph.text.is_empty() {
+            continue;
+        }
+        if heading {
+            doc.push_heading(paragraph.text);
+        } else {
+            doc.push_paragraph(paragraph);
+        }
+    }
+    doc
+}
+
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 10 && line.chars().all(|c| c == '-' || c == '－')
+}
+
+/// 注記・ルビを除いた文字列（ヘッダー用）
+fn strip_inline(line: &str) -> String {
+    let mut builder = ParagraphBuilder::new();
+    parse_line(line, &mut builder);
+    builder.finish().text
+}
+
+/// 1行を解析して`builder`に積む。見出し行なら`true`を返す
+fn parse_line(line: &str, builder: &mut ParagraphBuilder) -> bool {
+    let chars: Vec<char> = line.chars().collect();
+    let mut heading = false;
+    let mut ruby_start: Option<usize> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '｜' => {
+                ruby_start = Some(builder.len());
+                i += 1;
+            }
+            '《' => {
+                let Some(close) = find_from(&chars, i + 1, '》') else {
+                    builder.push(chars[i]);
+                    i += 1;
+                    continue;
+                };
+                let reading = strip_annotations(&chars[i + 1..close]);
+                let start = ruby_start.take().unwrap_or_else(|| implicit_ruby_start(builder));
+                builder.add_ruby(start, &reading);
+                i = close + 1;
+            }
+            '［' if chars.get(i + 1) == Some(&'＃') => {
+                let close = find_annotation_end(&chars, i);
+                let note: String = chars[i + 2..close].iter().collect();
+                if note.contains("見出し") && !note.starts_with("ここで") {
+                    heading = true;
+                }
+                // 外字注記は※を置き換える
+                if builder.tail(1) == ['※'] {
+                    builder.pop();
+                    builder.push(GAIJI_PLACEHOLDER);
+                }
+                i = (close + 1).min(chars.len());
+            }
+            '／' if chars.get(i + 1) == Some(&'＼') => {
+                builder.push_str("〳〵");
+                i += 2;
+            }
+            '／' if chars.get(i + 1) == Some(&'″') && chars.get(i + 2) == Some(&'＼') => {
+                builder.push_str("〴〵");
+                i += 3;
+            }
+            ch => {
+                builder.push(ch);
+                i += 1;
+            }
+        }
+    }
+    heading
+}
+
+fn find_from(chars: &[char], from: usize, target: char) -> Option<usize> {
+    chars[from..].iter().position(|&c| c == target).map(|p| from + p)
+}
+
+/// `［＃`に対応する`］`の位置（入れ子を考慮、閉じていなければ行末）
+fn find_annotation_end(chars: &[char], open: usize) -> usize {
+    let mut depth = 0;
+    for (i, &c) in chars.iter().enumerate().skip(open) {
+        match c {
+            '［' => depth += 1,
+            '］' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    chars.len()
+}
+
+/// ルビ文字列中の注記を除く
+fn strip_annotations(chars: &[char]) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '［' && chars.get(i + 1) == Some(&'＃') {
+            i = find_annotation_end(chars, i) + 1;
+        } else {
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// ｜がない場合の親文字の開始位置（直前の同じ文字種の連続）
+fn implicit_ruby_start(builder: &ParagraphBuilder) -> usize {
+    let tail = builder.tail(builder.len());
+    let Some(&last) = tail.last() else {
+        return 0;
+    };
+    let script = script_of(last);
+    let run = tail.iter().rev().take_while(|&&c| script_of(c) == script && script != Script::Space).count();
+    builder.len() - run.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "吾輩は猫である
+夏目漱石
+
+-------------------------------------------------------
+【テキスト中に現れる記号について】
+
+《》：ルビ
+（例）吾輩《わがはい》
+-------------------------------------------------------
+
+［＃８字下げ］一［＃「一」は中見出し］
+
+　吾輩《わがはい》は猫である。名前はまだ無い。
+　どこで生れたかとんと｜見当《けんとう》がつかぬ。※［＃「てへん＋劣」、第3水準1-84-77］
+
+［＃８字下げ］二［＃「二」は中見出し］
+
+　時々／＼見物に出る。［＃「見物」に傍点］
+
+底本：「吾輩は猫である」岩波文庫、岩波書店
+入力：校正者
+";
+
+    #[test]
+    fn test_header_and_footer() {
+        let doc = parse(SAMPLE);
+        assert_eq!(doc.format, SourceFormat::Aozora);
+        assert_eq!(doc.title.as_deref(), Some("吾輩は猫である"));
+        assert_eq!(doc.author.as_deref(), Some("夏目漱石"));
+        assert!(!doc.plain_text().contains("底本"));
+        assert!(!doc.plain_text().contains("記号について"));
+    }
+
+    #[test]
+    fn test_chapters_and_markup() {
+        let doc = parse(SAMPLE);
+        assert_eq!(doc.chapters.len(), 2);
+        assert_eq!(doc.chapters[0].title.as_deref(), Some("一"));
+        assert_eq!(doc.chapters[1].title.as_deref(), Some("二"));
+
+        let first = &doc.chapters[0].paragraphs[0];
+        assert_eq!(first.text, "吾輩は猫である。名前はまだ無い。");
+        assert_eq!(first.ruby[0].reading, "わがはい");
+        assert_eq!(first.ruby_base(&first.ruby[0]), "吾輩");
+
+        let second = &doc.chapters[0].paragraphs[1];
+        assert_eq!(second.text, "どこで生れたかとんと見当がつかぬ。〓");
+        assert_eq!(second.ruby_base(&second.ruby[0]), "見当");
+
+        assert_eq!(doc.chapters[1].paragraphs[0].text, "時々〳〵見物に出る。");
+    }
+
+    #[test]
+    fn test_decode_shift_jis() {
+        let (bytes, _, _) = SHIFT_JIS.encode("吾輩《わがはい》は猫である\r\n");
+        let doc = parse_bytes(&bytes);
+        assert_eq!(doc.title.as_deref(), Some("吾輩は猫である"));
+        assert_eq!(decode("テスト".as_bytes()), "テスト");
+    }
+}
@@ -0,0 +1,344 @@
+//! 形態素解析のバックエンド
+//! `MorphAnalyzer`が使う分割器の差し替え口。Vibrato（辞書）と規則ベース（文字種・句読点）を用意し、
+//! Linderaはfeature`lindera`で有効にする。まとまり分割・ふりがな・統計はどのバックエンドの結果にも使える
+
+use std::sync::OnceLock;
+#[cfg(feature = "lindera")]
+use readmaker_shared::ReadMakerError;
+use readmaker_shared::{Result, WordAnalysis};
+use sha2::{Digest, Sha256};
+use vibrato::{Dictionary, Tokenizer};
+use crate::fallback::{split_punctuation, Engine, FallbackPolicy};
+use crate::report::Token;
+use crate::segmenter::segment;
+use crate::{hex_prefix, mixed, read_dictionary_with_digest, LATIN_WORD_FEATURE};
+
+/// 形態素解析のバックエンド
+pub trait TokenizerBackend: Send + Sync {
+    /// 結果に記録するエンジン
+    fn engine(&self) -> Engine;
+
+    /// 分割結果を区別する識別子（辞書のハッシュなど。解析結果のキャッシュのキーに使う）
+    fn version(&self) -> &str;
+
+    /// 読み・品詞・原形・文字オフセット付きで分割する
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>>;
+
+    /// 分割に使うVibratoの辞書（辞書を使わないバックエンドでは`None`）
+    fn dictionary(&self) -> Option<&Dictionary> {
+        None
+    }
+
+    /// `Analysis`の形で分割する（既定では品詞と細分類をカンマでつないで`part_of_speech`にする）
+    fn analyze_words(&self, text: &str) -> Result<Vec<WordAnalysis>> {
+        Ok(self.tokenize(text)?.into_iter().map(word_from_token).collect())
+    }
+}
+
+fn word_from_token(token: Token) -> WordAnalysis {
+    let feature = std::iter::once(token.pos).chain(token.pos_detail).collect::<Vec<_>>().join(",");
+    WordAnalysis {
+        surface: token.surface,
+        reading: token.reading,
+        part_of_speech: feature.clone(),
+        features: vec![feature],
+    }
+}
+
+/// Vibrato（IPADIC形式の辞書）による形態素解析
+///
+/// ラテン文字の区間は未知語処理に回さず、単語境界で区切る
+pub struct VibratoBackend {
+    tokenizer: Tokenizer,
+    /// [`crate::dictionary_digest`]と同じ値（読み込み済みの辞書から作った場合は初めて使うときに計算する）
+    dictionary_version: OnceLock<String>,
+}
+
+impl VibratoBackend {
+    /// 読み込み済みの辞書から作成
+    ///
+    /// バージョンは`version`を初めて呼んだときに辞書を書き出して計算する（書き出しは読み込みの逆なので同じ値になる）
+    pub fn from_dictionary(dict: Dictionary) -> Self {
+        Self { tokenizer: Tokenizer::new(dict), dictionary_version: OnceLock::new() }
+    }
+
+    /// メモリ上の辞書データから作成（zstd圧縮/非圧縮の両対応、バージョンは読み込みながら計算する）
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let (dict, version) = read_dictionary_with_digest(data)?;
+        Ok(Self { tokenizer: Tokenizer::new(dict), dictionary_version: OnceLock::from(version) })
+    }
+}
+
+impl TokenizerBackend for VibratoBackend {
+    fn engine(&self) -> Engine {
+        Engine::Vibrato
+    }
+
+    fn version(&self) -> &str {
+        self.dictionary_version.get_or_init(|| {
+            let mut hasher = Sha256::new();
+            match self.tokenizer.dictionary().write(&mut hasher) {
+                Ok(_) => hex_prefix(&hasher.finalize()),
+                Err(_) => "unknown".to_string(),
+            }
+        })
+    }
+
+    fn dictionary(&self) -> Option<&Dictionary> {
+        Some(self.tokenizer.dictionary())
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>> {
+        let mut worker = self.tokenizer.new_worker();
+        let mut tokens = Vec::new();
+
+        for span in mixed::split_spans(text) {
+            if span.kind == mixed::SpanKind::Latin {
+                tokens.extend(
+                    span.words()
+                        .into_iter()
+                        .map(|w| Token::from_feature(&w.text, LATIN_WORD_FEATURE, w.start, w.end)),
+                );
+                continue;
+            }
+
+            worker.reset_sentence(&span.text);
+            worker.tokenize();
+            for i in 0..worker.num_tokens() {
+                let token = worker.token(i);
+                let range = token.range_char();
+                tokens.push(Token::from_feature(
+                    token.surface(),
+                    token.feature(),
+                    span.start + range.start,
+                    span.start + range.end,
+                ));
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// 品詞には辞書の素性をそのまま入れる
+    fn analyze_words(&self, text: &str) -> Result<Vec<WordAnalysis>> {
+        let mut worker = self.tokenizer.new_worker();
+        let mut words = Vec::new();
+
+        for span in mixed::split_spans(text) {
+            if span.kind == mixed::SpanKind::Latin {
+                words.extend(span.words().into_iter().map(|w| WordAnalysis {
+                    reading: w.text.clone(),
+                    surface: w.text,
+                    part_of_speech: LATIN_WORD_FEATURE.to_string(),
+                    features: vec![LATIN_WORD_FEATURE.to_string()],
+                }));
+                continue;
+            }
+
+            worker.reset_sentence(&span.text);
+            worker.tokenize();
+            for i in 0..worker.num_tokens() {
+                let token = worker.token(i);
+                words.push(WordAnalysis {
+                    surface: token.surface().to_string(),
+                    reading: token.surface().to_string(), // Vibratoでは読みが直接取得できない場合は表面形を使用
+                    part_of_speech: token.feature().to_string(),
+                    features: vec![token.feature().to_string()], // 単一の特徴文字列をベクターに格納
+                });
+            }
+        }
+
+        Ok(words)
+    }
+}
+
+/// 辞書を使わない規則ベースの分割（品詞は`*`、読みは表層形）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleBasedBackend {
+    /// 文字種（漢字・ひらがな・カタカナ等）の切れ目で分割（[`crate::segmenter`]）
+    CharClass,
+    /// 句読点・空白で分割（区切り文字は形態素に含めない）
+    Punctuation,
+}
+
+impl RuleBasedBackend {
+    /// フォールバック方針に対応する分割（`Error`の場合は`None`）
+    pub fn for_policy(policy: FallbackPolicy) -> Option<Self> {
+        match policy {
+            FallbackPolicy::Error => None,
+            FallbackPolicy::CharClass => Some(Self::CharClass),
+            FallbackPolicy::Punctuation => Some(Self::Punctuation),
+        }
+    }
+
+    /// 分割する（失敗しない）
+    pub fn segment(self, text: &str) -> Vec<Token> {
+        match self {
+            Self::CharClass => segment(text).into_iter().map(|s| Token::plain(&s.surface, s.start, s.end)).collect(),
+            Self::Punctuation => locate_surfaces(text, &split_punctuation(text)),
+        }
+    }
+}
+
+impl TokenizerBackend for RuleBasedBackend {
+    fn engine(&self) -> Engine {
+        match self {
+            Self::CharClass => Engine::CharClass,
+            Self::Punctuation => Engine::Punctuation,
+        }
+    }
+
+    fn version(&self) -> &str {
+        self.engine().as_str()
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>> {
+        Ok(self.segment(text))
+    }
+}
+
+/// 表層形を先頭から順に探してオフセットを付ける
+fn locate_surfaces(text: &str, surfaces: &[String]) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    let mut tokens = Vec::with_capacity(surfaces.len());
+    for surface in surfaces {
+        let needle: Vec<char> = surface.chars().collect();
+        let start = (pos..=chars.len().saturating_sub(needle.len()))
+            .find(|&i| chars[i..].starts_with(&needle))
+            .unwrap_or(pos);
+        pos = start + needle.len();
+        tokens.push(Token::plain(surface, start, pos));
+    }
+    tokens
+}
+
+/// Lindera（IPADIC・UniDicなどLindera形式の辞書）による形態素解析
+#[cfg(feature = "lindera")]
+pub struct LinderaBackend {
+    segmenter: lindera::segmenter::Segmenter,
+    version: String,
+}
+
+#[cfg(feature = "lindera")]
+impl LinderaBackend {
+    /// 辞書のディレクトリのパス、または`embedded://ipadic`などのURIから作成
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        let dictionary = lindera::dictionary::load_dictionary(uri)
+            .map_err(|e| ReadMakerError::Analysis(format!("Linderaの辞書読み込みエラー: {}: {}", uri, e)))?;
+        Ok(Self {
+            segmenter: lindera::segmenter::Segmenter::new(lindera::mode::Mode::Normal, dictionary, None),
+            version: format!("lindera:{}", uri),
+        })
+    }
+}
+
+#[cfg(feature = "lindera")]
+impl TokenizerBackend for LinderaBackend {
+    fn engine(&self) -> Engine {
+        Engine::Lindera
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>> {
+        let tokens = self
+            .segmenter
+            .segment(std::borrow::Cow::Borrowed(text))
+            .map_err(|e| ReadMakerError::Analysis(format!("Linderaの解析エラー: {}", e)))?;
+        // バイト位置を文字位置に直す（形態素は先頭から順に並ぶ）
+        let (mut byte_pos, mut char_pos) = (0, 0);
+        let mut result = Vec::with_capacity(tokens.len());
+        for mut token in tokens {
+            char_pos += text[byte_pos..token.byte_start].chars().count();
+            let start = char_pos;
+            char_pos += token.surface.chars().count();
+            byte_pos = token.byte_end;
+            let feature = token.details().join(",");
+            result.push(Token::from_feature(&token.surface, &feature, start, char_pos));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{analyze_report, ReportOptions};
+    use crate::testutil::mini_dictionary;
+    use crate::{furigana, MorphAnalyzer};
+
+    /// 1文字ずつ区切るだけのバックエンド（差し替えの確認用）
+    struct PerChar;
+
+    impl TokenizerBackend for PerChar {
+        fn engine(&self) -> Engine {
+            Engine::CharClass
+        }
+
+        fn version(&self) -> &str {
+            "per-char"
+        }
+
+        fn tokenize(&self, text: &str) -> Result<Vec<Token>> {
+            Ok(text.chars().enumerate().map(|(i, c)| Token::plain(&c.to_string(), i, i + 1)).collect())
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let analyzer = MorphAnalyzer::with_backend(PerChar);
+        assert_eq!(analyzer.engine(), Engine::CharClass);
+        assert_eq!(analyzer.dictionary_version(), "per-char");
+        assert_eq!(analyzer.analyze_words("猫です").unwrap(), vec!["猫", "で", "す"]);
+
+        let report = analyze_report(Some(&analyzer), "猫です。", &ReportOptions::default()).unwrap();
+        let chunks: Vec<_> = report.chunks.unwrap().into_iter().map(|c| c.text).collect();
+        assert_eq!(chunks, vec!["猫", "で", "す。"]);
+    }
+
+    #[test]
+    fn test_rule_based_backend() {
+        let tokens = RuleBasedBackend::Punctuation.tokenize("今日は、良い天気です。 明日も").unwrap();
+        let spans: Vec<_> = tokens.iter().map(|t| (t.surface.as_str(), t.start, t.end)).collect();
+        assert_eq!(spans, vec![("今日は", 0, 3), ("良い天気です", 4, 10), ("明日も", 12, 15)]);
+
+        let words = RuleBasedBackend::CharClass.analyze_words("今日は良い天気です。").unwrap();
+        assert_eq!(words[0].surface, "今日は");
+        assert_eq!(words[0].part_of_speech, "*");
+        assert!(RuleBasedBackend::for_policy(FallbackPolicy::Error).is_none());
+    }
+
+    #[test]
+    fn test_backends_share_furigana() {
+        let text = "今日は良い天気です。";
+        let vibrato = VibratoBackend::from_dictionary(mini_dictionary()).tokenize(text).unwrap();
+        assert_eq!(furigana(&vibrato)[0].reading, "きょう");
+        // 読みのないバックエンドではルビを振らない
+        assert!(furigana(&RuleBasedBackend::CharClass.tokenize(text).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_dictionary_version_is_same_for_every_load_path() {
+        let mut raw = Vec::new();
+        mini_dictionary().write(&mut raw).unwrap();
+        let expected = crate::dictionary_digest(&raw).unwrap();
+        assert_eq!(VibratoBackend::from_bytes(&raw).unwrap().version(), expected);
+        assert_eq!(VibratoBackend::from_dictionary(mini_dictionary()).version(), expected);
+
+        #[cfg(feature = "zstd")]
+        {
+            let compressed = zstd::encode_all(raw.as_slice(), 3).unwrap();
+            assert_eq!(crate::dictionary_digest(&compressed).unwrap(), expected);
+            assert_eq!(VibratoBackend::from_bytes(&compressed).unwrap().version(), expected);
+        }
+    }
+
+    #[cfg(feature = "lindera")]
+    #[test]
+    fn test_lindera_missing_dictionary() {
+        assert!(LinderaBackend::from_uri("testdata/no-such-lindera-dictionary").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! 解析速度の計測
+//! コーパス（1行1文）を繰り返し解析し、スループット・レイテンシのパーセンタイル・ピークメモリを集計する。
+//! 結果はJSONで保存し、保存済みのベースラインと比べて性能の後退を検出する
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use readmaker_shared::{ReadMakerError, Result};
+use crate::MorphAnalyzer;
+
+/// 結果のJSONの形式のバージョン（項目を変えたら上げる。異なるベースラインとは比べない）
+pub const BENCHMARK_SCHEMA_VERSION: u32 = 1;
+
+/// 計測する文の集まり
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    pub name: String,
+    pub sentences: Vec<String>,
+}
+
+impl Corpus {
+    /// テキストから作る（空行は除く）
+    pub fn from_text(name: impl Into<String>, text: &str) -> Self {
+        Self {
+            name: name.into(),
+            sentences: text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect(),
+        }
+    }
+
+    /// ファイルから読む（名前はファイル名）
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ReadMakerError::Validation(format!("コーパスを読み込めません: {}: {}", path.display(), e)))?;
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        Ok(Self::from_text(name, &text))
+    }
+
+    pub fn chars(&self) -> usize {
+        self.sentences.iter().map(|s| s.chars().count()).sum()
+    }
+}
+
+/// 1文あたりの解析時間（マイクロ秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    pub fn from_durations(durations: &[Duration]) -> Self {
+        if durations.is_empty() {
+            return Self::default();
+        }
+        let mut micros: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1e6).collect();
+        micros.sort_by(f64::total_cmp);
+        Self {
+            p50: percentile(&micros, 50.0),
+            p95: percentile(&micros, 95.0),
+            p99: percentile(&micros, 99.0),
+            mean: micros.iter().sum::<f64>() / micros.len() as f64,
+            max: micros[micros.len() - 1],
+        }
+    }
+}
+
+/// 昇順に並んだ値のパーセンタイル（最近順位法）
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// コーパスごとの結果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorpusResult {
+    pub name: String,
+    pub sentences: usize,
+    pub chars: usize,
+    pub chars_per_sec: f64,
+    pub latency_us: LatencySummary,
+}
+
+/// 計測結果（ベースラインとして保存する）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub schema_version: u32,
+    /// 辞書のバージョン（[`MorphAnalyzer::dictionary_version`]）
+    pub dictionary: String,
+    pub dictionary_load_ms: f64,
+    /// 各コーパスを解析した回数（ウォームアップを除く）
+    pub iterations: usize,
+    pub chars_per_sec: f64,
+    pub latency_us: LatencySummary,
+    /// 計測を終えた時点のピークメモリ（取得できないプラットフォームでは`None`）
+    pub peak_memory_bytes: Option<u64>,
+    pub corpora: Vec<CorpusResult>,
+}
+
+impl BenchmarkResult {
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| ReadMakerError::Internal(format!("計測結果のJSON化に失敗しました: {}", e)))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| ReadMakerError::Validation(format!("計測結果のJSONを読めません: {}", e)))
+    }
+}
+
+/// コーパスを`iterations`回ずつ解析して計測する（最初の1回はウォームアップとして集計しない）
+///
+/// `dictionary_load`は辞書の読み込みにかかった時間（呼び出し側で計る）
+pub fn run_benchmark(
+    analyzer: &MorphAnalyzer,
+    dictionary_load: Duration,
+    corpora: &[Corpus],
+    iterations: usize,
+) -> Result<BenchmarkResult> {
+    let iterations = iterations.max(1);
+    let mut all_durations = Vec::new();
+    let (mut total_chars, mut total_time) = (0usize, Duration::ZERO);
+    let mut results = Vec::with_capacity(corpora.len());
+
+    for corpus in corpora {
+        for sentence in &corpus.sentences {
+            analyzer.tokenize(sentence)?;
+        }
+        let mut durations = Vec::with_capacity(corpus.sentences.len() * iterations);
+        for _ in 0..iterations {
+            for sentence in &corpus.sentences {
+                let start = Instant::now();
+                analyzer.tokenize(sentence)?;
+                durations.push(start.elapsed());
+            }
+        }
+        let elapsed: Duration = durations.iter().sum();
+        let chars = corpus.chars() * iterations;
+        results.push(CorpusResult {
+            name: corpus.name.clone(),
+            sentences: corpus.sentences.len(),
+            chars: corpus.chars(),
+            chars_per_sec: throughput(chars, elapsed),
+            latency_us: LatencySummary::from_durations(&durations),
+        });
+        total_chars += chars;
+        total_time += elapsed;
+        all_durations.extend(durations);
+    }
+
+    Ok(BenchmarkResult {
+        schema_version: BENCHMARK_SCHEMA_VERSION,
+        dictionary: analyzer.dictionary_version().to_string(),
+        dictionary_load_ms: dictionary_load.as_secs_f64() * 1000.0,
+        iterations,
+        chars_per_sec: throughput(total_chars, total_time),
+        latency_us: LatencySummary::from_durations(&all_durations),
+        peak_memory_bytes: peak_memory_bytes(),
+        corpora: results,
+    })
+}
+
+fn throughput(chars: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        chars as f64 / elapsed.as_secs_f64()
+    }
+}
+
+/// プロセスのピークメモリ（Linux・Androidの`/proc/self/status`の`VmHWM`）
+pub fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// ベースラインより悪くなった項目
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    /// 悪化した割合（0.1なら10%悪化）
+    pub change: f64,
+}
+
+/// ベースラインと比べ、`tolerance`（0.1なら10%）を超えて悪くなった項目を返す
+///
+/// スループットは下がると、レイテンシ・読み込み時間・ピークメモリは上がると悪化とみなす
+pub fn compare_with_baseline(baseline: &BenchmarkResult, current: &BenchmarkResult, tolerance: f64) -> Result<Vec<Regression>> {
+    if baseline.schema_version != current.schema_version {
+        return Err(ReadMakerError::Validation(format!(
+            "ベースラインの形式が異なります（ベースライン: {}、今回: {}）",
+            baseline.schema_version, current.schema_version
+        )));
+    }
+
+    let mut metrics = vec![
+        ("chars_per_sec", baseline.chars_per_sec, current.chars_per_sec, false),
+        ("latency_p50_us", baseline.latency_us.p50, current.latency_us.p50, true),
+        ("latency_p95_us", baseline.latency_us.p95, current.latency_us.p95, true),
+        ("latency_p99_us", baseline.latency_us.p99, current.latency_us.p99, true),
+        ("dictionary_load_ms", baseline.dictionary_load_ms, current.dictionary_load_ms, true),
+    ];
+    if let (Some(base), Some(cur)) = (baseline.peak_memory_bytes, current.peak_memory_bytes) {
+        metrics.push(("peak_memory_bytes", base as f64, cur as f64, true));
+    }
+
+    Ok(metrics
+        .into_iter()
+        .filter(|&(_, base, _, _)| base > 0.0)
+        .filter_map(|(metric, base, cur, lower_is_better)| {
+            let change = if lower_is_better { cur / base - 1.0 } else { 1.0 - cur / base };
+            (change > tolerance).then_some(Regression { metric, baseline: base, current: cur, change })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::mini_dictionary;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let durations: Vec<Duration> = (1..=100).map(Duration::from_micros).collect();
+        let summary = LatencySummary::from_durations(&durations);
+        assert_eq!((summary.p50, summary.p95, summary.p99, summary.max), (50.0, 95.0, 99.0, 100.0));
+        assert!((summary.mean - 50.5).abs() < 1e-6);
+        assert_eq!(LatencySummary::from_durations(&[]), LatencySummary::default());
+    }
+
+    #[test]
+    fn test_run_benchmark() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let corpus = Corpus::from_text("mini", "今日は良い天気です。\n\n吾輩は猫である。\n");
+        assert_eq!((corpus.sentences.len(), corpus.chars()), (2, 18));
+
+        let result = run_benchmark(&analyzer, Duration::from_millis(12), &[corpus], 3).unwrap();
+        assert_eq!(result.iterations, 3);
+        assert_eq!(result.dictionary, analyzer.dictionary_version());
+        assert!((result.dictionary_load_ms - 12.0).abs() < 1e-6);
+        assert!(result.chars_per_sec > 0.0);
+        assert_eq!(result.corpora[0].sentences, 2);
+
+        // JSONの小数は最下位の桁が丸まることがあるため、許容差つきで比べる
+        let restored = BenchmarkResult::from_json(&result.to_json().unwrap()).unwrap();
+        assert_eq!((&restored.dictionary, restored.iterations, restored.corpora.len()), (&result.dictionary, 3, 1));
+        assert!((restored.chars_per_sec - result.chars_per_sec).abs() <= result.chars_per_sec * 1e-12);
+        assert!((restored.latency_us.p99 - result.latency_us.p99).abs() <= result.latency_us.p99 * 1e-12);
+    }
+
+    #[test]
+    fn test_compare_with_baseline() {
+        let baseline = BenchmarkResult {
+            schema_version: BENCHMARK_SCHEMA_VERSION,
+            dictionary: "abc".to_string(),
+            dictionary_load_ms: 100.0,
+            iterations: 5,
+            chars_per_sec: 1_000_000.0,
+            latency_us: LatencySummary { p50: 10.0, p95: 20.0, p99: 30.0, mean: 12.0, max: 40.0 },
+            peak_memory_bytes: Some(1000),
+            corpora: Vec::new(),
+        };
+        let mut current = baseline.clone();
+        current.chars_per_sec = 950_000.0;
+        current.latency_us.p99 = 40.0;
+        current.peak_memory_bytes = None;
+
+        let regressions = compare_with_baseline(&baseline, &current, 0.1).unwrap();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].metric, "latency_p99_us");
+        assert!((regressions[0].change - 1.0 / 3.0).abs() < 1e-9);
+
+        // 5%の低下は許容範囲内、10%を超える低下は後退
+        current.chars_per_sec = 850_000.0;
+        let metrics: Vec<_> = compare_with_baseline(&baseline, &current, 0.1).unwrap().into_iter().map(|r| r.metric).collect();
+        assert_eq!(metrics, vec!["chars_per_sec", "latency_p99_us"]);
+
+        current.schema_version += 1;
+        assert!(compare_with_baseline(&baseline, &current, 0.1).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+//! 解析結果のキャッシュ
+//! テキストのハッシュ・辞書のバージョン・オプションをキーに、`analyze_report`の結果をLRUで保持する
+
+use std::mem::size_of;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use lru::LruCache;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use readmaker_shared::Result;
+use crate::fallback::FallbackPolicy;
+use crate::report::{analyze_report, Chunk, Report, ReportOptions, Sentence, Token, REPORT_SCHEMA_VERSION};
+use crate::MorphAnalyzer;
+
+/// 辞書なしで解析した結果の辞書のバージョン
+pub const NO_DICTIONARY: &str = "none";
+
+/// キャッシュのキー（テキストそのものは持たない）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// テキストのSHA-256
+    pub text_hash: [u8; 32],
+    /// `MorphAnalyzer::dictionary_version`（辞書なしなら`NO_DICTIONARY`）
+    pub dictionary: String,
+    pub options: ReportOptions,
+}
+
+impl CacheKey {
+    pub fn new(analyzer: Option<&MorphAnalyzer>, text: &str, options: &ReportOptions) -> Self {
+        Self {
+            text_hash: Sha256::digest(text.as_bytes()).into(),
+            dictionary: analyzer.map_or(NO_DICTIONARY, MorphAnalyzer::dictionary_version).to_string(),
+            options: *options,
+        }
+    }
+
+    /// 外部のキャッシュ（Redisなど）で使う文字列のキー
+    ///
+    /// スキーマのバージョンを含めるため、`REPORT_SCHEMA_VERSION`を上げると古い結果は参照されなくなる
+    pub fn to_key_string(&self, prefix: &str) -> String {
+        let flag = |b: bool| if b { '1' } else { '0' };
+        let fallback = match self.options.fallback {
+            FallbackPolicy::Error => 'e',
+            FallbackPolicy::CharClass => 'c',
+            FallbackPolicy::Punctuation => 'p',
+        };
+        let hash: String = self.text_hash.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}:v{}:{}:{}{}{}{}:{}",
+            prefix,
+            REPORT_SCHEMA_VERSION,
+            self.dictionary,
+            flag(self.options.tokens),
+            flag(self.options.sentences),
+            flag(self.options.chunks),
+            fallback,
+            hash
+        )
+    }
+}
+
+/// ヒット率などの統計
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// 上限を超えて追い出した件数
+    pub evictions: u64,
+    pub entries: usize,
+    /// 保持している結果の推定サイズ（バイト）
+    pub bytes: usize,
+    pub max_entries: usize,
+    pub max_bytes: usize,
+}
+
+impl CacheMetrics {
+    /// ヒット率（参照がなければ0）
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct Entries {
+    lru: LruCache<CacheKey, (Arc<Report>, usize)>,
+    bytes: usize,
+}
+
+/// 件数と推定サイズの上限付きのLRUキャッシュ（スレッド間で共有できる）
+pub struct ReportCache {
+    entries: Mutex<Entries>,
+    max_entries: usize,
+    max_bytes: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ReportCache {
+    /// `max_entries`件・推定`max_bytes`バイトまで保持する（`max_entries`が0なら1件）
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(Entries { lru: LruCache::new(capacity), bytes: 0 }),
+            max_entries: capacity.get(),
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// キャッシュから取り出す（ヒット・ミスを数える）
+    pub fn get(&self, key: &CacheKey) -> Option<Arc<Report>> {
+        let found = self.lock().lru.get(key).map(|(report, _)| Arc::clone(report));
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// 追加する（推定サイズが`max_bytes`を超える結果は保持しない）
+    pub fn insert(&self, key: CacheKey, report: Arc<Report>) {
+        let size = estimated_size(&report);
+        if size > self.max_bytes {
+            return;
+        }
+        let mut entries = self.lock();
+        entries.bytes += size;
+        if let Some((old_key, (_, old_size))) = entries.lru.push(key.clone(), (report, size)) {
+            entries.bytes -= old_size;
+            if old_key != key {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        while entries.bytes > self.max_bytes {
+            let Some((_, (_, old_size))) = entries.lru.pop_lru() else {
+                break;
+            };
+            entries.bytes -= old_size;
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// キャッシュにあればそれを、なければ解析して保持した結果を返す（解析中はロックしない）
+    pub fn analyze(
+        &self,
+        analyzer: Option<&MorphAnalyzer>,
+        text: &str,
+        options: &ReportOptions,
+    ) -> Result<Arc<Report>> {
+        let key = CacheKey::new(analyzer, text, options);
+        if let Some(report) = self.get(&key) {
+            return Ok(report);
+        }
+        let report = Arc::new(analyze_report(analyzer, text, options)?);
+        self.insert(key, Arc::clone(&report));
+        Ok(report)
+    }
+
+    /// すべて破棄する（統計は残す）
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.lru.clear();
+        entries.bytes = 0;
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        let entries = self.lock();
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: entries.lru.len(),
+            bytes: entries.bytes,
+            max_entries: self.max_entries,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+/// 結果が使うヒープを含めたおおよそのサイズ
+fn estimated_size(report: &Report) -> usize {
+    let tokens: usize = report.tokens.iter().flatten().map(|t| {
+        size_of::<Token>()
+            + t.surface.len()
+            + t.reading.len()
+            + t.pos.len()
+            + t.lemma.len()
+            + t.pos_detail.iter().map(|d| size_of::<String>() + d.len()).sum::<usize>()
+    }).sum();
+    let sentences = report.sentences.as_ref().map_or(0, |s| s.len() * size_of::<Sentence>());
+    let chunks: usize = report.chunks.iter().flatten().map(|c| size_of::<Chunk>() + c.text.len()).sum();
+    size_of::<Report>() + size_of::<CacheKey>() + tokens + sentences + chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::mini_dictionary;
+
+    #[test]
+    fn test_hit_and_miss() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let cache = ReportCache::new(8, 1 << 20);
+        let options = ReportOptions::default();
+
+        let first = cache.analyze(Some(&analyzer), "吾輩は猫である。", &options).unwrap();
+        let second = cache.analyze(Some(&analyzer), "吾輩は猫である。", &options).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // オプションや辞書の有無が違えば別の結果
+        let no_tokens = ReportOptions { tokens: false, ..options };
+        assert!(cache.analyze(Some(&analyzer), "吾輩は猫である。", &no_tokens).unwrap().tokens.is_none());
+        assert!(!Arc::ptr_eq(&first, &cache.analyze(None, "吾輩は猫である。", &options).unwrap()));
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses, metrics.entries), (1, 3, 3));
+        assert_eq!(metrics.hit_rate(), 0.25);
+        assert!(metrics.bytes > 0);
+    }
+
+    #[test]
+    fn test_entry_limit_evicts_least_recently_used() {
+        let cache = ReportCache::new(2, 1 << 20);
+        let options = ReportOptions::default();
+        for text in ["一つ目", "二つ目"] {
+            cache.analyze(None, text, &options).unwrap();
+        }
+        // 「一つ目」を使ってから追加すると「二つ目」が追い出される
+        assert!(cache.get(&CacheKey::new(None, "一つ目", &options)).is_some());
+        cache.analyze(None, "三つ目", &options).unwrap();
+
+        assert!(cache.get(&CacheKey::new(None, "二つ目", &options)).is_none());
+        assert!(cache.get(&CacheKey::new(None, "一つ目", &options)).is_some());
+        let metrics = cache.metrics();
+        assert_eq!((metrics.entries, metrics.evictions), (2, 1));
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let options = ReportOptions::default();
+        let size = estimated_size(&analyze_report(None, "今日は良い天気です。", &options).unwrap());
+        let cache = ReportCache::new(100, size * 2);
+        for text in ["今日は良い天気です。", "明日は良い天気です。", "昨日は良い天気です。"] {
+            cache.analyze(None, text, &options).unwrap();
+        }
+        let metrics = cache.metrics();
+        assert_eq!(metrics.entries, 2);
+        assert!(metrics.bytes <= size * 2);
+
+        // 上限より大きい結果は保持しない
+        let small = ReportCache::new(100, 16);
+        small.analyze(None, "今日は良い天気です。", &options).unwrap();
+        assert_eq!(small.metrics().entries, 0);
+    }
+
+    #[test]
+    fn test_key_string() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let key = CacheKey::new(Some(&analyzer), "abc", &ReportOptions::default());
+        assert_eq!(
+            key.to_key_string("readmaker:report"),
+            format!(
+                "readmaker:report:v{}:{}:111c:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                REPORT_SCHEMA_VERSION,
+                analyzer.dictionary_version()
+            )
+        );
+        assert_eq!(analyzer.dictionary_version().len(), 16);
+        assert_ne!(key, CacheKey::new(None, "abc", &ReportOptions::default()));
+    }
+}
@@ -0,0 +1,241 @@
+//! 辞書のコンパイル
+//! MeCab IPADIC形式のソース（`*.csv`・`matrix.def`・`char.def`・`unk.def`、EUC-JP/UTF-8）からVibratoの辞書を作る
+
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use encoding_rs::{EUC_JP, UTF_8};
+use vibrato::{Dictionary, SystemDictionaryBuilder};
+use readmaker_shared::{ReadMakerError, Result};
+
+/// 既定のzstd圧縮レベル（辞書は1度だけ作って配布するため、時間をかけて小さくする）
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 19;
+
+/// `scripts/build_ipadic_lex.js`が出力する連結済みの語彙
+const GENERATED_LEXICON: &str = "lex_utf8.csv";
+
+/// ソースファイルの文字コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceEncoding {
+    /// ファイルごとにUTF-8として読めるか確かめ、読めなければEUC-JPとする
+    #[default]
+    Auto,
+    Utf8,
+    /// 配布されているMeCab IPADICの文字コード
+    EucJp,
+}
+
+impl FromStr for SourceEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "auto" => Ok(SourceEncoding::Auto),
+            "utf-8" | "utf8" => Ok(SourceEncoding::Utf8),
+            "euc-jp" | "eucjp" => Ok(SourceEncoding::EucJp),
+            other => Err(format!("不明な文字コード: {}（auto・utf-8・euc-jp）", other)),
+        }
+    }
+}
+
+impl fmt::Display for SourceEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SourceEncoding::Auto => "auto",
+            SourceEncoding::Utf8 => "utf-8",
+            SourceEncoding::EucJp => "euc-jp",
+        })
+    }
+}
+
+/// UTF-8に揃えた辞書のソース
+#[derive(Debug, Clone, Default)]
+pub struct DictionarySource {
+    /// すべての`*.csv`をファイル名順に連結した語彙
+    pub lexicon: String,
+    pub matrix: String,
+    pub char_def: String,
+    pub unk_def: String,
+    /// 読み込んだ語彙のファイル
+    pub lexicon_files: Vec<PathBuf>,
+}
+
+impl DictionarySource {
+    /// IPADICのディレクトリから読む（`*.csv`はファイル名順に連結するため、同じソースからは同じ辞書ができる）
+    pub fn from_dir(dir: impl AsRef<Path>, encoding: SourceEncoding) -> Result<Self> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir)
+            .map_err(|e| ReadMakerError::Validation(format!("辞書のソースを開けません: {}: {}", dir.display(), e)))?;
+        let mut lexicon_files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")))
+            // 旧手順（`npm run dic:build-lex`）が同じディレクトリに出力した連結済みの語彙は読まない
+            .filter(|path| path.file_name().is_none_or(|name| name != GENERATED_LEXICON))
+            .collect();
+        lexicon_files.sort();
+        if lexicon_files.is_empty() {
+            return Err(ReadMakerError::Validation(format!("語彙（*.csv）がありません: {}", dir.display())));
+        }
+
+        let mut lexicon = String::new();
+        for path in &lexicon_files {
+            for line in read_source(path, encoding)?.lines().filter(|line| is_lexicon_entry(line)) {
+                lexicon.push_str(line);
+                lexicon.push('\n');
+            }
+        }
+        Ok(Self {
+            lexicon,
+            matrix: read_source(&dir.join("matrix.def"), encoding)?,
+            char_def: read_source(&dir.join("char.def"), encoding)?,
+            unk_def: read_source(&dir.join("unk.def"), encoding)?,
+            lexicon_files,
+        })
+    }
+
+    /// 表層形が一致する語彙の行（ソースの順）
+    pub fn lookup(&self, surface: &str) -> Vec<&str> {
+        self.lexicon
+            .lines()
+            .filter(|line| line.split(',').next() == Some(surface))
+            .collect()
+    }
+
+    /// 語彙の数（ソースから組み立てた辞書のシステム辞書の語彙数と同じ）
+    pub fn lexicon_len(&self) -> usize {
+        self.lexicon.lines().count()
+    }
+
+    /// Vibratoの辞書を組み立てる
+    pub fn build(&self) -> Result<Dictionary> {
+        SystemDictionaryBuilder::from_readers(
+            self.lexicon.as_bytes(),
+            self.matrix.as_bytes(),
+            self.char_def.as_bytes(),
+            self.unk_def.as_bytes(),
+        )
+        .map_err(|e| ReadMakerError::Validation(format!("辞書の組み立てに失敗しました: {}", e)))
+    }
+}
+
+/// 語彙の行か（空行・`#`で始まる行・連接ID/コストが整数でない行は読み飛ばす）
+fn is_lexicon_entry(line: &str) -> bool {
+    if line.is_empty() || line.starts_with('#') {
+        return false;
+    }
+    let columns: Vec<&str> = line.splitn(5, ',').collect();
+    columns.len() >= 4 && columns[1..4].iter().all(|c| c.parse::<i32>().is_ok())
+}
+
+/// ソースファイルをUTF-8にして読む（改行はLFに揃える）
+fn read_source(path: &Path, encoding: SourceEncoding) -> Result<String> {
+    let bytes = fs::read(path)
+        .map_err(|e| ReadMakerError::Validation(format!("読み込めません: {}: {}", path.display(), e)))?;
+    let encoding = match encoding {
+        SourceEncoding::Auto if std::str::from_utf8(&bytes).is_ok() => UTF_8,
+        SourceEncoding::Auto | SourceEncoding::EucJp => EUC_JP,
+        SourceEncoding::Utf8 => UTF_8,
+    };
+    let (text, had_errors) = encoding.decode_with_bom_removal(&bytes);
+    if had_errors {
+        return Err(ReadMakerError::Validation(format!(
+            "{}として読めない文字があります: {}",
+            encoding.name(),
+            path.display()
+        )));
+    }
+    Ok(text.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// 辞書を書き出し、書き出したバイト数を返す（`level`を指定するとzstdで圧縮する）
+pub fn write_dictionary(dict: &Dictionary, mut writer: impl Write, level: Option<i32>) -> Result<u64> {
+    let io_error = |e: std::io::Error| ReadMakerError::Internal(format!("辞書の書き出しに失敗しました: {}", e));
+    let mut raw = Vec::new();
+    dict.write(&mut raw)
+        .map_err(|e| ReadMakerError::Internal(format!("辞書の書き出しに失敗しました: {}", e)))?;
+    let bytes = match level {
+        None => raw,
+        #[cfg(feature = "zstd")]
+        Some(level) => zstd::encode_all(raw.as_slice(), level).map_err(io_error)?,
+        #[cfg(not(feature = "zstd"))]
+        Some(_) => return Err(ReadMakerError::Validation("zstd圧縮にはfeature`zstd`が必要です".to_string())),
+    };
+    writer.write_all(&bytes).map_err(io_error)?;
+    Ok(bytes.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_dictionary, MorphAnalyzer};
+
+    const MINI_IPADIC: &str = "testdata/mini-ipadic";
+
+    fn compile(dir: &Path, encoding: SourceEncoding, level: Option<i32>) -> Vec<u8> {
+        let dict = DictionarySource::from_dir(dir, encoding).unwrap().build().unwrap();
+        let mut bytes = Vec::new();
+        write_dictionary(&dict, &mut bytes, level).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_compile_utf8_source() {
+        let bytes = compile(Path::new(MINI_IPADIC), SourceEncoding::Auto, None);
+        let analyzer = MorphAnalyzer::from_dictionary(read_dictionary(&bytes).unwrap());
+        assert_eq!(analyzer.analyze_words("吾輩は猫である。").unwrap(), vec!["吾輩", "は", "猫", "で", "ある", "。"]);
+        // 同じソースからは同じ辞書ができる
+        assert_eq!(bytes, compile(Path::new(MINI_IPADIC), SourceEncoding::Utf8, None));
+    }
+
+    #[test]
+    fn test_compile_euc_jp_source() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["lex.csv", "matrix.def", "char.def", "unk.def"] {
+            let text = fs::read_to_string(Path::new(MINI_IPADIC).join(name)).unwrap().replace('\n', "\r\n");
+            fs::write(dir.path().join(name), EUC_JP.encode(&text).0).unwrap();
+        }
+        let utf8 = compile(Path::new(MINI_IPADIC), SourceEncoding::Utf8, None);
+        assert_eq!(compile(dir.path(), SourceEncoding::Auto, None), utf8);
+        assert_eq!(compile(dir.path(), SourceEncoding::EucJp, None), utf8);
+        // EUC-JPのファイルはUTF-8としては読めない
+        assert!(DictionarySource::from_dir(dir.path(), SourceEncoding::Utf8).is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compressed_output() {
+        let raw = compile(Path::new(MINI_IPADIC), SourceEncoding::Auto, None);
+        let compressed = compile(Path::new(MINI_IPADIC), SourceEncoding::Auto, Some(3));
+        assert!(crate::is_zstd_compressed(&compressed));
+        assert_eq!(zstd::decode_all(compressed.as_slice()).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let source = DictionarySource::from_dir(MINI_IPADIC, SourceEncoding::Auto).unwrap();
+        let entries = source.lookup("で");
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].starts_with("で,0,0,1000,助詞"));
+        assert!(entries[1].starts_with("で,0,0,1100,助動詞"));
+        assert!(source.lookup("犬").is_empty());
+        assert_eq!(source.lexicon_len(), 15);
+    }
+
+    #[test]
+    fn test_lexicon_entry_filter() {
+        assert!(is_lexicon_entry("猫,0,0,1000,名詞,一般,*,*,*,*,猫,ネコ,ネコ"));
+        assert!(!is_lexicon_entry("# comment"));
+        assert!(!is_lexicon_entry(""));
+        assert!(!is_lexicon_entry("猫,left,0,1000,名詞"));
+        assert!(!is_lexicon_entry("猫,0,0"));
+    }
+
+    #[test]
+    fn test_missing_source() {
+        assert!(DictionarySource::from_dir("testdata/no-such-ipadic", SourceEncoding::Auto).is_err());
+        assert_eq!("EUC_JP".parse::<SourceEncoding>().unwrap(), SourceEncoding::EucJp);
+        assert!("sjis".parse::<SourceEncoding>().is_err());
+    }
+}
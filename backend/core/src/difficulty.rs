@@ -0,0 +1,138 @@
+//! 読みやすさの目安
+//! 漢字の割合・1文の長さ・本文の長さから、アプリの難易度（初級〜超級）と同じ段階で判定する
+
+use serde::{Deserialize, Serialize};
+use crate::report::Sentence;
+use crate::segmenter::{script_of, Script};
+
+/// 漢字の割合がこれ以上なら最も難しいとみなす（一般的な新聞記事でおよそ4割）
+const KANJI_RATIO_MAX: f64 = 0.6;
+/// 1文の文字数がこれ以上なら最も難しいとみなす
+const SENTENCE_CHARS_MAX: f64 = 60.0;
+/// 本文の文字数がこれ以上なら最も難しいとみなす（アプリの「超級」の基準）
+const TEXT_CHARS_MAX: f64 = 400.0;
+
+/// 難易度の段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+    Expert,
+}
+
+impl DifficultyLevel {
+    /// 0〜100の点数から段階を決める
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s < 25.0 => Self::Beginner,
+            s if s < 50.0 => Self::Intermediate,
+            s if s < 75.0 => Self::Advanced,
+            _ => Self::Expert,
+        }
+    }
+
+    /// アプリでの表示名
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Beginner => "初級",
+            Self::Intermediate => "中級",
+            Self::Advanced => "上級",
+            Self::Expert => "超級",
+        }
+    }
+}
+
+/// 難易度の判定結果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    pub level: DifficultyLevel,
+    /// 0〜100（大きいほど難しい）
+    pub score: f64,
+    /// 本文の文字数（空白・記号を除く）
+    pub chars: usize,
+    pub sentences: usize,
+    /// 本文の文字に占める漢字の割合
+    pub kanji_ratio: f64,
+    pub avg_sentence_chars: f64,
+}
+
+/// 本文として数える文字か（空白・記号を除く）
+fn is_text_char(c: char) -> bool {
+    !matches!(script_of(c), Script::Space | Script::Punctuation)
+}
+
+/// 難易度を判定する（`sentences`は[`crate::Report::sentences`]）
+///
+/// 点数は漢字の割合を4割、1文の平均文字数を3割、本文の文字数を3割の重みで合計する
+pub fn estimate_difficulty(text: &str, sentences: &[Sentence]) -> Difficulty {
+    let chars: Vec<char> = text.chars().collect();
+    let text_chars = chars.iter().filter(|&&c| is_text_char(c)).count();
+    let kanji = chars.iter().filter(|&&c| script_of(c) == Script::Kanji).count();
+
+    let sentence_lengths: Vec<usize> = sentences
+        .iter()
+        .map(|s| chars[s.start.min(chars.len())..s.end.min(chars.len())].iter().filter(|&&c| is_text_char(c)).count())
+        .filter(|&n| n > 0)
+        .collect();
+    let avg_sentence_chars = if sentence_lengths.is_empty() {
+        0.0
+    } else {
+        sentence_lengths.iter().sum::<usize>() as f64 / sentence_lengths.len() as f64
+    };
+    let kanji_ratio = if text_chars == 0 { 0.0 } else { kanji as f64 / text_chars as f64 };
+
+    let score = 100.0
+        * (0.4 * (kanji_ratio / KANJI_RATIO_MAX).min(1.0)
+            + 0.3 * (avg_sentence_chars / SENTENCE_CHARS_MAX).min(1.0)
+            + 0.3 * (text_chars as f64 / TEXT_CHARS_MAX).min(1.0));
+
+    Difficulty {
+        level: DifficultyLevel::from_score(score),
+        score,
+        chars: text_chars,
+        sentences: sentence_lengths.len(),
+        kanji_ratio,
+        avg_sentence_chars,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{analyze_report, ReportOptions};
+
+    fn difficulty(text: &str) -> Difficulty {
+        let report = analyze_report(None, text, &ReportOptions::default()).unwrap();
+        estimate_difficulty(text, &report.sentences.unwrap())
+    }
+
+    #[test]
+    fn test_easy_text() {
+        let d = difficulty("ねこが います。いぬも います。");
+        assert_eq!((d.chars, d.sentences), (12, 2));
+        assert_eq!(d.kanji_ratio, 0.0);
+        assert_eq!(d.avg_sentence_chars, 6.0);
+        assert_eq!(d.level, DifficultyLevel::Beginner);
+        assert_eq!(d.level.label(), "初級");
+    }
+
+    #[test]
+    fn test_harder_text_scores_higher() {
+        let easy = difficulty("ねこが います。");
+        let hard = difficulty(&"人工知能技術の発展により自然言語処理分野においても大きな進歩が見られ、形態素解析や機械翻訳などの技術は実用段階に達した。".repeat(4));
+        assert!(hard.score > easy.score);
+        assert!(hard.kanji_ratio > 0.5);
+        assert_eq!(hard.level, DifficultyLevel::Expert);
+        assert!(difficulty("").score == 0.0);
+    }
+
+    #[test]
+    fn test_level_thresholds() {
+        assert_eq!(DifficultyLevel::from_score(24.9), DifficultyLevel::Beginner);
+        assert_eq!(DifficultyLevel::from_score(25.0), DifficultyLevel::Intermediate);
+        assert_eq!(DifficultyLevel::from_score(74.9), DifficultyLevel::Advanced);
+        assert_eq!(DifficultyLevel::from_score(100.0), DifficultyLevel::Expert);
+    }
+}
@@ -0,0 +1,260 @@
+//! 構造化ドキュメント
+//! 青空文庫・EPUB・HTMLなど各インポーターが共通で出力する、章・段落・ルビからなる文書
+
+use serde::{Deserialize, Serialize};
+use crate::mixed::{text_stats, TextStats};
+
+/// 取り込み元の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    Plain,
+    Aozora,
+    Epub,
+    Html,
+}
+
+/// ルビ（`start`..`end`は段落テキスト上の文字オフセット）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruby {
+    pub start: usize,
+    pub end: usize,
+    pub reading: String,
+}
+
+/// 段落（マークアップを除いた本文と、著者が付けたルビ）
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Paragraph {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ruby: Vec<Ruby>,
+}
+
+impl Paragraph {
+    /// ルビなしの段落
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self { text: text.into(), ruby: Vec::new() }
+    }
+
+    /// ルビの親文字
+    pub fn ruby_base(&self, ruby: &Ruby) -> String {
+        self.text.chars().skip(ruby.start).take(ruby.end - ruby.start).collect()
+    }
+}
+
+/// 章
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Chapter {
+    pub title: Option<String>,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+impl Chapter {
+    /// 段落を改行でつないだ本文
+    pub fn text(&self) -> String {
+        self.paragraphs.iter().map(|p| p.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.paragraphs.is_empty()
+    }
+}
+
+/// 構造化ドキュメント
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    pub format: SourceFormat,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Document {
+    pub fn new(format: SourceFormat) -> Self {
+        Self { format, title: None, author: None, chapters: Vec::new() }
+    }
+
+    /// プレーンテキストを1章の文書にする（空でない行をそれぞれ1段落にし、空行は捨てる）
+    pub fn from_plain_text(text: &str) -> Self {
+        let paragraphs = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Paragraph::plain)
+            .collect();
+        Self {
+            chapters: vec![Chapter { title: None, paragraphs }],
+            ..Self::new(SourceFormat::Plain)
+        }
+    }
+
+    /// 新しい章を始める（最後の章が空ならそのまま使う）
+    pub fn begin_chapter(&mut self) {
+        if self.chapters.last().is_none_or(|c| !c.is_empty()) {
+            self.chapters.push(Chapter::default());
+        }
+    }
+
+    /// 見出しを追加（最後の章が空ならその題にし、そうでなければ新しい章を始める）
+    pub fn push_heading(&mut self, title: String) {
+        match self.chapters.last_mut() {
+            Some(chapter) if chapter.is_empty() => chapter.title = Some(title),
+            _ => self.chapters.push(Chapter { title: Some(title), paragraphs: Vec::new() }),
+        }
+    }
+
+    /// 最後の章に段落を追加
+    pub fn push_paragraph(&mut self, paragraph: Paragraph) {
+        if self.chapters.is_empty() {
+            self.chapters.push(Chapter::default());
+        }
+        self.chapters.last_mut().expect("章は必ずある").paragraphs.push(paragraph);
+    }
+
+    /// 空の章を取り除く
+    pub fn remove_empty_chapters(&mut self) {
+        self.chapters.retain(|c| !c.is_empty());
+    }
+
+    /// 全段落
+    pub fn paragraphs(&self) -> impl Iterator<Item = &Paragraph> {
+        self.chapters.iter().flat_map(|c| c.paragraphs.iter())
+    }
+
+    /// 解析器に渡す本文（章は空行、段落は改行で区切る）
+    pub fn plain_text(&self) -> String {
+        self.chapters
+            .iter()
+            .map(Chapter::text)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// 本文の文字数（改行を除く）
+    pub fn char_count(&self) -> usize {
+        self.paragraphs().map(|p| p.text.chars().count()).sum()
+    }
+
+    /// 日本語文字数・英単語数
+    pub fn stats(&self) -> TextStats {
+        text_stats(&self.plain_text())
+    }
+}
+
+/// 段落を組み立てる（インポーター用）
+///
+/// 先頭の空白は捨て、連続する空白は1つにまとめ、ルビのオフセットを自動で計算する
+#[derive(Debug, Default)]
+pub struct ParagraphBuilder {
+    text: String,
+    len: usize,
+    ruby: Vec<Ruby>,
+    pending_space: bool,
+}
+
+impl ParagraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 現在の文字数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 本文を追加
+    pub fn push_str(&mut self, s: &str) {
+        for ch in s.chars() {
+            self.push(ch);
+        }
+    }
+
+    /// 1文字追加
+    pub fn push(&mut self, ch: char) {
+        // 全角空白は字下げとして意味を持つため、半角の空白類のみまとめる
+        if ch.is_whitespace() && ch != '\u{3000}' {
+            self.pending_space = self.len > 0;
+            return;
+        }
+        if ch == '\u{3000}' && self.len == 0 {
+            return;
+        }
+        if std::mem::take(&mut self.pending_space) {
+            self.text.push(' ');
+            self.len += 1;
+        }
+        self.text.push(ch);
+        self.len += 1;
+    }
+
+    /// 親文字と読みを追加
+    pub fn push_ruby(&mut self, base: &str, reading: &str) {
+        let start = self.len;
+        self.push_str(base);
+        self.add_ruby(start, reading);
+    }
+
+    /// 直前に追加済みの`start`文字目以降にルビを付ける
+    pub fn add_ruby(&mut self, start: usize, reading: &str) {
+        let reading = reading.trim();
+        if start < self.len && !reading.is_empty() {
+            self.ruby.push(Ruby { start, end: self.len, reading: reading.to_string() });
+        }
+    }
+
+    /// 直前の1文字を取り除く
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.text.pop()?;
+        self.len -= 1;
+        self.ruby.retain(|r| r.end <= self.len);
+        Some(ch)
+    }
+
+    /// 追加済みの本文の末尾`n`文字
+    pub fn tail(&self, n: usize) -> Vec<char> {
+        let mut tail: Vec<char> = self.text.chars().rev().take(n).collect();
+        tail.reverse();
+        tail
+    }
+
+    /// 段落を確定し、ビルダーを空に戻す
+    pub fn finish(&mut self) -> Paragraph {
+        self.pending_space = false;
+        self.len = 0;
+        Paragraph {
+            text: std::mem::take(&mut self.text),
+            ruby: std::mem::take(&mut self.ruby),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraph_builder_ruby_offsets() {
+        let mut builder = ParagraphBuilder::new();
+        builder.push_str("  吾輩は");
+        builder.push_ruby("猫", "ねこ");
+        builder.push_str("である。 ");
+        let paragraph = builder.finish();
+        assert_eq!(paragraph.text, "吾輩は猫である。");
+        assert_eq!(paragraph.ruby, vec![Ruby { start: 3, end: 4, reading: "ねこ".to_string() }]);
+        assert_eq!(paragraph.ruby_base(&paragraph.ruby[0]), "猫");
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn test_plain_text_joins_chapters() {
+        let mut doc = Document::from_plain_text("一行目\n\n二行目\n");
+        doc.chapters.push(Chapter { title: Some("第二章".to_string()), paragraphs: vec![Paragraph::plain("三行目")] });
+        assert_eq!(doc.plain_text(), "一行目\n二行目\n\n三行目");
+        assert_eq!(doc.char_count(), 9);
+    }
+}
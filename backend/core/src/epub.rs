@@ -0,0 +1,297 @@
+//! EPUBのインポーター
+//! container.xml → OPFのspineの順にXHTMLを読み、マークアップを除いてルビと見出しを保った構造化ドキュメントにする
+
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+use readmaker_shared::{ReadMakerError, Result};
+use crate::document::{Document, SourceFormat};
+use crate::markup::{self, Block, Element};
+
+/// 1ファイルあたりの展開サイズの上限（圧縮爆弾対策）
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+/// 読み込むファイルの展開サイズの合計の上限（同じファイルを何度も並べたspine対策）
+const MAX_TOTAL_SIZE: u64 = 256 * 1024 * 1024;
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// EPUBファイルのバイト列を構造化ドキュメントにする
+pub fn parse_bytes(bytes: &[u8]) -> Result<Document> {
+    parse_with_limits(bytes, MAX_ENTRY_SIZE, MAX_TOTAL_SIZE)
+}
+
+fn parse_with_limits(bytes: &[u8], max_entry_size: u64, max_total_size: u64) -> Result<Document> {
+    let archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| invalid(format!("ZIPとして読めません: {}", e)))?;
+    let mut reader = EntryReader { archive, max_entry_size, remaining: max_total_size, over_limit: false };
+
+    let container = markup::parse(&reader.read(CONTAINER_PATH)?);
+    let opf_path = container
+        .find("rootfile")
+        .and_then(|r| r.attr("full-path"))
+        .ok_or_else(|| invalid("container.xmlにrootfileがありません"))?
+        .to_string();
+    let package = markup::parse(&reader.read(&opf_path)?);
+
+    let mut doc = Document::new(SourceFormat::Epub);
+    doc.title = metadata_text(&package, "title");
+    doc.author = metadata_text(&package, "creator");
+
+    let mut seen = HashSet::new();
+    for href in spine_hrefs(&package) {
+        let path = resolve_path(&opf_path, &href);
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let xhtml = match reader.read(&path) {
+            Ok(xhtml) => xhtml,
+            Err(e) if reader.over_limit => return Err(e),
+            Err(e) => {
+                tracing::warn!("EPUBのspine項目を読み飛ばします: {}", e);
+                continue;
+            }
+        };
+        doc.begin_chapter();
+        let root = markup::parse(&xhtml);
+        let body = root.find("body").unwrap_or(&root);
+        for block in markup::blocks(body) {
+            match block {
+                Block::Heading(p) => doc.push_heading(p.text),
+                Block::Paragraph(p) => doc.push_paragraph(p),
+            }
+        }
+    }
+    doc.remove_empty_chapters();
+
+    if doc.chapters.is_empty() {
+        return Err(invalid("本文が見つかりません"));
+    }
+    Ok(doc)
+}
+
+fn invalid(message: impl std::fmt::Display) -> ReadMakerError {
+    ReadMakerError::Validation(format!("EPUBの読み込みに失敗しました: {}", message))
+}
+
+/// 展開サイズの合計を数えながらアーカイブ内のファイルを読む
+struct EntryReader<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    /// 1ファイルあたりの展開サイズの上限
+    max_entry_size: u64,
+    /// 残りの展開サイズ
+    remaining: u64,
+    /// サイズの上限を超えたか（途中で切れた本文を取り込まないよう、読み飛ばさずに失敗させる）
+    over_limit: bool,
+}
+
+impl EntryReader<'_> {
+    fn read(&mut self, path: &str) -> Result<String> {
+        let entry = self.archive.by_name(path).map_err(|e| invalid(format!("{}: {}", path, e)))?;
+        let mut bytes = Vec::new();
+        // どちらの上限を超えたかを区別できるよう、小さいほうの上限より1バイト多く読む
+        entry
+            .take(self.max_entry_size.min(self.remaining).saturating_add(1))
+            .read_to_end(&mut bytes)
+            .map_err(|e| invalid(format!("{}: {}", path, e)))?;
+        if bytes.len() as u64 > self.max_entry_size {
+            self.over_limit = true;
+            return Err(invalid(format!("{}が大きすぎます（上限{}バイト）", path, self.max_entry_size)));
+        }
+        if bytes.len() as u64 > self.remaining {
+            self.over_limit = true;
+            return Err(invalid("展開後のサイズの合計が上限を超えています"));
+        }
+        self.remaining -= bytes.len() as u64;
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+fn metadata_text(package: &Element, name: &str) -> Option<String> {
+    let text = package.find("metadata")?.find(name)?.text();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// spineの順に並べたXHTMLのhref（`linear="no"`の付録などは除く）
+fn spine_hrefs(package: &Element) -> Vec<String> {
+    let manifest: Vec<&Element> = package.find("manifest").map(|m| m.find_all("item")).unwrap_or_default();
+    let Some(spine) = package.find("spine") else {
+        return Vec::new();
+    };
+    spine
+        .find_all("itemref")
+        .into_iter()
+        .filter(|itemref| itemref.attr("linear") != Some("no"))
+        .filter_map(|itemref| {
+            let idref = itemref.attr("idref")?;
+            let item = manifest.iter().find(|item| item.attr("id") == Some(idref))?;
+            let is_xhtml = item
+                .attr("media-type")
+                .is_none_or(|t| t.contains("html") || t.contains("xml"));
+            is_xhtml.then(|| item.attr("href").map(str::to_string)).flatten()
+        })
+        .collect()
+}
+
+/// OPFからの相対hrefをアーカイブ内のパスにする
+fn resolve_path(opf_path: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let mut parts: Vec<&str> = opf_path.split('/').collect();
+    parts.pop();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    percent_decode(&parts.join("/"))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn build_epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer.start_file(*path, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container xmlns="urn:oasis:names:tc:opendocument:xmlns:container" version="1.0">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+
+    const OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>吾輩は猫である</dc:title>
+    <dc:creator>夏目漱石</dc:creator>
+  </metadata>
+  <manifest>
+    <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/ch%202.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/ch1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="style.css" media-type="text/css"/>
+  </manifest>
+  <spine>
+    <itemref idref="cover" linear="no"/>
+    <itemref idref="c1"/>
+    <itemref idref="c2"/>
+  </spine>
+</package>"#;
+
+    const CH1: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>一</title><link rel="stylesheet" href="../style.css"/></head>
+<body>
+  <h1>一</h1>
+  <p>　<ruby>吾輩<rt>わがはい</rt></ruby>は猫である。</p>
+  <p>名前はまだ<span class="em">無い</span>。</p>
+</body>
+</html>"#;
+
+    const CH2: &str = r#"<html><body><h1>二</h1><p>どこで<ruby><rb>生</rb><rp>（</rp><rt>うま</rt><rp>）</rp></ruby>れたか。</p></body></html>"#;
+
+    #[test]
+    fn test_parse_epub_spine_order() {
+        let bytes = build_epub(&[
+            (CONTAINER_PATH, CONTAINER),
+            ("OEBPS/content.opf", OPF),
+            ("OEBPS/cover.xhtml", "<html><body><p>表紙</p></body></html>"),
+            ("OEBPS/text/ch1.xhtml", CH1),
+            ("OEBPS/text/ch 2.xhtml", CH2),
+        ]);
+        let doc = parse_bytes(&bytes).unwrap();
+        assert_eq!(doc.format, SourceFormat::Epub);
+        assert_eq!(doc.title.as_deref(), Some("吾輩は猫である"));
+        assert_eq!(doc.author.as_deref(), Some("夏目漱石"));
+
+        let titles: Vec<_> = doc.chapters.iter().map(|c| c.title.as_deref()).collect();
+        assert_eq!(titles, vec![Some("一"), Some("二")]);
+
+        let first = &doc.chapters[0].paragraphs[0];
+        assert_eq!(first.text, "吾輩は猫である。");
+        assert_eq!(first.ruby_base(&first.ruby[0]), "吾輩");
+        assert_eq!(doc.chapters[0].paragraphs[1].text, "名前はまだ無い。");
+
+        let second = &doc.chapters[1].paragraphs[0];
+        assert_eq!(second.text, "どこで生れたか。");
+        assert_eq!(second.ruby[0].reading, "うま");
+        assert!(!doc.plain_text().contains("表紙"));
+    }
+
+    #[test]
+    fn test_repeated_spine_items() {
+        let opf = OPF.replace("<itemref idref=\"c2\"/>", &"<itemref idref=\"c1\"/>".repeat(100));
+        let files = [(CONTAINER_PATH, CONTAINER), ("OEBPS/content.opf", opf.as_str()), ("OEBPS/text/ch1.xhtml", CH1)];
+        let bytes = build_epub(&files);
+        // 同じファイルは1度だけ読む
+        assert_eq!(parse_bytes(&bytes).unwrap().chapters.len(), 1);
+
+        let needed = (CONTAINER.len() + opf.len() + CH1.len()) as u64;
+        assert!(parse_with_limits(&bytes, MAX_ENTRY_SIZE, needed).is_ok());
+        assert!(parse_with_limits(&bytes, MAX_ENTRY_SIZE, needed - 1).is_err());
+    }
+
+    #[test]
+    fn test_entry_size_limit() {
+        let long_chapter = CH1.replace("</body>", &format!("{}</body>", "<p>名前はまだ無い。</p>".repeat(100)));
+        let files = [
+            (CONTAINER_PATH, CONTAINER),
+            ("OEBPS/content.opf", OPF),
+            ("OEBPS/text/ch1.xhtml", long_chapter.as_str()),
+            ("OEBPS/text/ch 2.xhtml", CH2),
+        ];
+        let bytes = build_epub(&files);
+        let limit = long_chapter.len() as u64;
+        assert!(limit > OPF.len() as u64);
+        assert_eq!(parse_with_limits(&bytes, limit, MAX_TOTAL_SIZE).unwrap().chapters.len(), 2);
+        // 上限を超えた章は途中で切らずに取り込みを失敗させる
+        let err = parse_with_limits(&bytes, limit - 1, MAX_TOTAL_SIZE).unwrap_err();
+        assert!(err.to_string().contains("ch1.xhtmlが大きすぎます"));
+    }
+
+    #[test]
+    fn test_invalid_epub() {
+        assert!(parse_bytes(b"not a zip").is_err());
+        let bytes = build_epub(&[("mimetype", "application/epub+zip")]);
+        assert!(parse_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(resolve_path("OEBPS/content.opf", "../Text/a%20b.xhtml#p1"), "Text/a b.xhtml");
+        assert_eq!(resolve_path("content.opf", "./ch1.xhtml"), "ch1.xhtml");
+    }
+}
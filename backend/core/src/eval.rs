@@ -0,0 +1,283 @@
+//! 分割の精度の評価
+//! 正解の分割が付いたコーパス（[`crate::annotated`]）と解析結果を比べ、境界の適合率・再現率・F1と、
+//! 品詞・読みの正解率を求める。辞書や設定を変えたときに分割が良くなったか悪くなったかを確かめるのに使う
+
+use serde::Serialize;
+use readmaker_shared::Result;
+use crate::annotated::{AnnotatedCorpus, AnnotatedSentence};
+use crate::report::Token;
+use crate::MorphAnalyzer;
+
+/// IPADICの素性で読みが入っている列（[`Token::from_feature`]と同じ）
+const READING_COLUMN: usize = 7;
+
+/// 評価の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalOptions {
+    /// 品詞細分類まで一致したときだけ品詞を正解とする（`false`なら品詞の大分類だけ比べる）
+    pub detailed_pos: bool,
+    /// 一致度の低い文を何文まで残すか
+    pub max_worst: usize,
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self { detailed_pos: false, max_worst: 10 }
+    }
+}
+
+/// 分割の境界の一致度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SegmentationScore {
+    /// 正解の形態素の数
+    pub gold: usize,
+    /// 解析結果の形態素の数
+    pub predicted: usize,
+    /// 開始・終了の位置が正解と一致した形態素の数
+    pub correct: usize,
+}
+
+impl SegmentationScore {
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct, self.predicted)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct, self.gold)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 {
+            0.0
+        } else {
+            2.0 * p * r / (p + r)
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.gold += other.gold;
+        self.predicted += other.predicted;
+        self.correct += other.correct;
+    }
+}
+
+fn ratio(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 / total as f64
+    }
+}
+
+/// 境界は一致したが品詞か読みが異なった形態素
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaggingError {
+    pub surface: String,
+    /// 異なった項目（`pos`か`reading`）
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// 1文の評価
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SentenceEvaluation {
+    pub text: String,
+    pub segmentation: SegmentationScore,
+    /// 境界が一致した形態素のうち、品詞も一致した数
+    pub pos_correct: usize,
+    /// 境界が一致し、正解に読みがある形態素の数
+    pub reading_total: usize,
+    pub reading_correct: usize,
+    /// 正解と解析結果の表層形の並び
+    pub gold_surfaces: Vec<String>,
+    pub predicted_surfaces: Vec<String>,
+    pub tagging_errors: Vec<TaggingError>,
+}
+
+/// コーパス全体の評価
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Evaluation {
+    pub sentences: usize,
+    pub segmentation: SegmentationScore,
+    pub pos_correct: usize,
+    pub reading_total: usize,
+    pub reading_correct: usize,
+    /// 境界のF1が低い順の文（すべて正解の文は含めない）
+    pub worst: Vec<SentenceEvaluation>,
+}
+
+impl Evaluation {
+    /// 境界が一致した形態素のうち、品詞も一致した割合
+    pub fn pos_accuracy(&self) -> f64 {
+        ratio(self.pos_correct, self.segmentation.correct)
+    }
+
+    /// 境界が一致し正解に読みがある形態素のうち、読みも一致した割合
+    pub fn reading_accuracy(&self) -> f64 {
+        ratio(self.reading_correct, self.reading_total)
+    }
+}
+
+/// コーパスの各文を解析して正解と比べる
+pub fn evaluate(analyzer: &MorphAnalyzer, corpus: &AnnotatedCorpus, options: &EvalOptions) -> Result<Evaluation> {
+    let mut evaluation = Evaluation::default();
+    let mut imperfect = Vec::new();
+    for sentence in &corpus.sentences {
+        let result = evaluate_sentence(sentence, &analyzer.tokenize(&sentence.text())?, options);
+        evaluation.sentences += 1;
+        evaluation.segmentation.add(&result.segmentation);
+        evaluation.pos_correct += result.pos_correct;
+        evaluation.reading_total += result.reading_total;
+        evaluation.reading_correct += result.reading_correct;
+        let s = &result.segmentation;
+        let perfect = s.correct == s.gold && s.correct == s.predicted;
+        if !perfect || !result.tagging_errors.is_empty() {
+            imperfect.push(result);
+        }
+    }
+    // F1が同じなら品詞・読みの誤りが多い順
+    imperfect.sort_by(|a, b| {
+        a.segmentation
+            .f1()
+            .total_cmp(&b.segmentation.f1())
+            .then_with(|| b.tagging_errors.len().cmp(&a.tagging_errors.len()))
+    });
+    imperfect.truncate(options.max_worst);
+    evaluation.worst = imperfect;
+    Ok(evaluation)
+}
+
+/// 1文の正解と解析結果を比べる
+pub fn evaluate_sentence(sentence: &AnnotatedSentence, predicted: &[Token], options: &EvalOptions) -> SentenceEvaluation {
+    let spans = sentence.spans();
+    let gold: Vec<(Token, bool)> = sentence
+        .words
+        .iter()
+        .zip(&spans)
+        .map(|(w, &(start, end))| {
+            let has_reading = w.feature.split(',').nth(READING_COLUMN).is_some_and(|r| !r.is_empty() && r != "*");
+            (Token::from_feature(&w.surface, &w.feature, start, end), has_reading)
+        })
+        .collect();
+
+    let mut result = SentenceEvaluation {
+        text: sentence.text(),
+        segmentation: SegmentationScore { gold: gold.len(), predicted: predicted.len(), correct: 0 },
+        pos_correct: 0,
+        reading_total: 0,
+        reading_correct: 0,
+        gold_surfaces: sentence.words.iter().map(|w| w.surface.clone()).collect(),
+        predicted_surfaces: predicted.iter().map(|t| t.surface.clone()).collect(),
+        tagging_errors: Vec::new(),
+    };
+    for token in predicted {
+        let Ok(i) = spans.binary_search(&(token.start, token.end)) else {
+            continue;
+        };
+        let (expected, has_reading) = &gold[i];
+        result.segmentation.correct += 1;
+        let (expected_pos, actual_pos) = (pos_label(expected, options.detailed_pos), pos_label(token, options.detailed_pos));
+        if expected_pos == actual_pos {
+            result.pos_correct += 1;
+        } else {
+            result.tagging_errors.push(TaggingError {
+                surface: token.surface.clone(),
+                field: "pos",
+                expected: expected_pos,
+                actual: actual_pos,
+            });
+        }
+        if *has_reading {
+            result.reading_total += 1;
+            if token.reading == expected.reading {
+                result.reading_correct += 1;
+            } else {
+                result.tagging_errors.push(TaggingError {
+                    surface: token.surface.clone(),
+                    field: "reading",
+                    expected: expected.reading.clone(),
+                    actual: token.reading.clone(),
+                });
+            }
+        }
+    }
+    result
+}
+
+/// 比べる品詞（`detailed`なら品詞細分類を`,`でつなげる）
+fn pos_label(token: &Token, detailed: bool) -> String {
+    if detailed && !token.pos_detail.is_empty() {
+        format!("{},{}", token.pos, token.pos_detail.join(","))
+    } else {
+        token.pos.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::mini_dictionary;
+
+    const CORPUS: &str = "吾輩\t名詞,代名詞,一般,*,*,*,吾輩,ワガハイ,ワガハイ
+は\t助詞,係助詞,*,*,*,*,は,ハ,ワ
+猫\t名詞,一般,*,*,*,*,猫,ネコ,ネコ
+で\t助動詞,*,*,*,特殊・ダ,連用形,だ,デ,デ
+ある\t助動詞,*,*,*,五段・ラ行アル,基本形,ある,アル,アル
+EOS
+今日\t名詞,副詞可能,*,*,*,*,今日,キョウ,コンニチ
+は\t助詞,係助詞,*,*,*,*,は,ハ,ワ
+良い天気\t名詞,一般,*,*,*,*,*
+EOS
+アプリ\t名詞,一般,*,*,*,*,アプリ,アプリ,アプリ
+を\t助詞,格助詞,一般,*,*,*,を,ヲ,ヲ
+作る\t動詞,自立,*,*,五段・ラ行,基本形,作る,ツクル,ツクル
+EOS
+";
+
+    fn evaluation(options: &EvalOptions) -> Evaluation {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        evaluate(&analyzer, &AnnotatedCorpus::parse(CORPUS).unwrap(), options).unwrap()
+    }
+
+    #[test]
+    fn test_boundary_scores() {
+        let e = evaluation(&EvalOptions::default());
+        // 「良い天気」は正解では1語、解析結果では2語
+        assert_eq!(e.sentences, 3);
+        assert_eq!(e.segmentation, SegmentationScore { gold: 11, predicted: 12, correct: 10 });
+        assert!((e.segmentation.precision() - 10.0 / 12.0).abs() < 1e-9);
+        assert!((e.segmentation.recall() - 10.0 / 11.0).abs() < 1e-9);
+        assert!((e.segmentation.f1() - 20.0 / 23.0).abs() < 1e-9);
+        assert_eq!(SegmentationScore::default().f1(), 0.0);
+    }
+
+    #[test]
+    fn test_pos_and_reading_accuracy() {
+        let e = evaluation(&EvalOptions::default());
+        // 「である」の「で」は助詞と解析される。読みは正解にある10語すべて一致
+        assert_eq!((e.pos_correct, e.reading_total, e.reading_correct), (9, 10, 10));
+        assert!((e.pos_accuracy() - 0.9).abs() < 1e-9);
+        assert_eq!(e.reading_accuracy(), 1.0);
+
+        // 細分類まで比べても、この例では差は出ない
+        let detailed = evaluation(&EvalOptions { detailed_pos: true, ..EvalOptions::default() });
+        assert_eq!(detailed.pos_correct, 9);
+        assert_eq!(detailed.worst[1].tagging_errors[0].actual, "助詞,格助詞,一般");
+    }
+
+    #[test]
+    fn test_worst_sentences() {
+        let e = evaluation(&EvalOptions::default());
+        let texts: Vec<_> = e.worst.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["今日は良い天気", "吾輩は猫である"]);
+        assert_eq!(e.worst[0].gold_surfaces, vec!["今日", "は", "良い天気"]);
+        assert_eq!(e.worst[0].predicted_surfaces, vec!["今日", "は", "良い", "天気"]);
+        let error = &e.worst[1].tagging_errors[0];
+        assert_eq!((error.surface.as_str(), error.field), ("で", "pos"));
+        assert_eq!((error.expected.as_str(), error.actual.as_str()), ("助動詞", "助詞"));
+
+        assert_eq!(evaluation(&EvalOptions { max_worst: 1, ..EvalOptions::default() }).worst.len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! フォールバック方針
+//! 辞書が読み込めない場合の挙動と、結果を生成したエンジンの識別子
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::segmenter::segment_surfaces;
+
+/// 辞書が使えない場合の挙動
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackPolicy {
+    /// フォールバックせずエラーを返す
+    #[default]
+    Error,
+    /// 文字種（漢字・ひらがな・カタカナ等）の切れ目で分割（[`crate::segmenter`]）
+    CharClass,
+    /// 句読点・空白で分割
+    Punctuation,
+}
+
+/// 解析結果を生成したエンジン
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    /// Vibrato（辞書ベースの形態素解析）
+    Vibrato,
+    /// 文字種フォールバック
+    CharClass,
+    /// 句読点フォールバック
+    Punctuation,
+    /// Lindera（feature`lindera`のバックエンド）
+    Lindera,
+}
+
+impl Engine {
+    /// 辞書を使わないフォールバック結果かどうか
+    pub fn is_fallback(self) -> bool {
+        matches!(self, Engine::CharClass | Engine::Punctuation)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Engine::Vibrato => "vibrato",
+            Engine::CharClass => "char_class",
+            Engine::Punctuation => "punctuation",
+            Engine::Lindera => "lindera",
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FallbackPolicy {
+    /// 環境変数`READMAKER_FALLBACK`から方針を取得（未設定時は`Error`）
+    pub fn from_env() -> std::result::Result<Self, String> {
+        match env::var("READMAKER_FALLBACK") {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(FallbackPolicy::default()),
+        }
+    }
+
+    /// 方針に従ってフォールバック分割を行う（`Error`の場合は`None`）
+    pub fn segment(self, text: &str) -> Option<(Engine, Vec<String>)> {
+        match self {
+            FallbackPolicy::Error => None,
+            FallbackPolicy::CharClass => Some((Engine::CharClass, segment_surfaces(text))),
+            FallbackPolicy::Punctuation => Some((Engine::Punctuation, split_punctuation(text))),
+        }
+    }
+}
+
+impl FromStr for FallbackPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "error" | "none" => Ok(FallbackPolicy::Error),
+            "char_class" | "charclass" => Ok(FallbackPolicy::CharClass),
+            "punctuation" | "punct" => Ok(FallbackPolicy::Punctuation),
+            other => Err(format!("不明なフォールバック方針: {}", other)),
+        }
+    }
+}
+
+/// 句読点・空白で分割（区切り文字は除去）
+pub fn split_punctuation(text: &str) -> Vec<String> {
+    let is_sep = |c: char| c.is_whitespace() || matches!(c, '。' | '、' | '！' | '？' | '!' | '?');
+    text.split(is_sep)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punctuation_split() {
+        let parts = split_punctuation("今日は、良い天気です。 明日も");
+        assert_eq!(parts, vec!["今日は", "良い天気です", "明日も"]);
+    }
+
+    #[test]
+    fn test_error_policy_does_not_segment() {
+        assert!(FallbackPolicy::Error.segment("今日").is_none());
+        let (engine, parts) = FallbackPolicy::CharClass.segment("今日は良い天気です。").unwrap();
+        assert_eq!(engine, Engine::CharClass);
+        assert_eq!(parts, vec!["今日は", "良い", "天気です", "。"]);
+        assert!(engine.is_fallback());
+    }
+
+    #[test]
+    fn test_policy_from_str() {
+        assert_eq!("char-class".parse::<FallbackPolicy>().unwrap(), FallbackPolicy::CharClass);
+        assert_eq!("Punctuation".parse::<FallbackPolicy>().unwrap(), FallbackPolicy::Punctuation);
+        assert!("per-char".parse::<FallbackPolicy>().is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! FFIのエラーコードと直近のエラーメッセージ
+//! `rm_`関数は`RmStatus`を返し、失敗時の詳細はスレッドごとに`rm_last_error_message()`で取得できる
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// FFI関数の結果コード（値はC側と共有するため変更しない）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RmStatus {
+    Ok = 0,
+    /// 必須の引数がNULL
+    NullPointer = 1,
+    /// 入力がUTF-8として不正
+    InvalidUtf8 = 2,
+    /// 出力にNUL文字が含まれCの文字列にできない
+    InteriorNul = 3,
+    /// 辞書の読み込みに失敗
+    Dictionary = 4,
+    /// 解析に失敗（フォールバック方針がエラー）
+    Analysis = 5,
+    /// オプションのJSONが不正
+    InvalidOptions = 6,
+    /// 結果のシリアライズに失敗
+    Serialization = 7,
+    /// Rust側でパニックが発生
+    Panic = 8,
+    /// ジョブがキャンセルされた
+    Cancelled = 9,
+    /// ジョブがまだ終わっていない
+    Pending = 10,
+}
+
+/// FFI内部のエラー（コードとメッセージ）
+#[derive(Debug)]
+pub struct FfiError {
+    pub status: RmStatus,
+    pub message: String,
+}
+
+impl FfiError {
+    pub fn new(status: RmStatus, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+pub type FfiResult<T> = Result<T, FfiError>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', "\\0")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+}
+
+/// FFI関数の本体を実行し、エラーとパニックを結果コードと直近のエラーメッセージに変換する
+///
+/// パニックはC側へ伝播させず（未定義動作になるため）`RmStatus::Panic`にする
+pub fn run(f: impl FnOnce() -> FfiResult<()>) -> RmStatus {
+    clear_last_error();
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "不明なパニック".to_string());
+        Err(FfiError::new(RmStatus::Panic, format!("パニック: {}", message)))
+    });
+    match result {
+        Ok(()) => RmStatus::Ok,
+        Err(e) => {
+            set_last_error(&e.message);
+            e.status
+        }
+    }
+}
+
+/// NULLでないC文字列をUTF-8の`&str`として読む
+pub fn read_str<'a>(ptr: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(FfiError::new(RmStatus::NullPointer, format!("{}がNULLです", name)));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|e| FfiError::new(RmStatus::InvalidUtf8, format!("{}がUTF-8として不正です: {}", name, e)))
+}
+
+/// 文字列を呼び出し側が解放するC文字列にする
+pub fn into_c_string(s: String) -> FfiResult<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|e| FfiError::new(RmStatus::InteriorNul, format!("結果にNUL文字が含まれています: {}", e)))
+}
+
+/// 出力先ポインタに値を書き込む
+pub fn write_out<T>(out: *mut T, value: T) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(FfiError::new(RmStatus::NullPointer, "出力先がNULLです"));
+    }
+    unsafe { out.write(value) };
+    Ok(())
+}
+
+/// このスレッドで直前に失敗した`rm_`関数のエラーメッセージ
+///
+/// 成功した直後やエラーがない場合はNULLを返す。ポインタは同じスレッドで次に`rm_`関数を呼ぶまで有効で、解放してはならない
+#[no_mangle]
+pub extern "C" fn rm_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let ptr = rm_last_error_message();
+        (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_run_sets_and_clears_last_error() {
+        let status = run(|| Err(FfiError::new(RmStatus::Analysis, "解析失敗")));
+        assert_eq!(status, RmStatus::Analysis);
+        assert_eq!(last_error().as_deref(), Some("解析失敗"));
+
+        assert_eq!(run(|| Ok(())), RmStatus::Ok);
+        assert_eq!(last_error(), None);
+    }
+
+    #[test]
+    fn test_panic_becomes_status() {
+        let status = run(|| panic!("想定外"));
+        assert_eq!(status, RmStatus::Panic);
+        assert!(last_error().unwrap().contains("想定外"));
+    }
+
+    #[test]
+    fn test_last_error_is_thread_local() {
+        run(|| Err(FfiError::new(RmStatus::Dictionary, "このスレッドのエラー")));
+        let other = std::thread::spawn(last_error).join().unwrap();
+        assert_eq!(other, None);
+        assert!(last_error().is_some());
+    }
+
+    #[test]
+    fn test_read_str_errors() {
+        assert_eq!(read_str(ptr::null(), "input").unwrap_err().status, RmStatus::NullPointer);
+        let invalid = [0xFFu8, 0xFE, 0x00];
+        let err = read_str(invalid.as_ptr() as *const c_char, "input").unwrap_err();
+        assert_eq!(err.status, RmStatus::InvalidUtf8);
+        assert_eq!(into_c_string("a\0b".to_string()).unwrap_err().status, RmStatus::InteriorNul);
+    }
+}
@@ -0,0 +1,429 @@
+//! 長文向けの解析ジョブ
+//! バックグラウンドスレッドで文ごとに解析し、進捗の取得・キャンセル・結果の取得をFFIで提供する
+
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use crate::{analyze_report_with_progress, MorphAnalyzer, Report, ReportOptions};
+use super::error::{self, FfiError, FfiResult, RmStatus};
+use super::{read_options, report_to_json, report_to_msgpack, RmAnalyzer, RmBuffer};
+
+/// ジョブの状態（値はC側と共有するため変更しない）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RmJobState {
+    Running = 0,
+    Done = 1,
+    Failed = 2,
+    Cancelled = 3,
+}
+
+/// 進捗の通知先
+///
+/// ジョブのスレッドから、解析前と1文終わるたびに`Running`で、終了時に最終状態で1度呼ばれる
+pub type RmProgressCallback =
+    extern "C" fn(user_data: *mut c_void, processed: usize, total: usize, state: RmJobState);
+
+struct Callback {
+    function: RmProgressCallback,
+    user_data: *mut c_void,
+}
+
+// user_dataをジョブのスレッドへ渡す（スレッドをまたいで使えることは呼び出し側が保証する）
+unsafe impl Send for Callback {}
+
+impl Callback {
+    fn call(&self, processed: usize, total: usize, state: RmJobState) {
+        (self.function)(self.user_data, processed, total, state);
+    }
+}
+
+enum Outcome {
+    Running,
+    Done(Report),
+    Failed(FfiError),
+    Cancelled,
+}
+
+impl Outcome {
+    fn state(&self) -> RmJobState {
+        match self {
+            Outcome::Running => RmJobState::Running,
+            Outcome::Done(_) => RmJobState::Done,
+            Outcome::Failed(_) => RmJobState::Failed,
+            Outcome::Cancelled => RmJobState::Cancelled,
+        }
+    }
+}
+
+struct Shared {
+    processed: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+    outcome: Mutex<Outcome>,
+    finished: Condvar,
+}
+
+/// 実行中または終了した解析ジョブ（FFIの不透明ハンドルの実体）
+pub struct RmJob {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RmJob {
+    /// バックグラウンドスレッドで解析を始める（`analyzer`がなければ`options.fallback`に従う）
+    pub fn start(analyzer: Option<Arc<MorphAnalyzer>>, text: String, options: ReportOptions) -> Self {
+        Self::spawn(analyzer, text, options, None)
+    }
+
+    fn spawn(
+        analyzer: Option<Arc<MorphAnalyzer>>,
+        text: String,
+        options: ReportOptions,
+        callback: Option<Callback>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            processed: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+            outcome: Mutex::new(Outcome::Running),
+            finished: Condvar::new(),
+        });
+
+        let worker = shared.clone();
+        let thread = thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                analyze_report_with_progress(analyzer.as_deref(), &text, &options, |processed, total| {
+                    worker.processed.store(processed, Ordering::Release);
+                    worker.total.store(total, Ordering::Release);
+                    if let Some(callback) = &callback {
+                        callback.call(processed, total, RmJobState::Running);
+                    }
+                    !worker.cancelled.load(Ordering::Acquire)
+                })
+            }));
+            let outcome = match result {
+                Ok(Ok(Some(report))) => Outcome::Done(report),
+                Ok(Ok(None)) => Outcome::Cancelled,
+                Ok(Err(e)) => Outcome::Failed(FfiError::new(RmStatus::Analysis, e.to_string())),
+                Err(_) => Outcome::Failed(FfiError::new(RmStatus::Panic, "解析中にパニックが発生しました")),
+            };
+            let state = outcome.state();
+            *worker.outcome.lock().unwrap() = outcome;
+            worker.finished.notify_all();
+
+            if let Some(callback) = &callback {
+                callback.call(worker.processed.load(Ordering::Acquire), worker.total.load(Ordering::Acquire), state);
+            }
+        });
+
+        Self { shared, thread: Some(thread) }
+    }
+
+    /// 処理済みの文数・全体の文数・状態
+    pub fn progress(&self) -> (usize, usize, RmJobState) {
+        let state = self.shared.outcome.lock().unwrap().state();
+        (
+            self.shared.processed.load(Ordering::Acquire),
+            self.shared.total.load(Ordering::Acquire),
+            state,
+        )
+    }
+
+    /// キャンセルを要求する（解析中の文が終わった時点で止まる）
+    pub fn cancel(&self) {
+        self.shared.cancelled.store(true, Ordering::Release);
+    }
+
+    /// 結果を返す。`wait`が偽で未完了なら`RmStatus::Pending`
+    pub fn result(&self, wait: bool) -> FfiResult<Report> {
+        let mut outcome = self.shared.outcome.lock().unwrap();
+        while wait && matches!(*outcome, Outcome::Running) {
+            outcome = self.shared.finished.wait(outcome).unwrap();
+        }
+        match &*outcome {
+            Outcome::Running => Err(FfiError::new(RmStatus::Pending, "ジョブは実行中です")),
+            Outcome::Done(report) => Ok(report.clone()),
+            Outcome::Failed(e) => Err(FfiError::new(e.status, e.message.clone())),
+            Outcome::Cancelled => Err(FfiError::new(RmStatus::Cancelled, "ジョブはキャンセルされました")),
+        }
+    }
+}
+
+impl Drop for RmJob {
+    fn drop(&mut self) {
+        self.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn job_ref<'a>(job: *const RmJob) -> FfiResult<&'a RmJob> {
+    unsafe { job.as_ref() }.ok_or_else(|| FfiError::new(RmStatus::NullPointer, "jobがNULLです"))
+}
+
+fn start(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    options_json: *const c_char,
+    callback: Option<Callback>,
+    out_job: *mut *mut RmJob,
+) -> RmStatus {
+    error::run(|| {
+        let text = error::read_str(input, "input")?.to_string();
+        let options = read_options(options_json)?;
+        if out_job.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_jobがNULLです"));
+        }
+        let analyzer = unsafe { handle.as_ref() }.map(|h| h.analyzer.clone());
+        let job = RmJob::spawn(analyzer, text, options, callback);
+        error::write_out(out_job, Box::into_raw(Box::new(job)))
+    })
+}
+
+/// 解析ジョブをバックグラウンドスレッドで開始し、ハンドルを`out_job`に書き込む
+///
+/// 引数は`rm_analyzer_analyze_report()`と同じで、入力はコピーされる。ジョブは辞書を共有するため、
+/// 実行中に`rm_analyzer_free()`を呼んでもよい。ハンドルは`rm_job_free()`で解放すること
+#[no_mangle]
+pub extern "C" fn rm_job_start(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    options_json: *const c_char,
+    out_job: *mut *mut RmJob,
+) -> RmStatus {
+    start(handle, input, options_json, None, out_job)
+}
+
+/// `rm_job_start()`と同じだが、進捗を`callback`で通知する
+///
+/// `callback`はジョブのスレッドから呼ばれる。終了時の呼び出しは`rm_job_result()`で結果を取得できるようになった後に行われる。
+/// コールバックの中で`rm_job_free()`を呼んではならない
+#[no_mangle]
+pub extern "C" fn rm_job_start_with_callback(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    options_json: *const c_char,
+    callback: Option<RmProgressCallback>,
+    user_data: *mut c_void,
+    out_job: *mut *mut RmJob,
+) -> RmStatus {
+    let callback = callback.map(|function| Callback { function, user_data });
+    start(handle, input, options_json, callback, out_job)
+}
+
+/// 進捗（処理済みの文数・全体の文数）と状態を取得する。不要な出力先はNULLでよい
+#[no_mangle]
+pub extern "C" fn rm_job_progress(
+    job: *const RmJob,
+    out_processed: *mut usize,
+    out_total: *mut usize,
+    out_state: *mut RmJobState,
+) -> RmStatus {
+    error::run(|| {
+        let (processed, total, state) = job_ref(job)?.progress();
+        if !out_processed.is_null() {
+            error::write_out(out_processed, processed)?;
+        }
+        if !out_total.is_null() {
+            error::write_out(out_total, total)?;
+        }
+        if !out_state.is_null() {
+            error::write_out(out_state, state)?;
+        }
+        Ok(())
+    })
+}
+
+/// ジョブのキャンセルを要求する（すぐに戻り、解析中の文が終わった時点で止まる）
+#[no_mangle]
+pub extern "C" fn rm_job_cancel(job: *const RmJob) -> RmStatus {
+    error::run(|| {
+        job_ref(job)?.cancel();
+        Ok(())
+    })
+}
+
+/// 結果のJSON（`rm_analyzer_analyze_report()`と同じ形式）を`out_json`に書き込む
+///
+/// `wait`が偽で未完了なら`RM_PENDING`、キャンセル済みなら`RM_CANCELLED`、解析に失敗していればその結果コードを返す。
+/// 結果は`rm_free_string()`で解放すること
+#[no_mangle]
+pub extern "C" fn rm_job_result(job: *const RmJob, wait: bool, out_json: *mut *mut c_char) -> RmStatus {
+    error::run(|| {
+        let job = job_ref(job)?;
+        if out_json.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_jsonがNULLです"));
+        }
+        let report = job.result(wait)?;
+        error::write_out(out_json, error::into_c_string(report_to_json(&report)?)?)
+    })
+}
+
+/// `rm_job_result()`と同じだが、結果をMessagePackで`out_buffer`に書き込む（`rm_free_buffer()`で解放する）
+#[no_mangle]
+pub extern "C" fn rm_job_result_msgpack(job: *const RmJob, wait: bool, out_buffer: *mut RmBuffer) -> RmStatus {
+    error::run(|| {
+        let job = job_ref(job)?;
+        if out_buffer.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_bufferがNULLです"));
+        }
+        let report = job.result(wait)?;
+        error::write_out(out_buffer, RmBuffer::from_vec(report_to_msgpack(&report)?))
+    })
+}
+
+/// ジョブを解放する。実行中ならキャンセルし、スレッドの終了を待つ
+#[no_mangle]
+pub extern "C" fn rm_job_free(job: *mut RmJob) {
+    if !job.is_null() {
+        unsafe {
+            drop(Box::from_raw(job));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+    use std::time::{Duration, Instant};
+    use crate::testutil;
+
+    fn start_job(handle: *const RmAnalyzer, text: &str) -> *mut RmJob {
+        let input = CString::new(text).unwrap();
+        let mut job = ptr::null_mut();
+        assert_eq!(rm_job_start(handle, input.as_ptr(), ptr::null(), &mut job), RmStatus::Ok);
+        job
+    }
+
+    fn progress(job: *const RmJob) -> (usize, usize, RmJobState) {
+        let (mut processed, mut total, mut state) = (0, 0, RmJobState::Running);
+        assert_eq!(rm_job_progress(job, &mut processed, &mut total, &mut state), RmStatus::Ok);
+        (processed, total, state)
+    }
+
+    #[test]
+    fn test_job_completes() {
+        let analyzer = RmAnalyzer::from_bytes(&testutil::mini_dictionary_bytes()).unwrap();
+        let job = start_job(&analyzer, "今日は良い天気です。吾輩は猫である。");
+        drop(analyzer);
+
+        let mut json = ptr::null_mut();
+        assert_eq!(rm_job_result(job, true, &mut json), RmStatus::Ok);
+        let value: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        assert_eq!(value["engine"], "vibrato");
+        assert_eq!(value["sentences"].as_array().unwrap().len(), 2);
+        assert_eq!(progress(job), (2, 2, RmJobState::Done));
+
+        let mut buffer = RmBuffer { data: ptr::null_mut(), len: 0 };
+        assert_eq!(rm_job_result_msgpack(job, false, &mut buffer), RmStatus::Ok);
+        let report = Report::from_msgpack(unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) }).unwrap();
+        assert_eq!(serde_json::to_value(&report).unwrap(), value);
+        crate::ffi::rm_free_buffer(buffer);
+
+        crate::ffi::rm_free_string(json);
+        rm_job_free(job);
+    }
+
+    #[test]
+    fn test_job_cancel_stops_job() {
+        let text = "吾輩は猫である。名前はまだ無い。".repeat(200_000);
+        let job = start_job(ptr::null(), &text);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while progress(job).0 == 0 {
+            assert!(Instant::now() < deadline, "ジョブが始まらない");
+            thread::yield_now();
+        }
+
+        assert_eq!(rm_job_cancel(job), RmStatus::Ok);
+        let (processed_at_cancel, total, _) = progress(job);
+
+        let mut json = ptr::null_mut();
+        assert_eq!(rm_job_result(job, true, &mut json), RmStatus::Cancelled);
+        assert!(json.is_null());
+
+        // キャンセル後は解析中だった1文までしか進まない
+        let (processed, _, state) = progress(job);
+        assert_eq!(state, RmJobState::Cancelled);
+        assert!(processed <= processed_at_cancel + 1);
+        assert!(processed < total);
+        rm_job_free(job);
+    }
+
+    extern "C" fn record(user_data: *mut c_void, processed: usize, total: usize, state: RmJobState) {
+        let calls = unsafe { &*(user_data as *const Mutex<Vec<(usize, usize, RmJobState)>>) };
+        calls.lock().unwrap().push((processed, total, state));
+    }
+
+    #[test]
+    fn test_job_free_cancels_running_job() {
+        let calls: Mutex<Vec<(usize, usize, RmJobState)>> = Mutex::new(Vec::new());
+        let input = CString::new("あいうえお。".repeat(500_000)).unwrap();
+        let mut job = ptr::null_mut();
+        let status = rm_job_start_with_callback(
+            ptr::null(),
+            input.as_ptr(),
+            ptr::null(),
+            Some(record),
+            &calls as *const _ as *mut c_void,
+            &mut job,
+        );
+        assert_eq!(status, RmStatus::Ok);
+        // 解放はキャンセルしてスレッドの終了（終了時のコールバック）を待つ
+        rm_job_free(job);
+
+        let calls = calls.into_inner().unwrap();
+        let &(processed, total, state) = calls.last().expect("終了時のコールバックが呼ばれていない");
+        assert_eq!(state, RmJobState::Cancelled);
+        assert!(processed < total);
+    }
+
+    #[test]
+    fn test_job_callback() {
+        let calls: Mutex<Vec<(usize, usize, RmJobState)>> = Mutex::new(Vec::new());
+        let input = CString::new("一文目。二文目。三文目。").unwrap();
+        let mut job = ptr::null_mut();
+        let status = rm_job_start_with_callback(
+            ptr::null(),
+            input.as_ptr(),
+            ptr::null(),
+            Some(record),
+            &calls as *const _ as *mut c_void,
+            &mut job,
+        );
+        assert_eq!(status, RmStatus::Ok);
+        let mut json = ptr::null_mut();
+        assert_eq!(rm_job_result(job, true, &mut json), RmStatus::Ok);
+        crate::ffi::rm_free_string(json);
+        // 解放時にスレッドの終了（終了時のコールバック）を待つ
+        rm_job_free(job);
+
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls.first(), Some(&(0, 3, RmJobState::Running)));
+        assert_eq!(calls.last(), Some(&(3, 3, RmJobState::Done)));
+        assert_eq!(calls.len(), 5);
+    }
+
+    #[test]
+    fn test_job_errors() {
+        let input = CString::new("テキスト").unwrap();
+        let options = CString::new(r#"{"fallback":"error"}"#).unwrap();
+        let mut job = ptr::null_mut();
+        assert_eq!(rm_job_start(ptr::null(), input.as_ptr(), options.as_ptr(), &mut job), RmStatus::Ok);
+        let mut json = ptr::null_mut();
+        assert_eq!(rm_job_result(job, true, &mut json), RmStatus::Analysis);
+        assert_eq!(progress(job).2, RmJobState::Failed);
+        rm_job_free(job);
+
+        assert_eq!(rm_job_start(ptr::null(), ptr::null(), ptr::null(), &mut job), RmStatus::NullPointer);
+        assert_eq!(rm_job_cancel(ptr::null()), RmStatus::NullPointer);
+        assert_eq!(rm_job_result(ptr::null(), false, &mut json), RmStatus::NullPointer);
+        rm_job_free(ptr::null_mut());
+    }
+}
@@ -0,0 +1,571 @@
+//! React Native FFI ブリッジ
+//! JavaScript/TypeScript・モバイルのネイティブコードから呼び出すC FFI（宣言は`include/readmaker.h`）
+//!
+//! モバイル向けのライブラリ（libreadmaker_core）は`ReadMaker/native/rust`がこのモジュールを再公開して出力する
+
+// C ABIの関数はポインタの正当性を呼び出し側が保証する前提
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::env;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::fallback::{Engine, FallbackPolicy};
+use crate::{analyze_report, analyze_with, fallback_analysis, MorphAnalyzer, Report, ReportOptions, REPORT_SCHEMA_VERSION};
+
+pub use error::{rm_last_error_message, RmStatus};
+pub use job::{RmJob, RmJobState};
+
+use error::FfiError;
+
+mod error;
+mod job;
+
+/// 解析結果（どのエンジンが生成したかを必ず保持する）
+#[derive(Debug, Clone, Serialize)]
+pub struct Analysis {
+    pub engine: Engine,
+    pub words: Vec<String>,
+}
+
+/// 辞書ファイルパス（環境変数`READMAKER_DIC_PATH`で上書き可能）
+pub fn dictionary_path() -> String {
+    env::var("READMAKER_DIC_PATH").unwrap_or_else(|_| "dictionaries/ipadic.vibrato".to_string())
+}
+
+/// 公開API: 辞書データ（zstd圧縮/非圧縮）を読み込んで形態素解析する
+///
+/// 辞書が読み込めない・解析に失敗した場合は`policy`に従い、エラーを返すかフォールバック分割する
+pub fn analyze_text(input: &str, dictionary_data: &[u8], policy: FallbackPolicy) -> io::Result<Analysis> {
+    let analysis = match MorphAnalyzer::from_bytes(dictionary_data) {
+        Ok(analyzer) => analyze_with(Some(&analyzer), input, policy),
+        Err(e) => fallback_analysis(input, policy, e),
+    }
+    .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(Analysis { words: analysis.surfaces(), engine: analysis.engine })
+}
+
+/// 形態素解析結果をJSON形式で返す（React Native用）
+pub fn words_to_json(words: &[String]) -> String {
+    serde_json::to_string(words).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// エンジン識別子付きの解析結果をJSON形式で返す
+pub fn analysis_to_json(analysis: &Analysis) -> String {
+    serde_json::to_string(analysis).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// JavaScript用のC FFI形態素解析関数
+///
+/// 辞書が読み込めない場合は文字種フォールバックを使い、`engine`にその旨を記録する。
+/// 失敗時はNULLを返し、理由は`rm_last_error_message()`で取得できる
+///
+/// # 使用方法（JavaScript側）
+/// ```javascript
+/// const result = await analyzeText("吾輩は猫である。", dictionaryData);
+/// console.log(result); // {"engine":"vibrato","words":["吾輩", "は", "猫", "で", "ある", "。"]}
+/// ```
+#[no_mangle]
+pub extern "C" fn js_analyze_text(
+    input: *const c_char,
+    dictionary_data_ptr: *const u8,
+    dictionary_data_len: usize,
+) -> *mut c_char {
+    let mut result = ptr::null_mut();
+    error::run(|| {
+        let input_str = error::read_str(input, "input")?;
+        let dictionary_data = read_bytes(dictionary_data_ptr, dictionary_data_len, "dictionary_data")?;
+        let analysis = analyze_text(input_str, dictionary_data, FallbackPolicy::CharClass)
+            .map_err(|e| FfiError::new(RmStatus::Analysis, e.to_string()))?;
+        result = error::into_c_string(analysis_to_json(&analysis))?;
+        Ok(())
+    });
+    result
+}
+
+/// ポインタと長さからバイト列を読む（NULLはエラー）
+fn read_bytes<'a>(data: *const u8, len: usize, name: &str) -> error::FfiResult<&'a [u8]> {
+    if data.is_null() {
+        return Err(FfiError::new(RmStatus::NullPointer, format!("{}がNULLです", name)));
+    }
+    Ok(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// JavaScript用のメモリ解放関数
+///
+/// # 重要
+/// js_analyze_text()で返されたポインタは必ずこの関数で解放すること
+#[no_mangle]
+pub extern "C" fn js_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        unsafe {
+            let _ = CString::from_raw(ptr);
+        }
+    }
+}
+
+/// Bridge初期化確認用関数
+#[no_mangle]
+pub extern "C" fn js_test_bridge() -> *mut c_char {
+    let test_message = "ReadMaker Rust Bridge - OK";
+    match CString::new(test_message) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// 辞書を読み込み済みのアナライザー（FFIの不透明ハンドルの実体）
+///
+/// 解析ごとにワーカーを作るため、1つのハンドルを複数スレッドから同時に使える
+pub struct RmAnalyzer {
+    analyzer: Arc<MorphAnalyzer>,
+}
+
+impl RmAnalyzer {
+    /// 辞書データ（zstd圧縮/非圧縮）から作成
+    pub fn from_bytes(dictionary_data: &[u8]) -> io::Result<Self> {
+        let analyzer = MorphAnalyzer::from_bytes(dictionary_data).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Self { analyzer: Arc::new(analyzer) })
+    }
+
+    /// 辞書ファイル（zstd圧縮/非圧縮）から作成。ファイルはメモリマップして読むため、JS側へ辞書を読み込む必要はない
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let analyzer = MorphAnalyzer::from_path(path).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Self { analyzer: Arc::new(analyzer) })
+    }
+
+    /// 読み込み済みの辞書で解析（失敗時は文字種フォールバック）
+    pub fn analyze(&self, input: &str) -> io::Result<Analysis> {
+        let analysis = analyze_with(Some(&self.analyzer), input, FallbackPolicy::CharClass)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Analysis { words: analysis.surfaces(), engine: analysis.engine })
+    }
+
+    /// 形態素・文・表示用のまとまりを含む詳細な解析結果
+    pub fn report(&self, input: &str, options: &ReportOptions) -> io::Result<Report> {
+        analyze_report(Some(&self.analyzer), input, options).map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+/// 辞書を1度だけ読み込み、アナライザーのハンドルを`out_handle`に書き込む
+///
+/// 辞書データはこの関数の中でコピーされるため、呼び出し後に解放してよい。
+/// 失敗時は`RmStatus::Dictionary`などを返し、`out_handle`は変更しない。ハンドルは`rm_analyzer_free()`で解放すること
+#[no_mangle]
+pub extern "C" fn rm_analyzer_new(
+    dictionary_data_ptr: *const u8,
+    dictionary_data_len: usize,
+    out_handle: *mut *mut RmAnalyzer,
+) -> RmStatus {
+    error::run(|| {
+        let dictionary_data = read_bytes(dictionary_data_ptr, dictionary_data_len, "dictionary_data")?;
+        if out_handle.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_handleがNULLです"));
+        }
+        let analyzer = RmAnalyzer::from_bytes(dictionary_data)
+            .map_err(|e| FfiError::new(RmStatus::Dictionary, format!("辞書の読み込みに失敗しました: {}", e)))?;
+        error::write_out(out_handle, Box::into_raw(Box::new(analyzer)))
+    })
+}
+
+/// 辞書ファイルのパス（UTF-8）からアナライザーのハンドルを`out_handle`に書き込む
+///
+/// 辞書はRust側でメモリマップして読むため、アセットをJSやJava側でバイト列に読み込んでブリッジ越しにコピーする必要がない
+#[no_mangle]
+pub extern "C" fn rm_analyzer_new_from_path(path: *const c_char, out_handle: *mut *mut RmAnalyzer) -> RmStatus {
+    error::run(|| {
+        let path = error::read_str(path, "path")?;
+        if out_handle.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_handleがNULLです"));
+        }
+        let analyzer = RmAnalyzer::from_path(path)
+            .map_err(|e| FfiError::new(RmStatus::Dictionary, format!("辞書の読み込みに失敗しました: {}", e)))?;
+        error::write_out(out_handle, Box::into_raw(Box::new(analyzer)))
+    })
+}
+
+/// ハンドルのアナライザーで解析し、`js_analyze_text()`と同じ形式のJSONを`out_json`に書き込む
+///
+/// 同じハンドルを複数スレッドから同時に呼び出してよい。結果は`rm_free_string()`で解放すること
+#[no_mangle]
+pub extern "C" fn rm_analyzer_analyze(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    out_json: *mut *mut c_char,
+) -> RmStatus {
+    error::run(|| {
+        let analyzer = unsafe { handle.as_ref() }
+            .ok_or_else(|| FfiError::new(RmStatus::NullPointer, "handleがNULLです"))?;
+        let input_str = error::read_str(input, "input")?;
+        if out_json.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_jsonがNULLです"));
+        }
+        let analysis = analyzer.analyze(input_str).map_err(|e| FfiError::new(RmStatus::Analysis, e.to_string()))?;
+        error::write_out(out_json, error::into_c_string(analysis_to_json(&analysis))?)
+    })
+}
+
+/// バージョン付きの詳細な解析結果をJSONで`out_json`に書き込む
+///
+/// `options_json`はNULLまたは`{"tokens":true,"sentences":true,"chunks":true,"fallback":"char_class"}`の形式で、
+/// 省略した項目は既定値になる。`handle`がNULLの場合は辞書なしで`fallback`に従って分割する。
+/// 結果は`rm_free_string()`で解放すること
+///
+/// ```json
+/// {"version":1,"engine":"vibrato",
+///  "tokens":[{"surface":"今日","reading":"キョウ","pos":"名詞","pos_detail":["副詞可能"],"lemma":"今日","start":0,"end":2}, ...],
+///  "sentences":[{"start":0,"end":10,"token_start":0,"token_end":6}],
+///  "chunks":[{"text":"今日は","start":0,"end":3,"token_start":0,"token_end":2}, ...]}
+/// ```
+#[no_mangle]
+pub extern "C" fn rm_analyzer_analyze_report(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    options_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> RmStatus {
+    error::run(|| {
+        let input_str = error::read_str(input, "input")?;
+        let options = read_options(options_json)?;
+        if out_json.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_jsonがNULLです"));
+        }
+
+        let analyzer = unsafe { handle.as_ref() }.map(|h| h.analyzer.as_ref());
+        let report = analyze_report(analyzer, input_str, &options)
+            .map_err(|e| FfiError::new(RmStatus::Analysis, e.to_string()))?;
+        error::write_out(out_json, error::into_c_string(report_to_json(&report)?)?)
+    })
+}
+
+/// 詳細な解析結果をMessagePackで`out_buffer`に書き込む
+///
+/// 引数と結果の構造は`rm_analyzer_analyze_report()`と同じ（スキーマは`backend/core/REPORT_SCHEMA.md`）。
+/// JSON文字列より小さく、長文でも変換が速い。結果は`rm_free_buffer()`で解放すること
+#[no_mangle]
+pub extern "C" fn rm_analyzer_analyze_report_msgpack(
+    handle: *const RmAnalyzer,
+    input: *const c_char,
+    options_json: *const c_char,
+    out_buffer: *mut RmBuffer,
+) -> RmStatus {
+    error::run(|| {
+        let input_str = error::read_str(input, "input")?;
+        let options = read_options(options_json)?;
+        if out_buffer.is_null() {
+            return Err(FfiError::new(RmStatus::NullPointer, "out_bufferがNULLです"));
+        }
+
+        let analyzer = unsafe { handle.as_ref() }.map(|h| h.analyzer.as_ref());
+        let report = analyze_report(analyzer, input_str, &options)
+            .map_err(|e| FfiError::new(RmStatus::Analysis, e.to_string()))?;
+        error::write_out(out_buffer, RmBuffer::from_vec(report_to_msgpack(&report)?))
+    })
+}
+
+/// 結果のスキーマのバージョン（JSON・MessagePackの`version`と同じ値）
+#[no_mangle]
+pub extern "C" fn rm_report_schema_version() -> u32 {
+    REPORT_SCHEMA_VERSION
+}
+
+/// Rustが確保したバイト列（`rm_free_buffer()`で解放する）
+#[repr(C)]
+#[derive(Debug)]
+pub struct RmBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl RmBuffer {
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+}
+
+/// `rm_`関数が返したバイト列を解放する
+#[no_mangle]
+pub extern "C" fn rm_free_buffer(buffer: RmBuffer) {
+    if !buffer.data.is_null() {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+        }
+    }
+}
+
+/// オプションのJSON（NULLなら既定値）を読む
+fn read_options(options_json: *const c_char) -> error::FfiResult<ReportOptions> {
+    if options_json.is_null() {
+        return Ok(ReportOptions::default());
+    }
+    ReportOptions::from_json(error::read_str(options_json, "options_json")?)
+        .map_err(|e| FfiError::new(RmStatus::InvalidOptions, e.to_string()))
+}
+
+fn report_to_json(report: &Report) -> error::FfiResult<String> {
+    serde_json::to_string(report).map_err(|e| FfiError::new(RmStatus::Serialization, e.to_string()))
+}
+
+fn report_to_msgpack(report: &Report) -> error::FfiResult<Vec<u8>> {
+    report.to_msgpack().map_err(|e| FfiError::new(RmStatus::Serialization, e.to_string()))
+}
+
+/// `rm_analyzer_new()`で作成したハンドルを解放する
+///
+/// 同期APIで解析中のスレッドが残っている間に呼んではならない（実行中のジョブは辞書を共有しているため影響しない）
+#[no_mangle]
+pub extern "C" fn rm_analyzer_free(handle: *mut RmAnalyzer) {
+    if !handle.is_null() {
+        unsafe {
+            drop(Box::from_raw(handle));
+        }
+    }
+}
+
+/// `rm_`関数が返した文字列を解放する
+#[no_mangle]
+pub extern "C" fn rm_free_string(ptr: *mut c_char) {
+    js_free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{CStr, CString};
+    use crate::testutil;
+
+    #[test]
+    fn test_js_bridge_basic() {
+        let input = CString::new("今日は良い天気です。").unwrap();
+        // 辞書なし（空データ）の場合は文字種フォールバックになる
+        let dictionary: [u8; 0] = [];
+        let result_ptr = js_analyze_text(input.as_ptr(), dictionary.as_ptr(), dictionary.len());
+
+        assert!(!result_ptr.is_null());
+
+        let result_cstr = unsafe { CStr::from_ptr(result_ptr) };
+        let result_str = result_cstr.to_str().unwrap();
+
+        // デバッグ出力追加
+        println!("形態素解析結果: {}", result_str);
+
+        // エンジン識別子付きのJSONかチェック
+        let value: serde_json::Value = serde_json::from_str(result_str).unwrap();
+        assert_eq!(value["engine"], "char_class");
+        assert!(value["words"].as_array().unwrap().iter().any(|w| w == "今日は"));
+
+        // メモリ解放
+        js_free_string(result_ptr);
+    }
+
+    #[test]
+    fn test_error_policy_without_dictionary() {
+        assert!(analyze_text("今日は良い天気です。", &[], FallbackPolicy::Error).is_err());
+    }
+
+    fn new_handle(dictionary: &[u8]) -> *mut RmAnalyzer {
+        let mut handle = ptr::null_mut();
+        assert_eq!(rm_analyzer_new(dictionary.as_ptr(), dictionary.len(), &mut handle), RmStatus::Ok);
+        assert!(!handle.is_null());
+        handle
+    }
+
+    fn last_error() -> String {
+        let message = rm_last_error_message();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_analyzer_handle() {
+        let dictionary = testutil::mini_dictionary_bytes();
+        let handle = new_handle(&dictionary);
+        drop(dictionary);
+
+        let input = CString::new("今日は良い天気です。").unwrap();
+        let mut result_ptr = ptr::null_mut();
+        assert_eq!(rm_analyzer_analyze(handle, input.as_ptr(), &mut result_ptr), RmStatus::Ok);
+        assert!(rm_last_error_message().is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(result_ptr) }.to_str().unwrap()).unwrap();
+        assert_eq!(value["engine"], "vibrato");
+        assert_eq!(value["words"], serde_json::json!(["今日", "は", "良い", "天気", "です", "。"]));
+
+        rm_free_string(result_ptr);
+        rm_analyzer_free(handle);
+    }
+
+    #[test]
+    fn test_analyzer_from_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw = testutil::mini_dictionary_bytes();
+        #[allow(unused_mut)]
+        let mut files = vec![("raw.vibrato", raw.clone())];
+        #[cfg(feature = "zstd")]
+        files.push(("ipadic.vibrato.zst", zstd::encode_all(raw.as_slice(), 3).unwrap()));
+        for (name, data) in files {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+
+            let path = CString::new(path.to_str().unwrap()).unwrap();
+            let mut handle = ptr::null_mut();
+            assert_eq!(rm_analyzer_new_from_path(path.as_ptr(), &mut handle), RmStatus::Ok);
+            let analyzer = unsafe { &*handle };
+            assert_eq!(analyzer.analyze("吾輩は猫である。").unwrap().engine, Engine::Vibrato);
+            rm_analyzer_free(handle);
+        }
+
+        let missing = CString::new(dir.path().join("missing.vibrato").to_str().unwrap()).unwrap();
+        let mut handle = ptr::null_mut();
+        assert_eq!(rm_analyzer_new_from_path(missing.as_ptr(), &mut handle), RmStatus::Dictionary);
+        assert!(last_error().contains("missing.vibrato"));
+        assert_eq!(rm_analyzer_new_from_path(ptr::null(), &mut handle), RmStatus::NullPointer);
+        assert!(handle.is_null());
+    }
+
+    #[test]
+    fn test_analyzer_handle_shared_across_threads() {
+        let analyzer = std::sync::Arc::new(RmAnalyzer::from_bytes(&testutil::mini_dictionary_bytes()).unwrap());
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let analyzer = analyzer.clone();
+                std::thread::spawn(move || analyzer.analyze("吾輩は猫である。").unwrap().words)
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), vec!["吾輩", "は", "猫", "で", "ある", "。"]);
+        }
+    }
+
+    fn report_status(handle: *const RmAnalyzer, input: &str, options: Option<&str>) -> (RmStatus, *mut c_char) {
+        let input = CString::new(input).unwrap();
+        let options = options.map(|o| CString::new(o).unwrap());
+        let mut result_ptr = ptr::null_mut();
+        let status = rm_analyzer_analyze_report(
+            handle,
+            input.as_ptr(),
+            options.as_ref().map_or(ptr::null(), |o| o.as_ptr()),
+            &mut result_ptr,
+        );
+        (status, result_ptr)
+    }
+
+    fn report_json(handle: *const RmAnalyzer, input: &str, options: Option<&str>) -> serde_json::Value {
+        let (status, result_ptr) = report_status(handle, input, options);
+        assert_eq!(status, RmStatus::Ok);
+        let value = serde_json::from_str(unsafe { CStr::from_ptr(result_ptr) }.to_str().unwrap()).unwrap();
+        rm_free_string(result_ptr);
+        value
+    }
+
+    #[test]
+    fn test_analyze_report_json() {
+        let dictionary = testutil::mini_dictionary_bytes();
+        let handle = new_handle(&dictionary);
+
+        let value = report_json(handle, "今日は良い天気です。", None);
+        assert_eq!(value["version"], REPORT_SCHEMA_VERSION);
+        assert_eq!(value["tokens"][0]["reading"], "キョウ");
+        assert_eq!(value["tokens"][0]["pos"], "名詞");
+        assert_eq!(value["tokens"][3]["start"], 5);
+        assert_eq!(value["sentences"].as_array().unwrap().len(), 1);
+        assert_eq!(value["chunks"][0]["text"], "今日は");
+
+        let value = report_json(handle, "今日は良い天気です。", Some(r#"{"tokens":false,"chunks":false}"#));
+        assert!(value.get("tokens").is_none());
+        assert!(value.get("chunks").is_none());
+        assert!(value.get("sentences").is_some());
+
+        rm_analyzer_free(handle);
+
+        // ハンドルなしはフォールバック方針に従う
+        let value = report_json(ptr::null(), "今日は良い天気です。", None);
+        assert_eq!(value["engine"], "char_class");
+        let (status, result_ptr) = report_status(ptr::null(), "テキスト", Some(r#"{"fallback":"error"}"#));
+        assert_eq!(status, RmStatus::Analysis);
+        assert!(result_ptr.is_null());
+    }
+
+    #[test]
+    fn test_analyze_report_msgpack() {
+        let dictionary = testutil::mini_dictionary_bytes();
+        let handle = new_handle(&dictionary);
+        let input = CString::new("今日は良い天気です。").unwrap();
+        let mut buffer = RmBuffer { data: ptr::null_mut(), len: 0 };
+        assert_eq!(
+            rm_analyzer_analyze_report_msgpack(handle, input.as_ptr(), ptr::null(), &mut buffer),
+            RmStatus::Ok
+        );
+        let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        let report = Report::from_msgpack(bytes).unwrap();
+        assert_eq!(serde_json::to_value(&report).unwrap(), report_json(handle, "今日は良い天気です。", None));
+        rm_free_buffer(buffer);
+
+        let mut buffer = RmBuffer { data: ptr::null_mut(), len: 0 };
+        let error = CString::new(r#"{"fallback":"error"}"#).unwrap();
+        let status = rm_analyzer_analyze_report_msgpack(ptr::null(), input.as_ptr(), error.as_ptr(), &mut buffer);
+        assert_eq!(status, RmStatus::Analysis);
+        assert!(buffer.data.is_null());
+        rm_free_buffer(buffer);
+        rm_analyzer_free(handle);
+    }
+
+    #[test]
+    fn test_error_status_codes() {
+        let mut handle = ptr::null_mut();
+        let broken = b"not a dictionary";
+        assert_eq!(rm_analyzer_new(broken.as_ptr(), broken.len(), &mut handle), RmStatus::Dictionary);
+        assert!(handle.is_null());
+        assert!(last_error().contains("辞書"));
+        assert_eq!(rm_analyzer_new(ptr::null(), 0, &mut handle), RmStatus::NullPointer);
+        assert!(last_error().contains("dictionary_data"));
+
+        let mut result_ptr = ptr::null_mut();
+        let input = CString::new("テキスト").unwrap();
+        assert_eq!(rm_analyzer_analyze(ptr::null(), input.as_ptr(), &mut result_ptr), RmStatus::NullPointer);
+
+        // 不正なUTF-8
+        let invalid = [0xE3u8, 0x81, 0x00];
+        let (status, _) = {
+            let mut out = ptr::null_mut();
+            let status = rm_analyzer_analyze_report(
+                ptr::null(),
+                invalid.as_ptr() as *const c_char,
+                ptr::null(),
+                &mut out,
+            );
+            (status, out)
+        };
+        assert_eq!(status, RmStatus::InvalidUtf8);
+        assert!(last_error().contains("UTF-8"));
+
+        let (status, _) = report_status(ptr::null(), "テキスト", Some("{broken"));
+        assert_eq!(status, RmStatus::InvalidOptions);
+        let (status, _) = report_status(ptr::null(), "テキスト", Some(r#"{"fallback":"unknown"}"#));
+        assert_eq!(status, RmStatus::InvalidOptions);
+
+        // js_analyze_textもNULLを返すときは理由を残す
+        assert!(js_analyze_text(ptr::null(), broken.as_ptr(), broken.len()).is_null());
+        assert!(last_error().contains("input"));
+        rm_analyzer_free(ptr::null_mut());
+    }
+
+    #[test]
+    fn test_js_bridge_test() {
+        let result_ptr = js_test_bridge();
+        assert!(!result_ptr.is_null());
+
+        let result_cstr = unsafe { CStr::from_ptr(result_ptr) };
+        let result_str = result_cstr.to_str().unwrap();
+
+        assert_eq!(result_str, "ReadMaker Rust Bridge - OK");
+
+        js_free_string(result_ptr);
+    }
+}
@@ -0,0 +1,177 @@
+//! ふりがな
+//! 形態素の読み（カタカナ）を送り仮名と突き合わせ、表層形の漢字部分にだけルビを振る
+
+use serde::{Deserialize, Serialize};
+use crate::document::Ruby;
+use crate::report::Token;
+use crate::segmenter::{script_of, Script};
+
+/// カタカナをひらがなにする（長音符などそれ以外の文字はそのまま）
+pub fn katakana_to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            'ヽ' | 'ヾ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(script_of(c), Script::Hiragana | Script::Katakana)
+}
+
+/// 表層形の漢字と仮名の連続（仮名はひらがなに揃える）
+enum Run {
+    Kanji { start: usize, end: usize },
+    Kana(Vec<char>),
+}
+
+fn runs(surface: &[char]) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for (i, &c) in surface.iter().enumerate() {
+        let kana = is_kana(c);
+        match runs.last_mut() {
+            Some(Run::Kanji { end, .. }) if !kana => *end = i + 1,
+            Some(Run::Kana(chars)) if kana => chars.extend(katakana_to_hiragana(&c.to_string()).chars()),
+            _ if kana => runs.push(Run::Kana(katakana_to_hiragana(&c.to_string()).chars().collect())),
+            _ => runs.push(Run::Kanji { start: i, end: i + 1 }),
+        }
+    }
+    runs
+}
+
+/// 読みを各連続に割り当てる（仮名は一致させ、漢字には1文字以上を割り当てる）
+fn align(runs: &[Run], reading: &[char], out: &mut Vec<(usize, usize, String)>) -> bool {
+    let Some((run, rest)) = runs.split_first() else {
+        return reading.is_empty();
+    };
+    match run {
+        Run::Kana(chars) => reading.starts_with(chars) && align(rest, &reading[chars.len()..], out),
+        Run::Kanji { start, end } => {
+            let remaining: usize = rest.iter().map(|r| if let Run::Kana(c) = r { c.len() } else { 1 }).sum();
+            for len in (1..=reading.len().saturating_sub(remaining)).rev() {
+                let mark = out.len();
+                out.push((*start, *end, reading[..len].iter().collect()));
+                if align(rest, &reading[len..], out) {
+                    return true;
+                }
+                out.truncate(mark);
+            }
+            false
+        }
+    }
+}
+
+/// 1つの形態素のルビ（オフセットは入力テキスト上の文字単位）
+///
+/// 「食べる/タベル」なら「食」だけに「た」を振る。送り仮名と読みが合わない場合は漢字を含む表層形全体に振る
+pub fn token_furigana(token: &Token) -> Vec<Ruby> {
+    let surface: Vec<char> = token.surface.chars().collect();
+    if !surface.iter().any(|&c| script_of(c) == Script::Kanji)
+        || token.reading == token.surface
+        || !token.reading.chars().all(|c| is_kana(c) || c == 'ー')
+    {
+        return Vec::new();
+    }
+    let reading: Vec<char> = katakana_to_hiragana(&token.reading).chars().collect();
+
+    let mut aligned = Vec::new();
+    if !align(&runs(&surface), &reading, &mut aligned) {
+        return vec![Ruby { start: token.start, end: token.end, reading: reading.into_iter().collect() }];
+    }
+    aligned
+        .into_iter()
+        .map(|(start, end, reading)| Ruby { start: token.start + start, end: token.start + end, reading })
+        .collect()
+}
+
+/// 形態素列のルビ
+pub fn furigana(tokens: &[Token]) -> Vec<Ruby> {
+    tokens.iter().flat_map(token_furigana).collect()
+}
+
+/// ルビ付きテキストの区間（テキスト全体を隙間なく覆う。`<ruby>`の描画用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RubySegment {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading: Option<String>,
+}
+
+/// テキストをルビのある区間とない区間に分ける（`ruby`は開始位置順で重ならないこと）
+pub fn ruby_segments(text: &str, ruby: &[Ruby]) -> Vec<RubySegment> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for r in ruby.iter().filter(|r| r.start < r.end && r.end <= chars.len()) {
+        if r.start < pos {
+            continue;
+        }
+        if pos < r.start {
+            segments.push(RubySegment { text: chars[pos..r.start].iter().collect(), reading: None });
+        }
+        segments.push(RubySegment { text: chars[r.start..r.end].iter().collect(), reading: Some(r.reading.clone()) });
+        pos = r.end;
+    }
+    if pos < chars.len() {
+        segments.push(RubySegment { text: chars[pos..].iter().collect(), reading: None });
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ruby(surface: &str, reading: &str) -> Vec<(String, String)> {
+        let token = Token { reading: reading.to_string(), ..Token::plain(surface, 10, 10 + surface.chars().count()) };
+        let chars: Vec<char> = surface.chars().collect();
+        token_furigana(&token)
+            .into_iter()
+            .map(|r| (chars[r.start - 10..r.end - 10].iter().collect(), r.reading))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+    }
+
+    #[test]
+    fn test_okurigana() {
+        assert_eq!(ruby("食べる", "タベル"), pairs(&[("食", "た")]));
+        assert_eq!(ruby("今日", "キョウ"), pairs(&[("今日", "きょう")]));
+        assert_eq!(ruby("お茶", "オチャ"), pairs(&[("茶", "ちゃ")]));
+        assert_eq!(ruby("取り扱い", "トリアツカイ"), pairs(&[("取", "と"), ("扱", "あつか")]));
+    }
+
+    #[test]
+    fn test_no_ruby() {
+        assert!(ruby("ひらがな", "ヒラガナ").is_empty());
+        assert!(ruby("カタカナ", "カタカナ").is_empty());
+        // 読みが分からない漢字
+        assert!(ruby("未知", "未知").is_empty());
+    }
+
+    #[test]
+    fn test_mismatched_reading_covers_surface() {
+        assert_eq!(ruby("行った", "イッテ"), pairs(&[("行った", "いって")]));
+    }
+
+    #[test]
+    fn test_katakana_to_hiragana() {
+        assert_eq!(katakana_to_hiragana("キョウハ、ヨイテンキ"), "きょうは、よいてんき");
+        assert_eq!(katakana_to_hiragana("ラーメン"), "らーめん");
+    }
+
+    #[test]
+    fn test_ruby_segments() {
+        let ruby = vec![
+            Ruby { start: 0, end: 2, reading: "きょう".to_string() },
+            Ruby { start: 3, end: 4, reading: "た".to_string() },
+        ];
+        let segments = ruby_segments("今日は食べる", &ruby);
+        let texts: Vec<_> = segments.iter().map(|s| (s.text.as_str(), s.reading.as_deref())).collect();
+        assert_eq!(texts, vec![("今日", Some("きょう")), ("は", None), ("食", Some("た")), ("べる", None)]);
+    }
+}
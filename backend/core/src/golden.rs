@@ -0,0 +1,188 @@
+//! 分割のゴールデンテスト
+//! 入力文ごとに期待する形態素（表層形・読み・品詞）とまとまりをJSON Lines（1行1文）で保存しておき、
+//! 現在の解析結果と比べる。辞書やコードの変更で分割が変わったら検出し、意図した変更なら保存し直す（bless）
+
+use serde::{Deserialize, Serialize};
+use readmaker_shared::{ReadMakerError, Result};
+use crate::fallback::FallbackPolicy;
+use crate::report::{analyze_report, ReportOptions};
+use crate::MorphAnalyzer;
+
+/// 期待する形態素（JSONでは`[表層形, 読み, 品詞]`の配列）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenToken(pub String, pub String, pub String);
+
+impl std::fmt::Display for GoldenToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.0, self.1, self.2)
+    }
+}
+
+/// 1文分の期待値
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenCase {
+    pub text: String,
+    pub tokens: Vec<GoldenToken>,
+    pub chunks: Vec<String>,
+}
+
+impl GoldenCase {
+    /// 現在の解析結果から作る（辞書で解析できなければエラー。フォールバックの結果は保存しない）
+    pub fn analyze(analyzer: &MorphAnalyzer, text: &str) -> Result<Self> {
+        let options = ReportOptions { sentences: false, fallback: FallbackPolicy::Error, ..ReportOptions::default() };
+        let report = analyze_report(Some(analyzer), text, &options)?;
+        Ok(Self {
+            text: text.to_string(),
+            tokens: report
+                .tokens
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| GoldenToken(t.surface, t.reading, t.pos))
+                .collect(),
+            chunks: report.chunks.unwrap_or_default().into_iter().map(|c| c.text).collect(),
+        })
+    }
+}
+
+/// 期待値と異なった文
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GoldenMismatch {
+    pub text: String,
+    /// 異なった項目（`tokens`か`chunks`）
+    pub field: &'static str,
+    /// 最初に異なった位置から後ろ（`|`区切り）
+    pub expected: String,
+    pub actual: String,
+}
+
+/// 入力文の一覧を読む（1行1文。空行と`#`で始まる行は除く）
+pub fn read_inputs(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// ゴールデンファイル（JSON Lines）を読む
+pub fn read_golden(text: &str) -> Result<Vec<GoldenCase>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            serde_json::from_str(l)
+                .map_err(|e| ReadMakerError::Validation(format!("ゴールデンファイルの{}行目を読めません: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+/// ゴールデンファイル（JSON Lines）にする
+pub fn write_golden(cases: &[GoldenCase]) -> Result<String> {
+    let mut out = String::new();
+    for case in cases {
+        let line = serde_json::to_string(case)
+            .map_err(|e| ReadMakerError::Internal(format!("ゴールデンファイルのJSON化に失敗しました: {}", e)))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// 入力文を解析して期待値を作り直す
+pub fn bless_golden(analyzer: &MorphAnalyzer, inputs: &[String]) -> Result<Vec<GoldenCase>> {
+    inputs.iter().map(|text| GoldenCase::analyze(analyzer, text)).collect()
+}
+
+/// 期待値の各文を解析し直し、異なった文を返す
+pub fn check_golden(analyzer: &MorphAnalyzer, cases: &[GoldenCase]) -> Result<Vec<GoldenMismatch>> {
+    let mut mismatches = Vec::new();
+    for expected in cases {
+        let actual = GoldenCase::analyze(analyzer, &expected.text)?;
+        let fields = [
+            ("tokens", first_difference(&expected.tokens, &actual.tokens)),
+            ("chunks", first_difference(&expected.chunks, &actual.chunks)),
+        ];
+        for (field, difference) in fields {
+            if let Some((expected_rest, actual_rest)) = difference {
+                mismatches.push(GoldenMismatch {
+                    text: expected.text.clone(),
+                    field,
+                    expected: expected_rest,
+                    actual: actual_rest,
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+/// 2つの並びが最初に異なった位置から後ろを`|`区切りで返す（同じなら`None`）
+fn first_difference<T: PartialEq + std::fmt::Display>(expected: &[T], actual: &[T]) -> Option<(String, String)> {
+    let common = expected.iter().zip(actual).take_while(|(e, a)| e == a).count();
+    if common == expected.len() && common == actual.len() {
+        return None;
+    }
+    let join = |items: &[T]| items.iter().map(ToString::to_string).collect::<Vec<_>>().join(" | ");
+    Some((join(&expected[common..]), join(&actual[common..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::mini_dictionary;
+
+    const INPUTS: &str = "testdata/golden/inputs.txt";
+    const GOLDEN: &str = "testdata/golden/mini-ipadic.jsonl";
+
+    /// `READMAKER_BLESS=1 cargo test golden`で期待値を保存し直す
+    #[test]
+    fn test_golden_mini_ipadic() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let inputs = read_inputs(&std::fs::read_to_string(INPUTS).unwrap());
+        if std::env::var("READMAKER_BLESS").is_ok_and(|v| v == "1") {
+            let cases = bless_golden(&analyzer, &inputs).unwrap();
+            std::fs::write(GOLDEN, write_golden(&cases).unwrap()).unwrap();
+        }
+
+        let cases = read_golden(&std::fs::read_to_string(GOLDEN).unwrap()).unwrap();
+        let texts: Vec<_> = cases.iter().map(|c| c.text.clone()).collect();
+        assert_eq!(texts, inputs, "入力文とゴールデンファイルが一致しません（READMAKER_BLESS=1で保存し直す）");
+        let mismatches = check_golden(&analyzer, &cases).unwrap();
+        let report: Vec<_> = mismatches
+            .iter()
+            .map(|m| format!("{} [{}]\n  期待: {}\n  結果: {}", m.text, m.field, m.expected, m.actual))
+            .collect();
+        assert!(mismatches.is_empty(), "分割が変わりました（意図した変更ならREADMAKER_BLESS=1で保存し直す）\n{}", report.join("\n"));
+    }
+
+    #[test]
+    fn test_detects_changes() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let mut cases = bless_golden(&analyzer, &["吾輩は猫である。".to_string()]).unwrap();
+        assert_eq!(cases[0].tokens[0], GoldenToken("吾輩".into(), "ワガハイ".into(), "名詞".into()));
+        assert!(check_golden(&analyzer, &cases).unwrap().is_empty());
+
+        cases[0].tokens[2].1 = "ビョウ".to_string();
+        cases[0].chunks.pop();
+        let mismatches = check_golden(&analyzer, &cases).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].field, "tokens");
+        assert!(mismatches[0].expected.starts_with("猫/ビョウ/名詞 | "));
+        assert!(mismatches[0].actual.starts_with("猫/ネコ/名詞 | "));
+        assert_eq!((mismatches[1].field, mismatches[1].expected.as_str()), ("chunks", ""));
+    }
+
+    #[test]
+    fn test_read_and_write() {
+        let cases = vec![GoldenCase {
+            text: "猫".to_string(),
+            tokens: vec![GoldenToken("猫".into(), "ネコ".into(), "名詞".into())],
+            chunks: vec!["猫".to_string()],
+        }];
+        let written = write_golden(&cases).unwrap();
+        assert_eq!(written, "{\"text\":\"猫\",\"tokens\":[[\"猫\",\"ネコ\",\"名詞\"]],\"chunks\":[\"猫\"]}\n");
+        assert_eq!(read_golden(&format!("{}\n", written)).unwrap(), cases);
+        assert!(read_golden("{broken").is_err());
+        assert_eq!(read_inputs("# コメント\n\n 猫 \n犬\n"), vec!["猫", "犬"]);
+    }
+}
@@ -0,0 +1,372 @@
+//! Webページの本文抽出
+//! readabilityと同様に、段落の文字数・読点の数・リンク密度・class/idの語から本文らしい要素を選び、
+//! ナビゲーション・広告・「続きを読む」などの定型文を除いた構造化ドキュメントにする
+
+use encoding_rs::{Encoding, UTF_8};
+use crate::document::{Document, Paragraph, SourceFormat};
+use crate::markup::{self, Block, Element, Node};
+
+/// 本文として扱わない要素
+const UNLIKELY_ELEMENTS: &[&str] = &[
+    "nav", "header", "footer", "aside", "form", "iframe", "button", "select", "noscript", "script", "style", "svg",
+    "figure",
+];
+
+/// 本文でなさそうなclass/idに含まれる語
+const NEGATIVE_HINTS: &[&str] = &[
+    "ad-", "ads", "advert", "banner", "breadcrumb", "comment", "footer", "header", "menu", "nav", "pager",
+    "pagination", "popular", "popup", "pr-", "promo", "ranking", "recommend", "related", "share", "sidebar", "sns",
+    "social", "sponsor", "widget",
+];
+
+/// 本文らしいclass/idに含まれる語
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "body", "content", "entry", "honbun", "main", "news", "post", "story", "text",
+];
+
+/// 単独の段落なら捨てる定型文
+const BOILERPLATE: &[&str] = &[
+    "続きを読む", "もっと見る", "記事を読む", "全文を読む", "関連記事", "関連ニュース", "おすすめ記事", "人気記事",
+    "この記事をシェア", "シェアする", "ツイート", "広告", "スポンサーリンク", "PR", "read more", "continue reading",
+    "advertisement", "sponsored",
+];
+
+/// 段落末尾に付いていれば取り除く定型文
+const TRAILING_BOILERPLATE: &[&str] = &["続きを読む", "…もっと見る", "Read more"];
+
+/// この文字数より短い段落は得点に数えない
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// 定型文とみなす段落の最大文字数
+const MAX_BOILERPLATE_CHARS: usize = 40;
+
+/// バイト列のHTMLを本文抽出する（`Content-Type`または`<meta charset>`の文字コードで読む）
+pub fn extract_bytes(bytes: &[u8], content_type: Option<&str>) -> Document {
+    extract(&decode(bytes, content_type))
+}
+
+/// 文字コードを判定してデコードする
+pub fn decode(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(charset_label)
+        .or_else(|| sniff_meta_charset(bytes))
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+fn charset_label(content_type: &str) -> Option<String> {
+    content_type.split(';').find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// 先頭1024バイトから`<meta charset>`/`<meta http-equiv>`を探す
+fn sniff_meta_charset(bytes: &[u8]) -> Option<String> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let pos = head.find("charset=")?;
+    let value = head[pos + "charset=".len()..].trim_start_matches(['"', '\'']);
+    let end = value.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(value.len());
+    (end > 0).then(|| value[..end].to_string())
+}
+
+/// HTMLから本文を抽出する
+pub fn extract(html: &str) -> Document {
+    let root = markup::parse(html);
+    let mut doc = Document::new(SourceFormat::Html);
+    doc.title = title(&root);
+    doc.author = meta_content(&root, "author");
+
+    let body = remove_unlikely(root.find("body").unwrap_or(&root));
+    let content = best_candidate(&body).unwrap_or(&body);
+    let content = remove_link_lists(content);
+
+    for block in markup::blocks(&content) {
+        match block {
+            Block::Heading(p) if doc.title.as_deref() == Some(p.text.as_str()) => {}
+            Block::Heading(p) => doc.push_heading(p.text),
+            Block::Paragraph(p) => {
+                if let Some(p) = strip_boilerplate(p) {
+                    doc.push_paragraph(p);
+                }
+            }
+        }
+    }
+    doc.remove_empty_chapters();
+    doc
+}
+
+fn meta_content(root: &Element, name: &str) -> Option<String> {
+    root.find_all("meta")
+        .into_iter()
+        .find(|m| m.attr("name") == Some(name) || m.attr("property") == Some(name))
+        .and_then(|m| m.attr("content"))
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+}
+
+/// og:title → `<title>`（サイト名の区切り以降は除く）→ 最初の`<h1>`
+fn title(root: &Element) -> Option<String> {
+    meta_content(root, "og:title")
+        .or_else(|| {
+            let title = root.find("title")?.text();
+            let title = [" | ", " - ", "｜", " – "]
+                .iter()
+                .fold(title.as_str(), |t, sep| t.split(sep).next().unwrap_or(t))
+                .trim()
+                .to_string();
+            (!title.is_empty()).then_some(title)
+        })
+        .or_else(|| Some(root.find("h1")?.text().trim().to_string()).filter(|t| !t.is_empty()))
+}
+
+fn class_and_id(element: &Element) -> String {
+    let mut hints = element.attr("class").unwrap_or_default().to_ascii_lowercase();
+    hints.push(' ');
+    hints.push_str(&element.attr("id").unwrap_or_default().to_ascii_lowercase());
+    hints
+}
+
+/// class/idによる加点（本文らしければ+25、そうでなければ-25）
+fn class_weight(element: &Element) -> f64 {
+    let hints = class_and_id(element);
+    let mut weight = 0.0;
+    if NEGATIVE_HINTS.iter().any(|h| hints.contains(h)) {
+        weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|h| hints.contains(h)) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn is_unlikely(element: &Element) -> bool {
+    if UNLIKELY_ELEMENTS.contains(&element.name.as_str()) {
+        return true;
+    }
+    if matches!(element.name.as_str(), "html" | "body" | "article" | "main" | "a") {
+        return false;
+    }
+    let hints = class_and_id(element);
+    NEGATIVE_HINTS.iter().any(|h| hints.contains(h)) && !POSITIVE_HINTS.iter().any(|h| hints.contains(h))
+}
+
+/// 本文になりえない要素を取り除いた複製を作る
+fn remove_unlikely(element: &Element) -> Element {
+    filter_tree(element, &|e| !is_unlikely(e))
+}
+
+/// リンクばかりの一覧（関連記事・タグ一覧など）を取り除いた複製を作る
+fn remove_link_lists(element: &Element) -> Element {
+    filter_tree(element, &|e| {
+        !matches!(e.name.as_str(), "ul" | "ol" | "div" | "table" | "section" | "p" | "li") || link_density(e) <= 0.5
+    })
+}
+
+fn filter_tree(element: &Element, keep: &impl Fn(&Element) -> bool) -> Element {
+    let children = element
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Node::Element(e) if !keep(e) => None,
+            Node::Element(e) => Some(Node::Element(filter_tree(e, keep))),
+            Node::Text(t) => Some(Node::Text(t.clone())),
+        })
+        .collect();
+    Element { name: element.name.clone(), attrs: element.attrs.clone(), children }
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// テキストのうちリンク内の文字の割合
+fn link_density(element: &Element) -> f64 {
+    let total = char_len(&element.text());
+    if total == 0 {
+        return 0.0;
+    }
+    let links: usize = element.find_all("a").iter().map(|a| char_len(&a.text())).sum();
+    links as f64 / total as f64
+}
+
+/// 段落として得点を数える要素か
+fn is_paragraph(element: &Element) -> bool {
+    match element.name.as_str() {
+        "p" | "pre" | "td" | "blockquote" => true,
+        // <br>区切りの本文を直接持つ<div>も段落とみなす
+        "div" => !element.elements().any(|e| matches!(e.name.as_str(), "div" | "p" | "section" | "article" | "table" | "ul" | "ol")),
+        _ => false,
+    }
+}
+
+/// 段落そのものの得点（文字数と読点の数）
+fn paragraph_score(element: &Element) -> f64 {
+    let text = element.text();
+    let len = char_len(&text);
+    if len < MIN_PARAGRAPH_CHARS {
+        return 0.0;
+    }
+    let commas = text.chars().filter(|c| matches!(c, '、' | '，' | ',' | '。')).count();
+    1.0 + commas as f64 + (len as f64 / 100.0).min(3.0)
+}
+
+/// 要素の種類による初期点
+fn tag_score(element: &Element) -> f64 {
+    match element.name.as_str() {
+        "article" => 10.0,
+        "div" | "main" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+/// 本文候補としての得点（子の段落は満点、孫の段落は半分を加える）
+fn candidate_score(element: &Element) -> f64 {
+    let mut paragraphs = 0.0;
+    for child in element.elements() {
+        if is_paragraph(child) {
+            paragraphs += paragraph_score(child);
+        }
+        for grandchild in child.elements() {
+            if is_paragraph(grandchild) {
+                paragraphs += paragraph_score(grandchild) / 2.0;
+            }
+        }
+    }
+    if paragraphs == 0.0 {
+        return 0.0;
+    }
+    (tag_score(element) + class_weight(element) + paragraphs) * (1.0 - link_density(element))
+}
+
+fn best_candidate<'a>(body: &'a Element) -> Option<&'a Element> {
+    let mut best: Option<(&'a Element, f64)> = None;
+    let mut consider = |e: &'a Element| {
+        let score = candidate_score(e);
+        if score > 0.0 && best.is_none_or(|(_, s)| score > s) {
+            best = Some((e, score));
+        }
+    };
+    consider(body);
+    body.walk(&mut consider);
+    best.map(|(e, _)| e)
+}
+
+/// 定型文だけの段落を捨て、段落末尾の「続きを読む」などを取り除く
+fn strip_boilerplate(mut paragraph: Paragraph) -> Option<Paragraph> {
+    let normalized: String = paragraph
+        .text
+        .trim_matches(|c: char| c.is_whitespace() || "【】[]［］<>＜＞≫»›→▶︎▼・…".contains(c))
+        .to_lowercase();
+    let len = normalized.chars().count();
+    if len == 0
+        || (len <= MAX_BOILERPLATE_CHARS && BOILERPLATE.iter().any(|b| normalized.starts_with(&b.to_lowercase())))
+        || normalized.contains("all rights reserved")
+        || normalized.starts_with('©')
+        || normalized.starts_with("copyright")
+    {
+        return None;
+    }
+
+    for suffix in TRAILING_BOILERPLATE {
+        if let Some(stripped) = paragraph.text.strip_suffix(suffix) {
+            let stripped = stripped.trim_end_matches(['…', '.', ' ', '（', '(']);
+            let len = stripped.chars().count();
+            paragraph.text = stripped.to_string();
+            paragraph.ruby.retain(|r| r.end <= len);
+            break;
+        }
+    }
+    Some(paragraph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<!DOCTYPE html>
+<html><head>
+<meta charset="utf-8">
+<title>新しい図書館が開館 | サンプルニュース</title>
+<meta name="author" content="山田太郎">
+</head>
+<body>
+<header><a href="/">サンプルニュース</a></header>
+<nav><ul><li><a href="/a">政治</a></li><li><a href="/b">経済</a></li><li><a href="/c">国際</a></li></ul></nav>
+<div id="main">
+  <article class="article-body">
+    <h1>新しい図書館が開館</h1>
+    <p>市の中心部に、新しい図書館が今月開館しました。蔵書はおよそ三十万冊で、市内で最大の規模です。</p>
+    <div class="ad-banner">広告：今なら初月無料、お得なキャンペーン実施中です。詳しくはこちらをご覧ください。</div>
+    <p>館内には、子ども向けの読み聞かせスペースや、静かに作業できる個室も用意されています。</p>
+    <p>館長は「多くの市民に、本を読む楽しさを知ってほしい」と話しています。…続きを読む</p>
+    <p><a href="/more">続きを読む</a></p>
+    <ul class="tags"><li><a href="/t/1">図書館</a></li><li><a href="/t/2">文化</a></li></ul>
+  </article>
+  <aside class="sidebar"><h2>人気記事</h2><p><a href="/x">別の記事の見出しがここに入ります、かなり長いタイトルです</a></p></aside>
+</div>
+<div class="related"><p><a href="/r">関連記事：読書週間が始まりました、各地でイベントが開かれています</a></p></div>
+<footer><p>Copyright © Sample News. All Rights Reserved.</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn test_extract_article_body() {
+        let doc = extract(ARTICLE);
+        assert_eq!(doc.format, SourceFormat::Html);
+        assert_eq!(doc.title.as_deref(), Some("新しい図書館が開館"));
+        assert_eq!(doc.author.as_deref(), Some("山田太郎"));
+
+        let texts: Vec<_> = doc.paragraphs().map(|p| p.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "市の中心部に、新しい図書館が今月開館しました。蔵書はおよそ三十万冊で、市内で最大の規模です。",
+                "館内には、子ども向けの読み聞かせスペースや、静かに作業できる個室も用意されています。",
+                "館長は「多くの市民に、本を読む楽しさを知ってほしい」と話しています。",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prefers_dense_text_over_link_lists() {
+        let html = r#"<body>
+<div class="list"><p><a href="/1">リンクだけの段落です、とても長いタイトルが続きます。本当に長いです。</a></p>
+<p><a href="/2">リンクだけの段落です、とても長いタイトルが続きます。本当に長いです。</a></p></div>
+<div><p>本文の段落です。読点が、いくつか、含まれていて、それなりの長さがあります。</p>
+<p>二つ目の段落です。こちらも、十分な長さの文章になっています。</p></div>
+</body>"#;
+        let doc = extract(html);
+        assert_eq!(doc.paragraphs().count(), 2);
+        assert!(doc.plain_text().starts_with("本文の段落です。"));
+    }
+
+    #[test]
+    fn test_deeply_nested_markup() {
+        // 入れ子の深さだけ再帰するとスタックが溢れる
+        let depth = 200_000;
+        let html = format!(
+            "<body><p>{}深い入れ子の中の本文です、読点もあります。{}</p></body>",
+            "<b>".repeat(depth),
+            "</b>".repeat(depth)
+        );
+        assert!(extract(&html).plain_text().contains("深い入れ子の中の本文です"));
+    }
+
+    #[test]
+    fn test_decode_charset() {
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode("<html><body><p>日本語の本文</p></body></html>");
+        assert!(decode(&bytes, Some("text/html; charset=Shift_JIS")).contains("日本語の本文"));
+
+        let mut html = b"<meta charset=\"shift_jis\">".to_vec();
+        html.extend_from_slice(&bytes);
+        assert!(decode(&html, None).contains("日本語の本文"));
+    }
+}
@@ -0,0 +1,293 @@
+//! 禁則処理つきの組版
+//! テキストを1行N文字・1ページM行に折り返し、ページと行の文字オフセットを返す（しおりの位置に使う）
+
+use serde::{Deserialize, Serialize};
+
+/// 行頭に置かない文字（閉じ括弧・句読点・小書きの仮名など）
+const NOT_LINE_START: &str = "、。，．,.・：；:;？！?!）」』】〕〉》］｝)]}〙〗｣ゝゞヽヾーぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶㇰㇱㇲㇳㇴㇵㇶㇷㇸㇹㇺㇻㇼㇽㇾㇿ々〻‐゠–〜～‥…";
+
+/// 行末に置かない文字（開き括弧）
+const NOT_LINE_END: &str = "「『（【〔〈《［｛([{〘〖｢";
+
+/// ぶら下げてよい文字（句読点）
+const HANGING: &str = "、。，．,.";
+
+/// 組方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    /// 横書き
+    #[default]
+    Horizontal,
+    /// 縦書き
+    Vertical,
+}
+
+/// 組版の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutOptions {
+    /// 1行の文字数（全角換算）
+    pub chars_per_line: usize,
+    /// 1ページの行数
+    pub lines_per_page: usize,
+    pub orientation: Orientation,
+    /// 句読点を行末からはみ出させる（ぶら下げ）。無効なら前の文字ごと次の行へ送る
+    pub hanging_punctuation: bool,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            chars_per_line: 20,
+            lines_per_page: 12,
+            orientation: Orientation::Horizontal,
+            hanging_punctuation: true,
+        }
+    }
+}
+
+/// 1行（縦書きでは1列）。オフセットは元テキスト上の文字単位で、`end`は含まない
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Line {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 1ページ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page {
+    pub index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub lines: Vec<Line>,
+}
+
+/// 組版結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    pub orientation: Orientation,
+    pub pages: Vec<Page>,
+}
+
+impl Layout {
+    /// 文字オフセットを含むページ番号と行番号（しおりの復元用）
+    pub fn locate(&self, offset: usize) -> Option<(usize, usize)> {
+        let page = self.pages.iter().position(|p| offset < p.end).or_else(|| self.pages.len().checked_sub(1))?;
+        let lines = &self.pages[page].lines;
+        let line = lines.iter().position(|l| offset < l.end).unwrap_or(lines.len().saturating_sub(1));
+        Some((page, line))
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.pages.iter().flat_map(|p| p.lines.iter())
+    }
+}
+
+/// 組版上の1文字分のまとまり（濁点などの結合文字や縦中横を含む）
+struct Cell {
+    text: String,
+    start: usize,
+    end: usize,
+    /// 半角を1とした幅
+    width: usize,
+}
+
+impl Cell {
+    fn first(&self) -> char {
+        self.text.chars().next().unwrap_or(' ')
+    }
+
+    fn last(&self) -> char {
+        self.text.chars().next_back().unwrap_or(' ')
+    }
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{3099}' | '\u{309A}' | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}' | '\u{0300}'..='\u{036F}')
+}
+
+/// 段落をセルに分ける
+///
+/// 横書きでは半角文字を半分の幅とし、縦書きでは2桁までの半角数字を縦中横として1文字分にまとめる
+fn cells(chars: &[char], offset: usize, orientation: Orientation) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if is_combining(c) {
+            if let Some(last) = cells.last_mut() {
+                last.text.push(c);
+                last.end = offset + i + 1;
+                i += 1;
+                continue;
+            }
+        }
+        if orientation == Orientation::Vertical && c.is_ascii_digit() {
+            let run = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if run <= 2 {
+                cells.push(Cell {
+                    text: chars[i..i + run].iter().collect(),
+                    start: offset + i,
+                    end: offset + i + run,
+                    width: 2,
+                });
+                i += run;
+                continue;
+            }
+        }
+        let width = if orientation == Orientation::Horizontal && (c.is_ascii() || ('\u{FF61}'..='\u{FF9F}').contains(&c)) {
+            1
+        } else {
+            2
+        };
+        cells.push(Cell { text: c.to_string(), start: offset + i, end: offset + i + 1, width });
+        i += 1;
+    }
+    cells
+}
+
+/// `k`番目のセルの前で改行してよいか
+fn can_break_before(cells: &[Cell], k: usize) -> bool {
+    !NOT_LINE_START.contains(cells[k].first()) && !NOT_LINE_END.contains(cells[k - 1].last())
+}
+
+/// 1段落を行に分ける
+fn break_lines(cells: &[Cell], max_width: usize, hanging: bool, lines: &mut Vec<Line>) {
+    let mut i = 0;
+    while i < cells.len() {
+        let mut width = 0;
+        let mut j = i;
+        while j < cells.len() && (j == i || width + cells[j].width <= max_width) {
+            width += cells[j].width;
+            j += 1;
+        }
+
+        if j < cells.len() && !can_break_before(cells, j) {
+            let hangs = hanging
+                && HANGING.contains(cells[j].first())
+                && (j + 1 == cells.len() || can_break_before(cells, j + 1));
+            if hangs {
+                j += 1;
+            } else if let Some(k) = (i + 1..j).rev().find(|&k| can_break_before(cells, k)) {
+                // 追い出し: 改行できる位置まで戻る
+                j = k;
+            }
+            // 改行できる位置がなければ禁則を破って折り返す
+        }
+
+        lines.push(Line {
+            text: cells[i..j].iter().map(|c| c.text.as_str()).collect(),
+            start: cells[i].start,
+            end: cells[j - 1].end,
+        });
+        i = j;
+    }
+}
+
+/// テキストを組版する（改行は段落の区切りとして扱い、空行も1行として残す）
+pub fn layout(text: &str, options: &LayoutOptions) -> Layout {
+    let max_width = options.chars_per_line.max(1) * 2;
+    let lines_per_page = options.lines_per_page.max(1);
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for paragraph in chars.split(|&c| c == '\n') {
+        let cells = cells(paragraph, start, options.orientation);
+        if cells.is_empty() {
+            lines.push(Line { text: String::new(), start, end: start });
+        } else {
+            break_lines(&cells, max_width, options.hanging_punctuation, &mut lines);
+        }
+        start += paragraph.len() + 1;
+    }
+    // 末尾の改行による空行は組まない
+    if text.ends_with('\n') {
+        lines.pop();
+    }
+
+    let pages = lines
+        .chunks(lines_per_page)
+        .enumerate()
+        .map(|(index, lines)| Page {
+            index,
+            start: lines[0].start,
+            end: lines[lines.len() - 1].end,
+            lines: lines.to_vec(),
+        })
+        .collect();
+    Layout { orientation: options.orientation, pages }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(chars_per_line: usize, lines_per_page: usize) -> LayoutOptions {
+        LayoutOptions { chars_per_line, lines_per_page, ..LayoutOptions::default() }
+    }
+
+    fn line_texts(laid: &Layout) -> Vec<&str> {
+        laid.lines().map(|l| l.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_wrap_and_paginate() {
+        let laid = layout("あいうえおかきくけこさしすせそ", &options(5, 2));
+        assert_eq!(line_texts(&laid), vec!["あいうえお", "かきくけこ", "さしすせそ"]);
+        assert_eq!(laid.pages.len(), 2);
+        assert_eq!((laid.pages[1].start, laid.pages[1].end), (10, 15));
+        assert_eq!(laid.locate(7), Some((0, 1)));
+        assert_eq!(laid.locate(12), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_hanging_punctuation() {
+        let laid = layout("吾輩は猫である。名前はまだ無い。", &options(7, 10));
+        assert_eq!(line_texts(&laid), vec!["吾輩は猫である。", "名前はまだ無い。"]);
+    }
+
+    #[test]
+    fn test_push_out_when_not_hanging() {
+        let opts = LayoutOptions { hanging_punctuation: false, ..options(7, 10) };
+        let laid = layout("吾輩は猫である。名前", &opts);
+        assert_eq!(line_texts(&laid), vec!["吾輩は猫であ", "る。名前"]);
+    }
+
+    #[test]
+    fn test_line_start_and_end_rules() {
+        // 閉じ括弧と小書き仮名は行頭に来ない
+        let laid = layout("彼は「はい」と言ったしゃしん", &options(5, 10));
+        for line in laid.lines().skip(1) {
+            let first = line.text.chars().next().unwrap();
+            assert!(!NOT_LINE_START.contains(first), "行頭禁則: {:?}", line.text);
+        }
+        // 開き括弧は行末に来ない
+        let laid = layout("あいうえ「かき」", &options(5, 10));
+        assert_eq!(line_texts(&laid), vec!["あいうえ", "「かき」"]);
+        // 句点の後の閉じ括弧はぶら下げず追い出す
+        let laid = layout("あいうえお。」か", &options(5, 10));
+        assert_eq!(line_texts(&laid), vec!["あいうえ", "お。」か"]);
+    }
+
+    #[test]
+    fn test_paragraph_offsets() {
+        let laid = layout("一行目\n\n三行目です", &options(3, 10));
+        let lines: Vec<_> = laid.lines().map(|l| (l.text.as_str(), l.start, l.end)).collect();
+        assert_eq!(lines, vec![("一行目", 0, 3), ("", 4, 4), ("三行目", 5, 8), ("です", 8, 10)]);
+    }
+
+    #[test]
+    fn test_vertical_tate_chu_yoko() {
+        let vertical = LayoutOptions { orientation: Orientation::Vertical, ..options(4, 10) };
+        let laid = layout("第12回の会", &vertical);
+        assert_eq!(laid.orientation, Orientation::Vertical);
+        assert_eq!(line_texts(&laid), vec!["第12回の", "会"]);
+
+        // 横書きでは半角は半分の幅
+        let laid = layout("ABCDEFGHあ", &options(4, 10));
+        assert_eq!(line_texts(&laid), vec!["ABCDEFGH", "あ"]);
+    }
+}
@@ -1,78 +1,148 @@
-/// ReadMaker Core - 形態素解析エンジン
-/// Vibratoベースの日本語形態素解析ライブラリ
+//! ReadMaker Core - 形態素解析エンジン
+//! Vibratoベースの日本語形態素解析ライブラリ
 
 use std::fs::File;
+use std::io::{self, BufReader, Read};
 use std::env;
-use serde_json;
-use vibrato::{Dictionary, Tokenizer};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use vibrato::Dictionary;
 use readmaker_shared::{WordAnalysis, Result, ReadMakerError};
 
+pub mod annotated;
+pub mod aozora;
+pub mod backend;
+pub mod bench;
+pub mod cache;
+pub mod compile;
+pub mod difficulty;
+pub mod document;
+pub mod epub;
+pub mod eval;
+pub mod fallback;
+pub mod furigana;
+pub mod golden;
+pub mod html;
+pub mod layout;
+pub mod markup;
+pub mod mixed;
+pub mod report;
+pub mod rsvp;
+pub mod segmenter;
+#[cfg(feature = "train")]
+pub mod train;
+pub mod trim;
+
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
+#[cfg(test)]
+mod testutil;
+
+pub use backend::{RuleBasedBackend, TokenizerBackend, VibratoBackend};
+#[cfg(feature = "lindera")]
+pub use backend::LinderaBackend;
+pub use cache::{CacheKey, CacheMetrics, ReportCache};
+pub use compile::{write_dictionary, DictionarySource, SourceEncoding};
+pub use difficulty::{estimate_difficulty, Difficulty, DifficultyLevel};
+pub use document::{Chapter, Document, Paragraph, Ruby, SourceFormat};
+pub use eval::{evaluate, EvalOptions, Evaluation, SegmentationScore};
+pub use fallback::{Engine, FallbackPolicy};
+pub use furigana::{furigana, ruby_segments, RubySegment};
+pub use golden::{bless_golden, check_golden, GoldenCase, GoldenMismatch};
+pub use layout::{Layout, LayoutOptions, Orientation};
+pub use mixed::{text_stats, TextStats};
+pub use report::{
+    analyze_report, analyze_report_with_progress, Report, ReportOptions, Token, MSGPACK_CONTENT_TYPE, REPORT_SCHEMA_VERSION,
+};
+pub use rsvp::{rsvp_schedule, rsvp_total_ms, RsvpFrame, RsvpOptions};
+
+/// zstdフレームの先頭4バイト
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// 英単語に付与する品詞情報（IPADICの素性形式）
+const LATIN_WORD_FEATURE: &str = "名詞,一般,*,*,*,*,*";
+
+/// 形態素解析器（分割は差し替え可能なバックエンドに任せる。既定はVibrato）
 pub struct MorphAnalyzer {
-    tokenizer: Tokenizer,
+    backend: Box<dyn TokenizerBackend>,
+}
+
+/// 解析結果（どのエンジンが生成したかを必ず保持する）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Analysis {
+    pub engine: Engine,
+    pub words: Vec<WordAnalysis>,
+}
+
+impl Analysis {
+    /// 表層形のみを取り出す
+    pub fn surfaces(&self) -> Vec<String> {
+        self.words.iter().map(|w| w.surface.clone()).collect()
+    }
 }
 
 impl MorphAnalyzer {
     /// 新しいアナライザーインスタンスを作成
     pub fn new() -> Result<Self> {
-        let dict = Self::load_dictionary()?;
-        let tokenizer = Tokenizer::new(dict);
-        
-        Ok(Self { tokenizer })
-    }
-    
-    /// 辞書ファイルの読み込み
-    fn load_dictionary() -> Result<Dictionary> {
         let dict_path = env::var("READMAKER_DIC_PATH")
             .unwrap_or_else(|_| "dictionaries/ipadic.vibrato".to_string());
-        
-        let dict_file = File::open(&dict_path)
-            .map_err(|e| ReadMakerError::Analysis(format!("辞書ファイルが見つかりません: {}", e)))?;
-        
-        // zstd圧縮/非圧縮の両対応
-        match zstd::stream::read::Decoder::new(dict_file) {
-            Ok(mut decoder) => {
-                Dictionary::read(&mut decoder)
-                    .or_else(|_| {
-                        // 失敗したら非圧縮として再試行
-                        let raw_file = File::open(&dict_path)
-                            .map_err(|e| ReadMakerError::Analysis(format!("辞書読み込みエラー: {}", e)))?;
-                        Dictionary::read(raw_file)
-                            .map_err(|e| ReadMakerError::Analysis(format!("辞書読み込みエラー: {}", e)))
-                    })
-            }
-            Err(_) => {
-                // zstdとして開けない場合は非圧縮として読む
-                let raw_file = File::open(&dict_path)
-                    .map_err(|e| ReadMakerError::Analysis(format!("辞書読み込みエラー: {}", e)))?;
-                Dictionary::read(raw_file)
-                    .map_err(|e| ReadMakerError::Analysis(format!("辞書読み込みエラー: {}", e)))
-            }
-        }
+        Self::from_path(dict_path)
+    }
+
+    /// 読み込み済みの辞書からアナライザーを作成（辞書のバージョンは書き出した辞書から計算する）
+    pub fn from_dictionary(dict: Dictionary) -> Self {
+        Self::with_backend(VibratoBackend::from_dictionary(dict))
+    }
+
+    /// メモリ上の辞書データからアナライザーを作成（zstd圧縮/非圧縮の両対応）
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Ok(Self::with_backend(VibratoBackend::from_bytes(data)?))
+    }
+
+    /// 辞書ファイルからアナライザーを作成（ファイルをメモリマップして読む）
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let mmap = map_dictionary_file(path.as_ref())?;
+        Self::from_bytes(&mmap)
+    }
+
+    /// 任意のバックエンドでアナライザーを作成
+    pub fn with_backend(backend: impl TokenizerBackend + 'static) -> Self {
+        Self { backend: Box::new(backend) }
+    }
+
+    pub fn backend(&self) -> &dyn TokenizerBackend {
+        self.backend.as_ref()
+    }
+
+    /// 解析結果に記録するエンジン
+    pub fn engine(&self) -> Engine {
+        self.backend.engine()
     }
-    
+
+    /// 辞書のバージョン（Vibratoでは非圧縮の辞書データのSHA-256の先頭16桁、[`dictionary_digest`]と同じ値）
+    ///
+    /// zstd圧縮の有無や読み込み方によらず同じ辞書なら同じ値になる。解析結果のキャッシュで辞書の入れ替えを区別するのに使う
+    pub fn dictionary_version(&self) -> &str {
+        self.backend.version()
+    }
+
+    /// 読み込み済みの辞書（辞書を使わないバックエンドでは`None`）
+    pub fn dictionary(&self) -> Option<&Dictionary> {
+        self.backend.dictionary()
+    }
+
     /// テキストを形態素解析
     pub fn analyze_text(&self, input: &str) -> Result<Vec<WordAnalysis>> {
-        let mut worker = self.tokenizer.new_worker();
-        worker.reset_sentence(input);
-        worker.tokenize();
-        
-        let mut words = Vec::new();
-        for i in 0..worker.num_tokens() {
-            let token = worker.token(i);
-            words.push(WordAnalysis {
-                surface: token.surface().to_string(),
-                reading: token.surface().to_string(), // Vibratoでは読みが直接取得できない場合は表面形を使用
-                part_of_speech: token.feature().to_string(),
-                features: vec![token.feature().to_string()], // 単一の特徴文字列をベクターに格納
-            });
-        }
-        
-        Ok(words)
+        self.backend.analyze_words(input)
     }
-    
+
+    /// 読み・品詞・原形・文字オフセット付きで形態素解析
+    pub fn tokenize(&self, input: &str) -> Result<Vec<Token>> {
+        self.backend.tokenize(input)
+    }
+
     /// 簡易形態素解析（単語のみ）
     pub fn analyze_words(&self, input: &str) -> Result<Vec<String>> {
         let analysis = self.analyze_text(input)?;
@@ -80,36 +150,157 @@ impl MorphAnalyzer {
     }
 }
 
-/// フォールバック形態素解析（辞書読み込み失敗時）
-pub fn analyze_text_fallback(text: &str) -> Vec<String> {
-    text.chars()
-        .map(|c| c.to_string())
-        .collect()
+/// データがzstdフレームで始まるか
+pub fn is_zstd_compressed(data: &[u8]) -> bool {
+    data.starts_with(&ZSTD_MAGIC)
 }
 
-/// 便利関数: テキストをJSON形式で分析
-pub fn analyze_to_json(input: &str) -> String {
-    match MorphAnalyzer::new() {
-        Ok(analyzer) => {
-            match analyzer.analyze_text(input) {
-                Ok(words) => serde_json::to_string(&words).unwrap_or_else(|_| "[]".to_string()),
-                Err(_) => {
-                    let fallback = analyze_text_fallback(input);
-                    serde_json::to_string(&fallback).unwrap_or_else(|_| "[]".to_string())
-                }
-            }
-        }
-        Err(_) => {
-            let fallback = analyze_text_fallback(input);
-            serde_json::to_string(&fallback).unwrap_or_else(|_| "[]".to_string())
+/// メモリ上の辞書データを読み込む（先頭がzstdフレームなら解凍する）
+pub fn read_dictionary(data: &[u8]) -> Result<Dictionary> {
+    let dict = if is_zstd_compressed(data) {
+        #[cfg(feature = "zstd")]
+        {
+            let mut decoder = zstd_decoder(data)?;
+            Dictionary::read(&mut decoder)
         }
+        #[cfg(not(feature = "zstd"))]
+        return Err(zstd_unsupported());
+    } else {
+        Dictionary::read(data)
+    };
+    dict.map_err(dictionary_read_error)
+}
+
+/// 辞書を読み込み、同時に[`dictionary_digest`]と同じバージョンを計算する（データを読み直さない）
+pub(crate) fn read_dictionary_with_digest(data: &[u8]) -> Result<(Dictionary, String)> {
+    let mut reader = BufReader::new(DigestReader { inner: decoded_reader(data)?, hasher: Sha256::new() });
+    let dict = Dictionary::read(&mut reader).map_err(dictionary_read_error)?;
+    // 辞書の後ろに余分なデータがあっても`dictionary_digest`と同じ値にする
+    io::copy(&mut reader, &mut io::sink()).map_err(|e| ReadMakerError::Analysis(format!("辞書の解凍エラー: {}", e)))?;
+    Ok((dict, hex_prefix(&reader.into_inner().hasher.finalize())))
+}
+
+/// 読んだバイト列のハッシュを計算しながら読む
+struct DigestReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// 辞書データの非圧縮の内容を読む（先頭がzstdフレームなら解凍する）
+fn decoded_reader(data: &[u8]) -> Result<Box<dyn Read + '_>> {
+    if is_zstd_compressed(data) {
+        #[cfg(feature = "zstd")]
+        return Ok(Box::new(zstd_decoder(data)?));
+        #[cfg(not(feature = "zstd"))]
+        return Err(zstd_unsupported());
     }
+    Ok(Box::new(data))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder(data: &[u8]) -> Result<zstd::stream::read::Decoder<'static, BufReader<&[u8]>>> {
+    zstd::stream::read::Decoder::new(data).map_err(|e| ReadMakerError::Analysis(format!("辞書の解凍エラー: {}", e)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> ReadMakerError {
+    ReadMakerError::Analysis("zstd圧縮された辞書は解凍してから渡してください".to_string())
+}
+
+fn dictionary_read_error(e: vibrato::errors::VibratoError) -> ReadMakerError {
+    ReadMakerError::Analysis(format!("辞書読み込みエラー: {}", e))
+}
+
+/// 辞書ファイルをメモリマップして読み込む（zstd圧縮/非圧縮の両対応）
+///
+/// ファイル全体をヒープへコピーしないため、数十MBの辞書でも読み込み時のピークメモリを抑えられる
+pub fn read_dictionary_file(path: impl AsRef<Path>) -> Result<Dictionary> {
+    read_dictionary(&map_dictionary_file(path.as_ref())?)
+}
+
+fn map_dictionary_file(path: &Path) -> Result<memmap2::Mmap> {
+    let file = File::open(path)
+        .map_err(|e| ReadMakerError::Analysis(format!("辞書ファイルが見つかりません: {}: {}", path.display(), e)))?;
+    // 読み込み中に辞書ファイルが書き換えられないことを前提とする
+    unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|e| ReadMakerError::Analysis(format!("辞書ファイルを開けません: {}: {}", path.display(), e)))
+}
+
+/// 辞書データのハッシュ（`MorphAnalyzer::dictionary_version`の値）
+///
+/// 非圧縮の辞書データのSHA-256の先頭16桁。zstd圧縮されていれば解凍した内容から計算する
+pub fn dictionary_digest(data: &[u8]) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut decoded_reader(data)?, &mut hasher)
+        .map_err(|e| ReadMakerError::Analysis(format!("辞書の解凍エラー: {}", e)))?;
+    Ok(hex_prefix(&hasher.finalize()))
+}
+
+pub(crate) fn hex_prefix(digest: &[u8]) -> String {
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// フォールバック分割の結果を解析結果の形に揃える
+pub(crate) fn fallback_analysis(input: &str, policy: FallbackPolicy, cause: ReadMakerError) -> Result<Analysis> {
+    let (engine, parts) = policy.segment(input).ok_or(cause)?;
+    let words = parts
+        .into_iter()
+        .map(|surface| WordAnalysis {
+            reading: surface.clone(),
+            surface,
+            part_of_speech: "*".to_string(),
+            features: Vec::new(),
+        })
+        .collect();
+    Ok(Analysis { engine, words })
+}
+
+/// 辞書を読み込んで解析し、失敗時は`policy`に従ってフォールバック
+pub fn analyze(input: &str, policy: FallbackPolicy) -> Result<Analysis> {
+    match MorphAnalyzer::new() {
+        Ok(analyzer) => analyze_with(Some(&analyzer), input, policy),
+        Err(e) => fallback_analysis(input, policy, e),
+    }
+}
+
+/// 既存のアナライザー（未読み込みなら`None`）で解析し、失敗時は`policy`に従ってフォールバック
+pub fn analyze_with(analyzer: Option<&MorphAnalyzer>, input: &str, policy: FallbackPolicy) -> Result<Analysis> {
+    let Some(analyzer) = analyzer else {
+        let cause = ReadMakerError::Analysis("辞書が読み込まれていません".to_string());
+        return fallback_analysis(input, policy, cause);
+    };
+    match analyzer.analyze_text(input) {
+        Ok(words) => Ok(Analysis { engine: analyzer.engine(), words }),
+        Err(e) => fallback_analysis(input, policy, e),
+    }
+}
+
+/// 構造化ドキュメントを段落ごとに解析
+pub fn analyze_document(analyzer: Option<&MorphAnalyzer>, doc: &Document, policy: FallbackPolicy) -> Result<Vec<Analysis>> {
+    doc.paragraphs()
+        .map(|p| analyze_with(analyzer, &p.text, policy))
+        .collect()
+}
+
+/// 便利関数: テキストをJSON形式で分析
+pub fn analyze_to_json(input: &str, policy: FallbackPolicy) -> Result<String> {
+    let analysis = analyze(input, policy)?;
+    serde_json::to_string(&analysis)
+        .map_err(|e| ReadMakerError::Internal(format!("JSON変換エラー: {}", e)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_analyzer_creation() {
         // 辞書ファイルが存在しない場合はスキップ
@@ -117,15 +308,59 @@ mod tests {
             println!("辞書ファイルが見つからないためテストをスキップ");
             return;
         }
-        
+
         let analyzer = MorphAnalyzer::new();
         assert!(analyzer.is_ok());
     }
-    
+
+    #[test]
+    fn test_vibrato_analysis() {
+        let analyzer = MorphAnalyzer::from_dictionary(testutil::mini_dictionary());
+        let analysis = analyze_with(Some(&analyzer), "今日は良い天気です。", FallbackPolicy::Error).unwrap();
+        assert_eq!(analysis.engine, Engine::Vibrato);
+        assert_eq!(analysis.surfaces(), vec!["今日", "は", "良い", "天気", "です", "。"]);
+    }
+
+    #[test]
+    fn test_latin_words_bypass_unknown_handler() {
+        let analyzer = MorphAnalyzer::from_dictionary(testutil::mini_dictionary());
+        let words = analyzer.analyze_words("React Nativeでアプリを作る").unwrap();
+        assert_eq!(words, vec!["React", "Native", "で", "アプリ", "を", "作る"]);
+    }
+
+    #[test]
+    fn test_analyze_document() {
+        let doc = aozora::parse("題\n著者\n\n吾輩《わがはい》は猫である。\n今日は良い天気です。\n");
+        let analyzer = MorphAnalyzer::from_dictionary(testutil::mini_dictionary());
+        let analyses = analyze_document(Some(&analyzer), &doc, FallbackPolicy::Error).unwrap();
+        assert_eq!(analyses.len(), 2);
+        assert_eq!(analyses[0].surfaces(), vec!["吾輩", "は", "猫", "で", "ある", "。"]);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_read_dictionary_raw_and_zstd() {
+        let mut raw = Vec::new();
+        testutil::mini_dictionary().write(&mut raw).unwrap();
+        let compressed = zstd::encode_all(raw.as_slice(), 3).unwrap();
+
+        for data in [&raw, &compressed] {
+            let analyzer = MorphAnalyzer::from_bytes(data).unwrap();
+            assert_eq!(analyzer.analyze_words("吾輩は猫").unwrap(), vec!["吾輩", "は", "猫"]);
+        }
+        assert!(read_dictionary(b"broken").is_err());
+        assert!(read_dictionary_file("testdata/no-such-dictionary.vibrato").is_err());
+    }
+
     #[test]
     fn test_fallback_analysis() {
-        let result = analyze_text_fallback("今日は良い天気です");
-        assert!(!result.is_empty());
-        assert!(result.contains(&"今".to_string()));
+        let cause = || ReadMakerError::Analysis("辞書なし".to_string());
+
+        let result = fallback_analysis("今日は良い天気です", FallbackPolicy::CharClass, cause()).unwrap();
+        assert_eq!(result.engine, Engine::CharClass);
+        assert!(result.surfaces().contains(&"今日は".to_string()));
+
+        let result = fallback_analysis("今日は良い天気です", FallbackPolicy::Error, cause());
+        assert!(matches!(result, Err(ReadMakerError::Analysis(_))));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,504 @@
+//! 寛容なHTML/XMLパーサー
+//! EPUBのXHTML・OPFや、Webページのような多少壊れたHTMLを簡易的な要素ツリーにし、段落とルビを取り出す
+
+use crate::document::{Paragraph, ParagraphBuilder};
+use crate::segmenter::{script_of, Script};
+
+/// 子要素を持たない要素
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// 中身をテキストとして読み飛ばす要素
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// 段落の区切りになる要素
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "br", "dd", "div", "dl", "dt", "figcaption", "figure",
+    "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "nav", "ol", "p", "pre", "section",
+    "table", "td", "th", "tr", "ul",
+];
+
+/// 要素の入れ子の最大の深さ（ルートの`#document`を含む）
+///
+/// ツリーをたどる処理（とツリーの破棄）は入れ子1段ごとに再帰するため、これより深い要素は子を持たせずに平らにする
+const MAX_DEPTH: usize = 512;
+
+/// 本文として扱わない要素
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "noscript", "template", "svg", "math", "rp"];
+
+/// ノード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// 要素（名前・属性名は名前空間接頭辞を除いた小文字）
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), ..Self::default() }
+    }
+
+    /// 属性値
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// 直下の子要素
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// 子孫要素から名前で最初の1つを探す（深さ優先）
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.elements().find_map(|e| if e.name == name { Some(e) } else { e.find(name) })
+    }
+
+    /// 子孫要素から名前ですべて探す（文書順）
+    pub fn find_all<'a>(&'a self, name: &str) -> Vec<&'a Element> {
+        let mut found = Vec::new();
+        self.walk(&mut |e| {
+            if e.name == name {
+                found.push(e);
+            }
+        });
+        found
+    }
+
+    /// 子孫要素をすべて訪れる（文書順）
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Element)) {
+        for child in self.elements() {
+            f(child);
+            child.walk(f);
+        }
+    }
+
+    /// 含まれるテキストをすべてつなげる（空白は整えない）
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.collect_text(&mut out);
+        out
+    }
+
+    fn collect_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                Node::Text(t) => out.push_str(t),
+                Node::Element(e) if !SKIPPED_ELEMENTS.contains(&e.name.as_str()) => e.collect_text(out),
+                Node::Element(_) => {}
+            }
+        }
+    }
+}
+
+/// 本文のまとまり
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// h1〜h6の見出し
+    Heading(Paragraph),
+    Paragraph(Paragraph),
+}
+
+/// 文字列を解析して`#document`要素をルートとするツリーにする
+///
+/// 閉じタグの省略や対応しない閉じタグは無視し、未閉じの要素は文書末で閉じる。
+/// [`MAX_DEPTH`]より深い要素は空の要素として置き、中身はその親の子にする
+pub fn parse(input: &str) -> Element {
+    let mut stack: Vec<Element> = vec![Element::new("#document")];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        push_text(&mut stack, &rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(body) = rest.strip_prefix("<!--") {
+            rest = body.find("-->").map_or("", |end| &body[end + 3..]);
+        } else if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let end = body.find("]]>").unwrap_or(body.len());
+            append_text(&mut stack, &body[..end]);
+            rest = body.get(end + 3..).unwrap_or("");
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(body) = rest.strip_prefix("</") {
+            let end = body.find('>').unwrap_or(body.len());
+            close_element(&mut stack, &local_name(body[..end].trim()));
+            rest = body.get(end + 1..).unwrap_or("");
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let end = tag_end(rest);
+            let (element, self_closing) = parse_tag(&rest[1..end]);
+            rest = rest.get(end + 1..).unwrap_or("");
+            let name = element.name.clone();
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                let close = format!("</{}", name);
+                let end = find_ascii_case_insensitive(rest, &close).unwrap_or(rest.len());
+                let mut element = element;
+                element.children.push(Node::Text(rest[..end].to_string()));
+                push_element(&mut stack, element);
+                rest = &rest[end..];
+                rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            } else if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                push_element(&mut stack, element);
+            } else {
+                // <p>の中で段落が始まったら前の<p>を閉じる
+                if BLOCK_ELEMENTS.contains(&name.as_str()) && stack.last().is_some_and(|e| e.name == "p") {
+                    close_element(&mut stack, "p");
+                }
+                if stack.len() >= MAX_DEPTH {
+                    push_element(&mut stack, element);
+                } else {
+                    stack.push(element);
+                }
+            }
+        } else {
+            append_text(&mut stack, "<");
+            rest = &rest[1..];
+        }
+    }
+
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap();
+        push_element(&mut stack, element);
+    }
+    stack.pop().unwrap()
+}
+
+fn push_text(stack: &mut [Element], raw: &str) {
+    if !raw.is_empty() {
+        append_text(stack, &decode_entities(raw));
+    }
+}
+
+fn append_text(stack: &mut [Element], text: &str) {
+    let parent = stack.last_mut().expect("ルート要素は常にある");
+    match parent.children.last_mut() {
+        Some(Node::Text(prev)) => prev.push_str(text),
+        _ => parent.children.push(Node::Text(text.to_string())),
+    }
+}
+
+fn push_element(stack: &mut [Element], element: Element) {
+    stack.last_mut().expect("ルート要素は常にある").children.push(Node::Element(element));
+}
+
+/// 開いている同名の要素まで閉じる（なければ無視）
+fn close_element(stack: &mut Vec<Element>, name: &str) {
+    let Some(pos) = stack.iter().rposition(|e| e.name == name) else {
+        return;
+    };
+    if pos == 0 {
+        return;
+    }
+    while stack.len() > pos {
+        let element = stack.pop().unwrap();
+        push_element(stack, element);
+    }
+}
+
+/// 引用符の中の`>`を無視して開始タグの終端を探す
+fn tag_end(s: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_tag(tag: &str) -> (Element, bool) {
+    let self_closing = tag.trim_end().ends_with('/');
+    let tag = tag.trim_end().trim_end_matches('/');
+    let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+    let mut element = Element::new(&local_name(&tag[..name_end]));
+
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(rest.len());
+        let key = local_name(&rest[..key_end]);
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(q).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+        if !key.is_empty() {
+            element.attrs.push((key, value));
+        }
+    }
+    (element, self_closing)
+}
+
+/// 名前空間接頭辞を除いて小文字にする（`dc:title` → `title`）
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_ascii_lowercase()
+}
+
+fn find_ascii_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+/// 文字参照を展開する
+pub fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        // 実体参照の名前は長くても10文字なので、`;`はその範囲だけで探す（`&`が多い入力でも線形に収まる）
+        let decoded = rest
+            .bytes()
+            .skip(1)
+            .take(11)
+            .position(|b| b == b';')
+            .and_then(|end| entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{00A0}',
+        "ensp" => '\u{2002}',
+        "emsp" => '\u{2003}',
+        "thinsp" => '\u{2009}',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "laquo" => '«',
+        "raquo" => '»',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "times" => '×',
+        _ => return None,
+    })
+}
+
+/// 要素の中身を段落・見出しのまとまりにする（ルビは`<ruby>`/`<rt>`から取り出す）
+pub fn blocks(root: &Element) -> Vec<Block> {
+    let mut collector = BlockCollector::default();
+    collector.visit(root);
+    collector.flush(false);
+    collector.blocks
+}
+
+#[derive(Default)]
+struct BlockCollector {
+    builder: ParagraphBuilder,
+    blocks: Vec<Block>,
+}
+
+impl BlockCollector {
+    fn flush(&mut self, heading: bool) {
+        let paragraph = self.builder.finish();
+        if paragraph.text.is_empty() {
+            return;
+        }
+        self.blocks.push(if heading { Block::Heading(paragraph) } else { Block::Paragraph(paragraph) });
+    }
+
+    fn visit(&mut self, element: &Element) {
+        for child in &element.children {
+            match child {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(e) => self.visit_element(e),
+            }
+        }
+    }
+
+    fn visit_element(&mut self, element: &Element) {
+        let name = element.name.as_str();
+        if SKIPPED_ELEMENTS.contains(&name) {
+            return;
+        }
+        if name == "ruby" {
+            self.visit_ruby(element);
+            return;
+        }
+        if name == "rt" {
+            return;
+        }
+        if !BLOCK_ELEMENTS.contains(&name) {
+            self.visit(element);
+            return;
+        }
+        let heading = matches!(name, "h1" | "h2" | "h3" | "h4" | "h5" | "h6");
+        self.flush(false);
+        self.visit(element);
+        self.flush(heading);
+    }
+
+    /// `<ruby>親<rt>おや</rt>文字<rt>もじ</rt></ruby>`のような複数組にも対応する
+    fn visit_ruby(&mut self, ruby: &Element) {
+        let mut start = self.builder.len();
+        for child in &ruby.children {
+            match child {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(e) if e.name == "rt" => {
+                    self.builder.add_ruby(start, &e.text());
+                    start = self.builder.len();
+                }
+                Node::Element(e) => self.visit_element(e),
+            }
+        }
+    }
+
+    /// 日本語の間にある改行・インデントはソースの整形なので捨てる
+    fn push_text(&mut self, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let ch = chars[i];
+            if !ch.is_ascii_whitespace() {
+                self.builder.push(if ch == '\u{00A0}' { ' ' } else { ch });
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let has_newline = chars[start..i].contains(&'\n');
+            let before = self.builder.tail(1).first().copied();
+            let after = chars.get(i).copied();
+            if !(has_newline && (before.is_some_and(is_cjk) || after.is_some_and(is_cjk))) {
+                self.builder.push(' ');
+            }
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(script_of(c), Script::Kanji | Script::Hiragana | Script::Katakana) || ('\u{3000}'..='\u{303F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tolerates_broken_html() {
+        let root = parse("<!DOCTYPE html><html><body><p class=\"a\">一<p>二<br>三</div><img src=x.png></body>");
+        let ps = root.find_all("p");
+        assert_eq!(ps.len(), 2);
+        assert_eq!(ps[0].attr("class"), Some("a"));
+        assert_eq!(ps[1].text(), "二三");
+        assert!(root.find("img").is_some());
+    }
+
+    #[test]
+    fn test_deep_nesting_is_flattened() {
+        let nesting = 200_000;
+        let html = format!("<body>{}本文{}</body>", "<b>".repeat(nesting), "</b>".repeat(nesting));
+        let root = parse(&html);
+        // ルートを1段目として数える
+        let mut depth = 1;
+        let mut element = &root;
+        while let Some(child) = element.elements().find(|e| !e.children.is_empty()) {
+            depth += 1;
+            element = child;
+        }
+        assert_eq!(depth, MAX_DEPTH);
+        assert_eq!(root.text(), "本文");
+        assert_eq!(blocks(&root).len(), 1);
+    }
+
+    #[test]
+    fn test_script_and_entities() {
+        let root = parse("<div>A&amp;B &#x3042;&unknown; <script>if (a < b) {}</script></div>");
+        assert_eq!(root.find("div").unwrap().text(), "A&B あ&unknown; ");
+        assert_eq!(local_name("dc:Title"), "title");
+    }
+
+    #[test]
+    fn test_many_ampersands_without_semicolon() {
+        assert_eq!(decode_entities("&amp &lt;&abcdefghijk;"), "&amp <&abcdefghijk;");
+        let text = format!("{}&gt;", "&".repeat(200_000));
+        let decoded = decode_entities(&text);
+        assert_eq!(decoded.len(), 200_001);
+        assert!(decoded.ends_with("&>"));
+    }
+
+    #[test]
+    fn test_blocks_with_ruby() {
+        let root = parse(
+            "<body><h2>第一章</h2>\n<p>\n  <ruby>吾輩<rp>(</rp><rt>わがはい</rt><rp>)</rp></ruby>は\n  猫である。</p>\
+             <p><ruby>漢<rt>かん</rt>字<rt>じ</rt></ruby> and <em>English</em> text</p></body>",
+        );
+        let blocks = blocks(&root);
+        assert_eq!(blocks.len(), 3);
+        assert!(matches!(&blocks[0], Block::Heading(p) if p.text == "第一章"));
+        let Block::Paragraph(first) = &blocks[1] else { panic!("段落ではありません") };
+        assert_eq!(first.text, "吾輩は猫である。");
+        assert_eq!(first.ruby_base(&first.ruby[0]), "吾輩");
+        assert_eq!(first.ruby[0].reading, "わがはい");
+        let Block::Paragraph(second) = &blocks[2] else { panic!("段落ではありません") };
+        assert_eq!(second.text, "漢字 and English text");
+        assert_eq!(second.ruby.len(), 2);
+        assert_eq!(second.ruby_base(&second.ruby[1]), "字");
+    }
+}
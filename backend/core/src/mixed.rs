@@ -0,0 +1,232 @@
+//! 日英混在テキストの処理
+//! ラテン文字の区間を検出して単語単位で扱い、日本語の文字数と英単語数を分けて数える
+
+use serde::{Deserialize, Serialize};
+use crate::segmenter::{script_of, Script};
+
+/// 日本語の平均読書速度（文字/分）
+pub const JAPANESE_CHARS_PER_MINUTE: f64 = 500.0;
+/// 日本語話者が文中の英単語を読む速度（単語/分）
+pub const ENGLISH_WORDS_PER_MINUTE: f64 = 150.0;
+
+/// 区間の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    Japanese,
+    Latin,
+}
+
+/// テキストの区間（オフセットは文字単位、`end`は含まない）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextSpan {
+    pub kind: SpanKind,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TextSpan {
+    /// ラテン文字区間を空白区切りの単語に分ける（日本語区間はそのまま1要素）
+    pub fn words(&self) -> Vec<TextSpan> {
+        if self.kind == SpanKind::Japanese {
+            return vec![self.clone()];
+        }
+        let mut words = Vec::new();
+        let mut current: Option<TextSpan> = None;
+        for (i, ch) in self.text.chars().enumerate() {
+            let pos = self.start + i;
+            if ch == ' ' {
+                words.extend(current.take());
+                continue;
+            }
+            let word = current.get_or_insert_with(|| TextSpan {
+                kind: SpanKind::Latin,
+                text: String::new(),
+                start: pos,
+                end: pos,
+            });
+            word.text.push(ch);
+            word.end = pos + 1;
+        }
+        words.extend(current);
+        words
+    }
+}
+
+/// 日本語文字数・英単語数の集計
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TextStats {
+    /// 日本語区間の文字数（空白・句読点を除く）
+    pub japanese_chars: usize,
+    /// ラテン文字区間の単語数
+    pub english_words: usize,
+}
+
+impl TextStats {
+    /// 推定読書時間（分）
+    pub fn reading_time_minutes(&self) -> f64 {
+        self.japanese_chars as f64 / JAPANESE_CHARS_PER_MINUTE
+            + self.english_words as f64 / ENGLISH_WORDS_PER_MINUTE
+    }
+}
+
+fn is_latin_letter(c: char) -> bool {
+    script_of(c) == Script::Latin
+}
+
+fn is_word_char(c: char) -> bool {
+    matches!(script_of(c), Script::Latin | Script::Digit)
+}
+
+/// 単語内部に現れうる記号（Node.js, don't, COVID-19 など）
+fn is_joiner(c: char) -> bool {
+    matches!(c, '\'' | '’' | '-' | '.' | '_' | '+' | '#' | '&')
+}
+
+/// `i`から始まるラテン文字区間の終端を返す
+fn scan_latin(chars: &[char], i: usize) -> usize {
+    let n = chars.len();
+    let mut j = i;
+    loop {
+        while j < n && is_word_char(chars[j]) {
+            j += 1;
+        }
+        if j < n && is_joiner(chars[j]) {
+            let mut k = j;
+            while k < n && is_joiner(chars[k]) {
+                k += 1;
+            }
+            if k < n && is_word_char(chars[k]) {
+                j = k;
+                continue;
+            }
+            // C++, C# のような末尾記号
+            while j < n && matches!(chars[j], '+' | '#') {
+                j += 1;
+            }
+            return j;
+        }
+        if j < n && chars[j] == ' ' {
+            let mut k = j;
+            while k < n && chars[k] == ' ' {
+                k += 1;
+            }
+            if k < n && is_latin_letter(chars[k]) {
+                j = k;
+                continue;
+            }
+        }
+        return j;
+    }
+}
+
+/// テキストを日本語区間とラテン文字区間に分ける
+pub fn split_spans(text: &str) -> Vec<TextSpan> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let push = |spans: &mut Vec<TextSpan>, kind, start: usize, end: usize| {
+        if start < end {
+            spans.push(TextSpan {
+                kind,
+                text: chars[start..end].iter().collect(),
+                start,
+                end,
+            });
+        }
+    };
+
+    let mut japanese_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        // ラテン文字区間は英字か、英字が続く数字列（5G, 3D）から始まる。数字列は1度だけ走査し、英字が続かなければ読み飛ばす
+        let digits_end = chars[i..]
+            .iter()
+            .position(|&c| script_of(c) != Script::Digit)
+            .map_or(chars.len(), |n| i + n);
+        if chars.get(digits_end).is_some_and(|&c| is_latin_letter(c)) {
+            let end = scan_latin(&chars, i);
+            push(&mut spans, SpanKind::Japanese, japanese_start, i);
+            push(&mut spans, SpanKind::Latin, i, end);
+            i = end;
+            japanese_start = end;
+        } else {
+            i = digits_end.max(i + 1);
+        }
+    }
+    push(&mut spans, SpanKind::Japanese, japanese_start, chars.len());
+    spans
+}
+
+/// 日本語文字数と英単語数を数える
+pub fn text_stats(text: &str) -> TextStats {
+    let mut stats = TextStats::default();
+    for span in split_spans(text) {
+        match span.kind {
+            SpanKind::Latin => stats.english_words += span.words().len(),
+            SpanKind::Japanese => {
+                stats.japanese_chars += span
+                    .text
+                    .chars()
+                    .filter(|&c| !matches!(script_of(c), Script::Space | Script::Punctuation))
+                    .count();
+            }
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latin_texts(text: &str) -> Vec<String> {
+        split_spans(text)
+            .into_iter()
+            .filter(|s| s.kind == SpanKind::Latin)
+            .map(|s| s.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_spans() {
+        let spans = split_spans("React Nativeでアプリを作る");
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].kind, SpanKind::Latin);
+        assert_eq!(spans[0].text, "React Native");
+        assert_eq!((spans[1].start, spans[1].end), (12, 19));
+    }
+
+    #[test]
+    fn test_latin_span_boundaries() {
+        assert_eq!(latin_texts("Node.jsとC++、5Gの話"), vec!["Node.js", "C++", "5G"]);
+        assert_eq!(latin_texts("2025年にAIが"), vec!["AI"]);
+        assert_eq!(latin_texts("don't stop。"), vec!["don't stop"]);
+    }
+
+    #[test]
+    fn test_long_digit_runs() {
+        let digits = "1".repeat(200_000);
+        let spans = split_spans(&format!("{}年", digits));
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].kind, SpanKind::Japanese);
+        assert_eq!(latin_texts(&format!("{}年と{}G", digits, digits)), vec![format!("{}G", digits)]);
+    }
+
+    #[test]
+    fn test_words_offsets() {
+        let spans = split_spans("最新のOpen AI Modelを");
+        let words = spans[1].words();
+        let texts: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Open", "AI", "Model"]);
+        assert_eq!((words[1].start, words[1].end), (8, 10));
+    }
+
+    #[test]
+    fn test_text_stats() {
+        let stats = text_stats("React Nativeで、AIアプリを作る。");
+        assert_eq!(stats.english_words, 3);
+        assert_eq!(stats.japanese_chars, 7);
+        assert!(stats.reading_time_minutes() > 0.0);
+    }
+}
@@ -0,0 +1,418 @@
+//! 詳細な解析結果
+//! 形態素（読み・品詞・原形・オフセット）・文の境界・表示用のまとまりを、スキーマのバージョン付きで返す
+
+use serde::{Deserialize, Serialize};
+use readmaker_shared::{ReadMakerError, Result};
+use crate::backend::{RuleBasedBackend, TokenizerBackend};
+use crate::fallback::{Engine, FallbackPolicy};
+use crate::segmenter::{script_of, Script};
+use crate::MorphAnalyzer;
+
+/// 解析結果のスキーマのバージョン（フィールドの意味が変わったら上げる。詳細は`REPORT_SCHEMA.md`）
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// 文末とみなす文字
+const SENTENCE_TERMINATORS: &str = "。．！？!?";
+
+/// 次のまとまりの先頭に付ける開き括弧
+const OPENING_BRACKETS: &str = "「『（(【〔〈《［";
+
+/// 文末記号の直後に続けば同じ文に含める閉じ括弧
+const CLOSING_BRACKETS: &str = "」』）)】〕〉》";
+
+/// 形態素（オフセットは入力テキスト上の文字単位、`end`は含まない）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub surface: String,
+    /// 読み（カタカナ、辞書にない場合は表層形）
+    pub reading: String,
+    /// 品詞（辞書なしの場合は`*`）
+    pub pos: String,
+    /// 品詞細分類（`*`は除く）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pos_detail: Vec<String>,
+    /// 原形
+    pub lemma: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    /// IPADIC形式の素性（品詞,細分類1,細分類2,細分類3,活用型,活用形,原形,読み,発音）から作る
+    pub fn from_feature(surface: &str, feature: &str, start: usize, end: usize) -> Self {
+        let fields: Vec<&str> = feature.split(',').collect();
+        let field = |i: usize| fields.get(i).copied().filter(|f| !f.is_empty() && *f != "*");
+        Self {
+            surface: surface.to_string(),
+            reading: field(7).unwrap_or(surface).to_string(),
+            pos: field(0).unwrap_or("*").to_string(),
+            pos_detail: (1..4).filter_map(field).map(str::to_string).collect(),
+            lemma: field(6).unwrap_or(surface).to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// 品詞のない形態素（フォールバック分割の結果）
+    pub fn plain(surface: &str, start: usize, end: usize) -> Self {
+        Self::from_feature(surface, "", start, end)
+    }
+}
+
+/// 文（`token_start`..`token_end`は`tokens`の添字）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sentence {
+    pub start: usize,
+    pub end: usize,
+    pub token_start: usize,
+    pub token_end: usize,
+}
+
+/// 表示用のまとまり（自立語と後続の付属語を1つにしたもの）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub token_start: usize,
+    pub token_end: usize,
+}
+
+/// 返す項目と辞書が使えない場合の挙動
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportOptions {
+    pub tokens: bool,
+    pub sentences: bool,
+    pub chunks: bool,
+    pub fallback: FallbackPolicy,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self { tokens: true, sentences: true, chunks: true, fallback: FallbackPolicy::CharClass }
+    }
+}
+
+impl ReportOptions {
+    /// JSON文字列から読む（省略した項目は既定値）
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json)
+    }
+}
+
+/// 詳細な解析結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    pub version: u32,
+    pub engine: Engine,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<Vec<Token>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentences: Option<Vec<Sentence>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<Chunk>>,
+}
+
+/// MessagePackのメディアタイプ（HTTPのContent-Type）
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+impl Report {
+    /// MessagePackにする（JSONと同じフィールド名のマップ形式）
+    pub fn to_msgpack(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self)
+            .map_err(|e| ReadMakerError::Internal(format!("MessagePackへの変換に失敗しました: {}", e)))
+    }
+
+    /// MessagePackから読む（スキーマのバージョンが異なる場合はエラー）
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self> {
+        let report: Self = rmp_serde::from_slice(bytes)
+            .map_err(|e| ReadMakerError::Validation(format!("MessagePackの読み込みに失敗しました: {}", e)))?;
+        if report.version != REPORT_SCHEMA_VERSION {
+            return Err(ReadMakerError::Validation(format!(
+                "対応していないスキーマのバージョンです: {}（対応: {}）",
+                report.version, REPORT_SCHEMA_VERSION
+            )));
+        }
+        Ok(report)
+    }
+}
+
+/// 解析して詳細な結果を作る（辞書がない・解析に失敗した場合は`options.fallback`に従う）
+pub fn analyze_report(analyzer: Option<&MorphAnalyzer>, text: &str, options: &ReportOptions) -> Result<Report> {
+    let (engine, tokens) = tokenize_or_fallback(analyzer, text, options.fallback)?;
+    Ok(build_report(engine, text, tokens, options))
+}
+
+/// 進捗を報告しながら解析する（長文向け）
+///
+/// 解析前に`progress(0, 全体の文数)`を、以降は1文終わるたびに`progress(処理済みの文数, 全体の文数)`を呼ぶ。
+/// `progress`が`false`を返したら残りを解析せずに`Ok(None)`を返す
+pub fn analyze_report_with_progress(
+    analyzer: Option<&MorphAnalyzer>,
+    text: &str,
+    options: &ReportOptions,
+    mut progress: impl FnMut(usize, usize) -> bool,
+) -> Result<Option<Report>> {
+    let chars: Vec<char> = text.chars().collect();
+    let bounds = sentence_bounds(&chars);
+    let total = bounds.len();
+    if !progress(0, total) {
+        return Ok(None);
+    }
+
+    let primary = analyzer.map_or(Engine::Vibrato, MorphAnalyzer::engine);
+    let mut engine = primary;
+    let mut tokens = Vec::new();
+    for (i, &(start, end)) in bounds.iter().enumerate() {
+        let sentence: String = chars[start..end].iter().collect();
+        let (sentence_engine, sentence_tokens) = tokenize_or_fallback(analyzer, &sentence, options.fallback)?;
+        if sentence_engine != primary {
+            engine = sentence_engine;
+        }
+        tokens.extend(sentence_tokens.into_iter().map(|mut t| {
+            t.start += start;
+            t.end += start;
+            t
+        }));
+        if !progress(i + 1, total) {
+            return Ok(None);
+        }
+    }
+    Ok(Some(build_report(engine, text, tokens, options)))
+}
+
+/// 辞書で形態素解析し、辞書がない・失敗した場合はフォールバック方針で分割する
+fn tokenize_or_fallback(
+    analyzer: Option<&MorphAnalyzer>,
+    text: &str,
+    policy: FallbackPolicy,
+) -> Result<(Engine, Vec<Token>)> {
+    match analyzer.map(|a| (a.engine(), a.tokenize(text))) {
+        Some((engine, Ok(tokens))) => Ok((engine, tokens)),
+        Some((_, Err(e))) => fallback_tokens(text, policy).ok_or(e),
+        None => fallback_tokens(text, policy)
+            .ok_or_else(|| ReadMakerError::Analysis("辞書が読み込まれていません".to_string())),
+    }
+}
+
+/// 形態素列から文とまとまりを組み立てる
+pub fn build_report(engine: Engine, text: &str, tokens: Vec<Token>, options: &ReportOptions) -> Report {
+    Report {
+        version: REPORT_SCHEMA_VERSION,
+        engine,
+        sentences: options.sentences.then(|| sentences(text, &tokens)),
+        chunks: options.chunks.then(|| chunks(&tokens)),
+        tokens: options.tokens.then_some(tokens),
+    }
+}
+
+/// フォールバック分割の結果を形態素の形にする
+fn fallback_tokens(text: &str, policy: FallbackPolicy) -> Option<(Engine, Vec<Token>)> {
+    let backend = RuleBasedBackend::for_policy(policy)?;
+    Some((backend.engine(), backend.segment(text)))
+}
+
+/// 文末記号（と直後の閉じ括弧）・改行で文に分けた区間（前後の空白を含み、テキスト全体を隙間なく覆う）
+fn sentence_bounds(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        if c == '\n' || SENTENCE_TERMINATORS.contains(c) {
+            while i < chars.len() && (SENTENCE_TERMINATORS.contains(chars[i]) || CLOSING_BRACKETS.contains(chars[i])) {
+                i += 1;
+            }
+            // 文末に続く空白・改行は前の文に含める
+            while i < chars.len() && script_of(chars[i]) == Script::Space {
+                i += 1;
+            }
+            bounds.push((start, i));
+            start = i;
+        }
+    }
+    if start < chars.len() || bounds.is_empty() {
+        bounds.push((start, chars.len()));
+    }
+    bounds
+}
+
+/// 文ごとに重なる形態素の範囲を求める（文も形態素も位置の順に並ぶため、形態素を先頭から1度だけたどる）
+fn sentences(text: &str, tokens: &[Token]) -> Vec<Sentence> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut cursor = 0;
+    sentence_bounds(&chars)
+        .into_iter()
+        .filter_map(|(start, end)| {
+            // 前後の空白を除く
+            let start = (start..end).find(|&i| script_of(chars[i]) != Script::Space)?;
+            let end = (start..end).rev().find(|&i| script_of(chars[i]) != Script::Space)? + 1;
+            while cursor < tokens.len() && tokens[cursor].end <= start {
+                cursor += 1;
+            }
+            // 文の境界をまたぐ形態素は両方の文に含めるため、次の文もcursorから数える
+            let token_start = cursor;
+            let mut token_end = token_start;
+            while token_end < tokens.len() && tokens[token_end].start < end {
+                token_end += 1;
+            }
+            Some(Sentence { start, end, token_start, token_end })
+        })
+        .collect()
+}
+
+/// 前の形態素にくっつける付属語か
+fn attaches_to_previous(token: &Token) -> bool {
+    match token.pos.as_str() {
+        "助詞" | "助動詞" => true,
+        "名詞" | "動詞" | "形容詞" => token.pos_detail.first().is_some_and(|d| matches!(d.as_str(), "接尾" | "非自立")),
+        "記号" => token.pos_detail.first().is_none_or(|d| d != "括弧開"),
+        // 辞書なしの場合は句読点・閉じ括弧だけを前にくっつける
+        "*" => token.surface.chars().all(|c| {
+            script_of(c) == Script::Punctuation && !OPENING_BRACKETS.contains(c)
+        }),
+        _ => false,
+    }
+}
+
+/// 自立語に後続の付属語をまとめる（開き括弧は次のまとまりの先頭に付ける）
+fn chunks(tokens: &[Token]) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut open_bracket = false;
+    for (i, token) in tokens.iter().enumerate() {
+        let contiguous = chunks.last().is_some_and(|c| c.end == token.start);
+        match chunks.last_mut() {
+            Some(last) if contiguous && (open_bracket || attaches_to_previous(token)) => {
+                last.text.push_str(&token.surface);
+                last.end = token.end;
+                last.token_end = i + 1;
+            }
+            _ => chunks.push(Chunk {
+                text: token.surface.clone(),
+                start: token.start,
+                end: token.end,
+                token_start: i,
+                token_end: i + 1,
+            }),
+        }
+        open_bracket = token.surface.chars().all(|c| OPENING_BRACKETS.contains(c));
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::mini_dictionary;
+
+    #[test]
+    fn test_report_with_dictionary() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let report = analyze_report(Some(&analyzer), "今日は良い天気です。吾輩は猫である。", &ReportOptions::default()).unwrap();
+        assert_eq!(report.version, REPORT_SCHEMA_VERSION);
+        assert_eq!(report.engine, Engine::Vibrato);
+
+        let tokens = report.tokens.unwrap();
+        assert_eq!(tokens[0].reading, "キョウ");
+        assert_eq!(tokens[0].pos, "名詞");
+        assert_eq!(tokens[0].pos_detail, vec!["副詞可能"]);
+        assert_eq!((tokens[3].start, tokens[3].end), (5, 7));
+        let de = tokens.iter().find(|t| t.surface == "で").unwrap();
+        assert_eq!((de.pos.as_str(), de.lemma.as_str()), ("助詞", "で"));
+
+        let sentences = report.sentences.unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!((sentences[1].start, sentences[1].end), (10, 18));
+        assert_eq!(tokens[sentences[1].token_start].surface, "吾輩");
+
+        let chunks: Vec<_> = report.chunks.unwrap().into_iter().map(|c| c.text).collect();
+        assert_eq!(chunks, vec!["今日は", "良い", "天気です。", "吾輩は", "猫である。"]);
+    }
+
+    #[test]
+    fn test_report_fallback_and_options() {
+        let options = ReportOptions::from_json(r#"{"chunks": false}"#).unwrap();
+        let report = analyze_report(None, "「はい」と言った。\n次の文", &options).unwrap();
+        assert_eq!(report.engine, Engine::CharClass);
+        assert!(report.chunks.is_none());
+        let sentences = report.sentences.unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!((sentences[1].start, sentences[1].end), (10, 13));
+
+        let options = ReportOptions { fallback: FallbackPolicy::Error, ..ReportOptions::default() };
+        assert!(analyze_report(None, "テキスト", &options).is_err());
+        assert!(ReportOptions::from_json("{broken").is_err());
+    }
+
+    #[test]
+    fn test_sentence_token_ranges() {
+        let token = |start, end| Token::from_feature("*", "*", start, end);
+        // 「。」を含む形態素が次の文にまたがる
+        let tokens = vec![token(0, 2), token(2, 4), token(4, 6), token(6, 10)];
+        let ranges: Vec<_> = sentences("一二。三四 五六。七", &tokens).iter().map(|s| (s.token_start, s.token_end)).collect();
+        assert_eq!(ranges, vec![(0, 2), (1, 4), (3, 4)]);
+        let ranges: Vec<_> = sentences("一。二。", &[]).iter().map(|s| (s.token_start, s.token_end)).collect();
+        assert_eq!(ranges, vec![(0, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn test_punctuation_fallback_offsets() {
+        let options = ReportOptions { fallback: FallbackPolicy::Punctuation, ..ReportOptions::default() };
+        let text = "一つ目、 二つ目。三つ目";
+        let report = analyze_report(None, text, &options).unwrap();
+        let chars: Vec<char> = text.chars().collect();
+        let tokens = report.tokens.unwrap();
+        assert!(tokens.len() >= 3);
+        for token in tokens {
+            assert_eq!(chars[token.start..token.end].iter().collect::<String>(), token.surface);
+        }
+    }
+
+    #[test]
+    fn test_report_with_progress() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let text = "今日は良い天気です。吾輩は猫である。\n名前はまだ無い。";
+        let mut calls = Vec::new();
+        let report = analyze_report_with_progress(Some(&analyzer), text, &ReportOptions::default(), |done, total| {
+            calls.push((done, total));
+            true
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(calls, vec![(0, 3), (1, 3), (2, 3), (3, 3)]);
+        // 一括解析と同じ結果になる
+        assert_eq!(report, analyze_report(Some(&analyzer), text, &ReportOptions::default()).unwrap());
+
+        let mut calls = 0;
+        let cancelled = analyze_report_with_progress(None, text, &ReportOptions::default(), |_, _| {
+            calls += 1;
+            false
+        })
+        .unwrap();
+        assert!(cancelled.is_none());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_msgpack_roundtrip() {
+        let analyzer = MorphAnalyzer::from_dictionary(mini_dictionary());
+        let report = analyze_report(Some(&analyzer), "今日は良い天気です。", &ReportOptions::default()).unwrap();
+        let bytes = report.to_msgpack().unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&report).unwrap().len());
+        assert_eq!(Report::from_msgpack(&bytes).unwrap(), report);
+
+        // 他言語のデコーダーからも同じフィールド名で読める
+        let value: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(value["version"], REPORT_SCHEMA_VERSION);
+        assert_eq!(value["tokens"][0]["reading"], "キョウ");
+
+        let future = Report { version: REPORT_SCHEMA_VERSION + 1, ..report };
+        assert!(Report::from_msgpack(&rmp_serde::to_vec_named(&future).unwrap()).is_err());
+        assert!(Report::from_msgpack(b"broken").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! RSVP（高速逐次提示）の表示スケジュール
+//! まとまりを1つずつ表示する時間を、読む速さ（文字/分）と句読点での間から決める
+
+use serde::{Deserialize, Serialize};
+use crate::report::Chunk;
+use crate::segmenter::{script_of, Script};
+
+/// 読点など、短い間を置く記号
+const CLAUSE_MARKS: &str = "、，,：:；;";
+/// 句点など、長い間を置く記号
+const SENTENCE_MARKS: &str = "。．.！？!?";
+/// 文末記号の後ろに付く閉じ括弧
+const CLOSING_BRACKETS: &str = "」』）)】〕〉》";
+
+/// スケジュールの設定
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RsvpOptions {
+    /// 読む速さ（文字/分。アプリの既定は300）
+    pub chars_per_minute: f64,
+    /// 読点で終わるまとまりの後に足す時間（ミリ秒）
+    pub clause_pause_ms: u64,
+    /// 句点で終わるまとまりの後に足す時間（ミリ秒）
+    pub sentence_pause_ms: u64,
+    /// 1つのまとまりを表示する最短の時間（ミリ秒）
+    pub min_frame_ms: u64,
+}
+
+impl Default for RsvpOptions {
+    fn default() -> Self {
+        Self { chars_per_minute: 300.0, clause_pause_ms: 150, sentence_pause_ms: 300, min_frame_ms: 120 }
+    }
+}
+
+/// 1回分の表示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RsvpFrame {
+    pub text: String,
+    /// 表示を始める時刻（先頭からのミリ秒）
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    /// 元のまとまりの文字オフセット
+    pub start: usize,
+    pub end: usize,
+}
+
+/// まとまりの表示スケジュールを作る（空白だけのまとまりは表示しない）
+pub fn rsvp_schedule(chunks: &[Chunk], options: &RsvpOptions) -> Vec<RsvpFrame> {
+    let ms_per_char = if options.chars_per_minute > 0.0 { 60_000.0 / options.chars_per_minute } else { 0.0 };
+    let mut frames = Vec::with_capacity(chunks.len());
+    let mut elapsed = 0;
+    for chunk in chunks {
+        let text = chunk.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        // 記号は読む文字に数えない
+        let chars = text.chars().filter(|&c| !matches!(script_of(c), Script::Space | Script::Punctuation)).count();
+        let reading_ms = (chars as f64 * ms_per_char).round() as u64;
+        let duration_ms = reading_ms.max(options.min_frame_ms) + pause_after(text, options);
+        frames.push(RsvpFrame {
+            text: text.to_string(),
+            start_ms: elapsed,
+            duration_ms,
+            start: chunk.start,
+            end: chunk.end,
+        });
+        elapsed += duration_ms;
+    }
+    frames
+}
+
+/// まとまりの末尾の句読点に応じた間
+fn pause_after(text: &str, options: &RsvpOptions) -> u64 {
+    match text.trim_end_matches(|c| CLOSING_BRACKETS.contains(c)).chars().last() {
+        Some(c) if SENTENCE_MARKS.contains(c) => options.sentence_pause_ms,
+        Some(c) if CLAUSE_MARKS.contains(c) => options.clause_pause_ms,
+        _ => 0,
+    }
+}
+
+/// スケジュール全体の長さ（ミリ秒）
+pub fn rsvp_total_ms(frames: &[RsvpFrame]) -> u64 {
+    frames.last().map_or(0, |f| f.start_ms + f.duration_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{analyze_report, ReportOptions};
+
+    fn chunk(text: &str, start: usize) -> Chunk {
+        let end = start + text.chars().count();
+        Chunk { text: text.to_string(), start, end, token_start: 0, token_end: 0 }
+    }
+
+    #[test]
+    fn test_schedule_with_pauses() {
+        let chunks = [chunk("今日は", 0), chunk("良い", 3), chunk("天気です。", 5), chunk("「はい」", 10), chunk("そう、", 14)];
+        let frames = rsvp_schedule(&chunks, &RsvpOptions::default());
+        let timing: Vec<_> = frames.iter().map(|f| (f.start_ms, f.duration_ms)).collect();
+        // 300文字/分 = 1文字200ミリ秒
+        assert_eq!(timing, vec![(0, 600), (600, 400), (1000, 1100), (2100, 400), (2500, 550)]);
+        assert_eq!(rsvp_total_ms(&frames), 3050);
+    }
+
+    #[test]
+    fn test_minimum_frame_and_blank_chunks() {
+        let options = RsvpOptions { chars_per_minute: 6000.0, ..RsvpOptions::default() };
+        let frames = rsvp_schedule(&[chunk("猫", 0), chunk(" \n", 1), chunk("。", 3)], &options);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].duration_ms, 120);
+        assert_eq!(frames[1].duration_ms, 120 + 300);
+        assert_eq!(rsvp_total_ms(&[]), 0);
+    }
+
+    #[test]
+    fn test_schedule_from_report() {
+        let report = analyze_report(None, "吾輩は猫である。名前はまだ無い。", &ReportOptions::default()).unwrap();
+        let frames = rsvp_schedule(&report.chunks.unwrap(), &RsvpOptions::default());
+        assert!(!frames.is_empty());
+        assert_eq!(frames.iter().map(|f| f.text.as_str()).collect::<String>(), "吾輩は猫である。名前はまだ無い。");
+    }
+}
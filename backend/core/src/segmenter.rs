@@ -0,0 +1,197 @@
+//! 文字種ベースの分割器
+//! 辞書なしでも表示用のまとまりを作れるよう、Unicodeの文字種の連続で分割する
+
+use serde::{Deserialize, Serialize};
+use crate::mixed::{split_spans, SpanKind};
+
+/// 文字種
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Script {
+    Kanji,
+    Hiragana,
+    Katakana,
+    Latin,
+    Digit,
+    Punctuation,
+    Space,
+    Other,
+}
+
+/// 文字の文字種を判定
+///
+/// 長音符「ー」は前の文字の文字種を引き継ぐため、単独では`Katakana`として扱う
+pub fn script_of(c: char) -> Script {
+    match c {
+        '々' | '〆' | '〇' | 'ヶ'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FFFF}' => Script::Kanji,
+        '\u{3041}'..='\u{309F}' => Script::Hiragana,
+        '・' => Script::Punctuation,
+        '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => Script::Katakana,
+        '0'..='9' | '０'..='９' => Script::Digit,
+        'a'..='z' | 'A'..='Z' | 'ａ'..='ｚ' | 'Ａ'..='Ｚ' => Script::Latin,
+        '\u{00C0}'..='\u{024F}' if c != '×' && c != '÷' => Script::Latin,
+        c if c.is_whitespace() => Script::Space,
+        c if c.is_ascii_punctuation() => Script::Punctuation,
+        '\u{2000}'..='\u{206F}' | '\u{3000}'..='\u{303F}' | '\u{FF01}'..='\u{FF65}' => Script::Punctuation,
+        _ => Script::Other,
+    }
+}
+
+/// 分割結果の1単位（オフセットは文字単位、`end`は含まない）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    pub surface: String,
+    /// 先頭の文字種（送り仮名を結合した場合は`Kanji`）
+    pub script: Script,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 同じ文字種の連続で分割（空白は除去、記号は1文字ずつ）
+pub fn script_runs(text: &str) -> Vec<Segment> {
+    script_runs_from(text, 0)
+}
+
+/// `offset`文字目から始まる部分文字列として`script_runs`を行う
+fn script_runs_from(text: &str, offset: usize) -> Vec<Segment> {
+    let mut runs: Vec<Segment> = Vec::new();
+    let mut prev: Option<Script> = None;
+
+    for (i, ch) in text.chars().enumerate() {
+        let i = offset + i;
+        let mut script = script_of(ch);
+        // 長音符はひらがな・カタカナの連続を途切れさせない
+        if ch == 'ー' && matches!(prev, Some(Script::Hiragana) | Some(Script::Katakana)) {
+            script = prev.unwrap_or(script);
+        }
+
+        let continues = prev == Some(script) && !matches!(script, Script::Punctuation | Script::Other);
+        prev = Some(script);
+        if script == Script::Space {
+            continue;
+        }
+        match runs.last_mut() {
+            Some(last) if continues && last.end == i => {
+                last.surface.push(ch);
+                last.end = i + 1;
+            }
+            _ => runs.push(Segment {
+                surface: ch.to_string(),
+                script,
+                start: i,
+                end: i + 1,
+            }),
+        }
+    }
+    runs
+}
+
+/// 表示用のまとまりに分割
+///
+/// 漢字の直後に続くひらがなは送り仮名・助詞として漢字側に結合する。
+/// 英語などのラテン文字区間は単語単位で区切る
+pub fn segment(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    for span in split_spans(text) {
+        match span.kind {
+            SpanKind::Latin => segments.extend(span.words().into_iter().map(|w| Segment {
+                surface: w.text,
+                script: Script::Latin,
+                start: w.start,
+                end: w.end,
+            })),
+            SpanKind::Japanese => segments.extend(segment_japanese(&span.text, span.start)),
+        }
+    }
+    segments
+}
+
+fn segment_japanese(text: &str, offset: usize) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut prev_run: Option<Script> = None;
+
+    for run in script_runs_from(text, offset) {
+        let script = run.script;
+        match segments.last_mut() {
+            Some(last)
+                if script == Script::Hiragana
+                    && prev_run == Some(Script::Kanji)
+                    && last.end == run.start =>
+            {
+                last.surface.push_str(&run.surface);
+                last.end = run.end;
+            }
+            _ => segments.push(run),
+        }
+        prev_run = Some(script);
+    }
+    segments
+}
+
+/// 表示用のまとまりの表層形のみを返す
+pub fn segment_surfaces(text: &str) -> Vec<String> {
+    segment(text).into_iter().map(|s| s.surface).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_of() {
+        assert_eq!(script_of('漢'), Script::Kanji);
+        assert_eq!(script_of('々'), Script::Kanji);
+        assert_eq!(script_of('か'), Script::Hiragana);
+        assert_eq!(script_of('カ'), Script::Katakana);
+        assert_eq!(script_of('ｶ'), Script::Katakana);
+        assert_eq!(script_of('R'), Script::Latin);
+        assert_eq!(script_of('７'), Script::Digit);
+        assert_eq!(script_of('。'), Script::Punctuation);
+        assert_eq!(script_of('・'), Script::Punctuation);
+        assert_eq!(script_of('　'), Script::Space);
+    }
+
+    #[test]
+    fn test_script_runs() {
+        let runs = script_runs("ReactとAIで2025年");
+        let surfaces: Vec<_> = runs.iter().map(|r| r.surface.as_str()).collect();
+        assert_eq!(surfaces, vec!["React", "と", "AI", "で", "2025", "年"]);
+        assert_eq!((runs[1].start, runs[1].end), (5, 6));
+    }
+
+    #[test]
+    fn test_okurigana_attached_to_kanji() {
+        let surfaces = segment_surfaces("今日は良い天気です。");
+        assert_eq!(surfaces, vec!["今日は", "良い", "天気です", "。"]);
+    }
+
+    #[test]
+    fn test_leading_hiragana_and_katakana() {
+        let surfaces = segment_surfaces("これはテストです。");
+        assert_eq!(surfaces, vec!["これは", "テスト", "です", "。"]);
+    }
+
+    #[test]
+    fn test_long_vowel_mark() {
+        let surfaces = segment_surfaces("すごーいコーヒー");
+        assert_eq!(surfaces, vec!["すごーい", "コーヒー"]);
+    }
+
+    #[test]
+    fn test_latin_words() {
+        let surfaces = segment_surfaces("React NativeでAIを使う");
+        assert_eq!(surfaces, vec!["React", "Native", "で", "AI", "を", "使う"]);
+    }
+
+    #[test]
+    fn test_space_breaks_attachment() {
+        let segments = segment("漢字 かな");
+        let surfaces: Vec<_> = segments.iter().map(|s| s.surface.as_str()).collect();
+        assert_eq!(surfaces, vec!["漢字", "かな"]);
+        assert_eq!((segments[1].start, segments[1].end), (3, 5));
+    }
+}
@@ -0,0 +1,27 @@
+//! テスト用ユーティリティ
+
+use vibrato::{Dictionary, SystemDictionaryBuilder};
+
+const LEX_CSV: &str = include_str!("../testdata/mini-ipadic/lex.csv");
+const MATRIX_DEF: &str = include_str!("../testdata/mini-ipadic/matrix.def");
+const CHAR_DEF: &str = include_str!("../testdata/mini-ipadic/char.def");
+const UNK_DEF: &str = include_str!("../testdata/mini-ipadic/unk.def");
+
+/// `testdata/mini-ipadic`から極小辞書を組み立てる
+pub fn mini_dictionary() -> Dictionary {
+    SystemDictionaryBuilder::from_readers(
+        LEX_CSV.as_bytes(),
+        MATRIX_DEF.as_bytes(),
+        CHAR_DEF.as_bytes(),
+        UNK_DEF.as_bytes(),
+    )
+    .expect("テスト用辞書の組み立てに失敗")
+}
+
+/// 極小辞書を`.vibrato`形式のバイト列にする（FFIのテスト用）
+#[cfg(feature = "ffi")]
+pub fn mini_dictionary_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    mini_dictionary().write(&mut bytes).expect("テスト用辞書の書き出しに失敗");
+    bytes
+}
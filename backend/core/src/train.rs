@@ -0,0 +1,171 @@
+//! 辞書の再学習
+//! 形態素解析済みのコーパス（MeCab形式）から、vibratoの学習機能で語彙のコストと連接コストを学習し直す。
+//! ニュース・ライトノベルなど、分野ごとに分割を合わせた辞書を作るのに使う
+
+use vibrato::trainer::{Corpus, Trainer, TrainerConfig};
+use readmaker_shared::{ReadMakerError, Result};
+use crate::annotated::AnnotatedCorpus;
+use crate::compile::DictionarySource;
+use crate::eval::{evaluate, EvalOptions, Evaluation};
+use crate::MorphAnalyzer;
+
+/// IPADIC向けの素性テンプレート（`training/feature.def`）
+pub const DEFAULT_FEATURE_DEF: &str = include_str!("../training/feature.def");
+/// IPADIC向けの素性の書き換え規則（`training/rewrite.def`）
+pub const DEFAULT_REWRITE_DEF: &str = include_str!("../training/rewrite.def");
+
+/// 学習の設定
+#[derive(Debug, Clone)]
+pub struct TrainOptions {
+    pub feature_def: String,
+    pub rewrite_def: String,
+    /// L1正則化の強さ（大きいほど素性が減る）
+    pub regularization_cost: f64,
+    pub max_iter: u64,
+    pub num_threads: usize,
+    /// 未知語としてまとめる最大の文字数（`None`なら制限しない。MeCabと揃えるなら24）
+    pub max_grouping_len: Option<usize>,
+}
+
+impl Default for TrainOptions {
+    fn default() -> Self {
+        Self {
+            feature_def: DEFAULT_FEATURE_DEF.to_string(),
+            rewrite_def: DEFAULT_REWRITE_DEF.to_string(),
+            regularization_cost: 0.01,
+            max_iter: 100,
+            num_threads: 1,
+            max_grouping_len: None,
+        }
+    }
+}
+
+/// 学習と評価の結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingReport {
+    pub train_sentences: usize,
+    pub held_out_sentences: usize,
+    /// 評価用の文での、元の辞書の評価
+    pub before: Evaluation,
+    /// 評価用の文での、学習した辞書の評価
+    pub after: Evaluation,
+}
+
+/// 語彙・文字定義・未知語定義は`base`のものを使い、コストを`corpus`から学習した辞書のソースを返す
+///
+/// 連接表（matrix.def）と語彙の連接ID・コストは学習結果で置き換わる
+pub fn train_dictionary(base: &DictionarySource, corpus: &AnnotatedCorpus, options: &TrainOptions) -> Result<DictionarySource> {
+    if corpus.sentences.is_empty() {
+        return Err(ReadMakerError::Validation("学習用の文がありません".to_string()));
+    }
+    let invalid = |e: vibrato::errors::VibratoError| ReadMakerError::Validation(format!("学習の設定を読めません: {}", e));
+    let config = TrainerConfig::from_readers(
+        base.lexicon.as_bytes(),
+        base.char_def.as_bytes(),
+        base.unk_def.as_bytes(),
+        options.feature_def.as_bytes(),
+        options.rewrite_def.as_bytes(),
+    )
+    .map_err(invalid)?;
+    let mut trainer = Trainer::new(config)
+        .map_err(invalid)?
+        .regularization_cost(options.regularization_cost)
+        .max_iter(options.max_iter.max(1))
+        .num_threads(options.num_threads.max(1));
+    if let Some(len) = options.max_grouping_len {
+        trainer = trainer.max_grouping_len(len);
+    }
+
+    let examples = Corpus::from_reader(corpus.to_mecab().as_bytes())
+        .map_err(|e| ReadMakerError::Validation(format!("学習用のコーパスを読めません: {}", e)))?;
+    let mut model = trainer
+        .train(examples)
+        .map_err(|e| ReadMakerError::Analysis(format!("学習に失敗しました: {}", e)))?;
+
+    let (mut lexicon, mut matrix, mut unk_def, mut user_lexicon) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    model
+        .write_dictionary(&mut lexicon, &mut matrix, &mut unk_def, &mut user_lexicon)
+        .map_err(|e| ReadMakerError::Internal(format!("学習した辞書の書き出しに失敗しました: {}", e)))?;
+    let utf8 = |bytes: Vec<u8>| {
+        String::from_utf8(bytes).map_err(|e| ReadMakerError::Internal(format!("学習した辞書がUTF-8ではありません: {}", e)))
+    };
+    Ok(DictionarySource {
+        lexicon: utf8(lexicon)?,
+        matrix: utf8(matrix)?,
+        char_def: base.char_def.clone(),
+        unk_def: utf8(unk_def)?,
+        lexicon_files: Vec::new(),
+    })
+}
+
+/// コーパスを学習用と評価用（`held_out_ratio`の割合）に分けて学習し、評価用の文で学習前後の精度を比べる
+///
+/// 評価に使った学習後のアナライザーを返す（辞書は[`MorphAnalyzer::dictionary`]で書き出せる）
+pub fn train_and_evaluate(
+    base: &DictionarySource,
+    corpus: &AnnotatedCorpus,
+    held_out_ratio: f64,
+    options: &TrainOptions,
+    eval_options: &EvalOptions,
+) -> Result<(MorphAnalyzer, TrainingReport)> {
+    let (train, held_out) = corpus.split_held_out(held_out_ratio)?;
+    let trained = MorphAnalyzer::from_dictionary(train_dictionary(base, &train, options)?.build()?);
+    let before = evaluate(&MorphAnalyzer::from_dictionary(base.build()?), &held_out, eval_options)?;
+    let after = evaluate(&trained, &held_out, eval_options)?;
+    let report = TrainingReport {
+        train_sentences: train.sentences.len(),
+        held_out_sentences: held_out.sentences.len(),
+        before,
+        after,
+    };
+    Ok((trained, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::SourceEncoding;
+
+    fn base() -> DictionarySource {
+        DictionarySource::from_dir("testdata/mini-ipadic", SourceEncoding::Utf8).unwrap()
+    }
+
+    fn corpus() -> AnnotatedCorpus {
+        AnnotatedCorpus::from_file("testdata/annotated/mini.txt").unwrap()
+    }
+
+    fn pos_of(analyzer: &MorphAnalyzer, text: &str, surface: &str) -> String {
+        analyzer.tokenize(text).unwrap().into_iter().find(|t| t.surface == surface).unwrap().pos
+    }
+
+    #[test]
+    fn test_train_learns_context() {
+        let base = base();
+        let before = MorphAnalyzer::from_dictionary(base.build().unwrap());
+        // 元の辞書はコストだけで選ぶため「である」の「で」も助詞になる
+        assert_eq!(pos_of(&before, "吾輩は猫である。", "で"), "助詞");
+
+        let trained = train_dictionary(&base, &corpus(), &TrainOptions::default()).unwrap();
+        let after = MorphAnalyzer::from_dictionary(trained.build().unwrap());
+        assert_eq!(pos_of(&after, "吾輩は猫である。", "で"), "助動詞");
+        assert_eq!(pos_of(&after, "アプリで天気を作る。", "で"), "助詞");
+        assert_eq!(trained.char_def, base.char_def);
+        assert!(trained.lexicon.lines().any(|l| l.starts_with("吾輩,")));
+    }
+
+    #[test]
+    fn test_train_and_evaluate() {
+        let (trained, report) =
+            train_and_evaluate(&base(), &corpus(), 0.25, &TrainOptions::default(), &EvalOptions::default()).unwrap();
+        assert!(trained.dictionary().is_some());
+        assert!(report.held_out_sentences > 0);
+        assert_eq!(report.train_sentences + report.held_out_sentences, corpus().sentences.len());
+        assert_eq!(report.after.sentences, report.held_out_sentences);
+        assert!(report.after.segmentation.f1() >= report.before.segmentation.f1());
+        assert!(report.after.pos_accuracy() >= report.before.pos_accuracy());
+
+        assert!(train_dictionary(&base(), &AnnotatedCorpus::default(), &TrainOptions::default()).is_err());
+        let broken = TrainOptions { feature_def: "BROKEN".to_string(), ..TrainOptions::default() };
+        assert!(train_dictionary(&base(), &corpus(), &broken).is_err());
+    }
+}
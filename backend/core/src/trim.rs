@@ -0,0 +1,274 @@
+//! 辞書の縮小
+//! モバイルに同梱する辞書を小さくするため、使わない素性の列を落とし、コーパスでの出現頻度が低い語を除く。
+//! 縮小前後の分割の違いはサンプルコーパスで確かめる（[`segmentation_diff`]）
+
+use std::collections::HashMap;
+use serde::Serialize;
+use readmaker_shared::Result;
+use crate::compile::DictionarySource;
+use crate::MorphAnalyzer;
+
+/// 残す素性の列（品詞・品詞細分類1〜3・原形・読み。[`crate::Token::from_feature`]が読む列）
+///
+/// 活用型・活用形は`*`にして列の位置を保ち、発音以降は落とす
+const KEPT_FEATURES: [usize; 6] = [0, 1, 2, 3, 6, 7];
+
+/// 縮小後の素性の列数
+const TRIMMED_FEATURE_COLUMNS: usize = 8;
+
+/// 頻度に関わらず残す品詞（付属語・記号はまとまり分割に欠かせず、語数も少ない）
+const PROTECTED_POS: [&str; 7] = ["助詞", "助動詞", "記号", "接続詞", "連体詞", "接頭詞", "フィラー"];
+
+/// 頻度に関わらず残す品詞細分類1（`こと`・`さん`など、まとまり分割で付属語として扱う語）
+const PROTECTED_POS_DETAIL: [&str; 2] = ["非自立", "接尾"];
+
+/// 縮小の設定
+#[derive(Debug, Clone, Default)]
+pub struct TrimOptions {
+    /// 使わない素性の列を落とす
+    pub drop_features: bool,
+    /// この回数未満しか出現しない語を除く（`None`なら頻度では除かない）
+    pub min_frequency: Option<u64>,
+}
+
+/// 縮小の結果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TrimStats {
+    pub entries_before: usize,
+    pub entries_after: usize,
+    /// 頻度が閾値未満で除いた語
+    pub removed_rare: usize,
+    /// 素性を落とした結果、同じ表層形・連接ID・素性になった語（コストが最小のものだけ残す）
+    pub removed_duplicates: usize,
+}
+
+/// 語彙の1行
+struct Entry<'a> {
+    surface: &'a str,
+    left_id: &'a str,
+    right_id: &'a str,
+    cost: i32,
+    features: Vec<&'a str>,
+}
+
+impl<'a> Entry<'a> {
+    /// `"`で囲まれた表層形（カンマを含む語）は扱わない
+    fn parse(line: &'a str) -> Option<Self> {
+        if line.starts_with('"') {
+            return None;
+        }
+        let mut columns = line.split(',');
+        let surface = columns.next()?;
+        let left_id = columns.next()?;
+        let right_id = columns.next()?;
+        let cost = columns.next()?.parse().ok()?;
+        Some(Self { surface, left_id, right_id, cost, features: columns.collect() })
+    }
+
+    fn is_protected(&self) -> bool {
+        let field = |i: usize| self.features.get(i).copied().unwrap_or("*");
+        PROTECTED_POS.contains(&field(0)) || PROTECTED_POS_DETAIL.contains(&field(1))
+    }
+
+    fn trimmed_features(&self) -> String {
+        (0..TRIMMED_FEATURE_COLUMNS)
+            .map(|i| match self.features.get(i) {
+                Some(field) if KEPT_FEATURES.contains(&i) => *field,
+                _ => "*",
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl DictionarySource {
+    /// 縮小したソースを作る（連接表・文字定義・未知語定義はそのまま）
+    ///
+    /// `frequencies`は表層形ごとの出現回数（[`word_frequencies`]）。`min_frequency`を指定したときだけ使う
+    pub fn trim(&self, options: &TrimOptions, frequencies: &HashMap<String, u64>) -> (DictionarySource, TrimStats) {
+        let mut stats = TrimStats::default();
+        let mut lines: Vec<String> = Vec::new();
+        // (表層形, 左ID, 右ID, 素性) → `lines`の添字とコスト
+        let mut seen: HashMap<(String, String, String, String), (usize, i32)> = HashMap::new();
+
+        for line in self.lexicon.lines() {
+            stats.entries_before += 1;
+            let Some(entry) = Entry::parse(line) else {
+                lines.push(line.to_string());
+                continue;
+            };
+            if let Some(min) = options.min_frequency {
+                if !entry.is_protected() && frequencies.get(entry.surface).copied().unwrap_or(0) < min {
+                    stats.removed_rare += 1;
+                    continue;
+                }
+            }
+            if !options.drop_features {
+                lines.push(line.to_string());
+                continue;
+            }
+
+            let features = entry.trimmed_features();
+            let trimmed = format!("{},{},{},{},{}", entry.surface, entry.left_id, entry.right_id, entry.cost, features);
+            let key = (entry.surface.to_string(), entry.left_id.to_string(), entry.right_id.to_string(), features);
+            match seen.get_mut(&key) {
+                Some((index, cost)) => {
+                    stats.removed_duplicates += 1;
+                    if entry.cost < *cost {
+                        *cost = entry.cost;
+                        lines[*index] = trimmed;
+                    }
+                }
+                None => {
+                    seen.insert(key, (lines.len(), entry.cost));
+                    lines.push(trimmed);
+                }
+            }
+        }
+
+        stats.entries_after = lines.len();
+        let mut lexicon = lines.join("\n");
+        lexicon.push('\n');
+        let trimmed = DictionarySource { lexicon, ..self.clone() };
+        (trimmed, stats)
+    }
+}
+
+/// コーパスを解析し、表層形ごとの出現回数を数える（1行を1文として扱う）
+pub fn word_frequencies(analyzer: &MorphAnalyzer, corpus: &str) -> Result<HashMap<String, u64>> {
+    let mut frequencies = HashMap::new();
+    for line in corpus.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        for word in analyzer.analyze_words(line)? {
+            *frequencies.entry(word).or_insert(0) += 1;
+        }
+    }
+    Ok(frequencies)
+}
+
+/// 分割が変わった文の例
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SegmentationChange {
+    pub sentence: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// 2つの解析器による分割の違い
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SegmentationDiff {
+    pub sentences: usize,
+    /// 分割（表層形の並び）が変わった文
+    pub changed: usize,
+    /// 分割は同じで、読み・品詞・原形のいずれかが変わった文
+    pub retagged: usize,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    /// 分割が変わった文の例（先頭から`max_examples`件）
+    pub examples: Vec<SegmentationChange>,
+}
+
+impl SegmentationDiff {
+    /// 分割が変わった文の割合
+    pub fn changed_rate(&self) -> f64 {
+        if self.sentences == 0 {
+            0.0
+        } else {
+            self.changed as f64 / self.sentences as f64
+        }
+    }
+}
+
+/// サンプルコーパスの各行を両方の解析器で分割して比べる
+pub fn segmentation_diff(
+    before: &MorphAnalyzer,
+    after: &MorphAnalyzer,
+    corpus: &str,
+    max_examples: usize,
+) -> Result<SegmentationDiff> {
+    let mut diff = SegmentationDiff::default();
+    for line in corpus.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let old = before.tokenize(line)?;
+        let new = after.tokenize(line)?;
+        diff.sentences += 1;
+        diff.tokens_before += old.len();
+        diff.tokens_after += new.len();
+
+        let surfaces = |tokens: &[crate::Token]| tokens.iter().map(|t| t.surface.clone()).collect::<Vec<_>>();
+        let (old_surfaces, new_surfaces) = (surfaces(&old), surfaces(&new));
+        if old_surfaces != new_surfaces {
+            diff.changed += 1;
+            if diff.examples.len() < max_examples {
+                diff.examples.push(SegmentationChange {
+                    sentence: line.to_string(),
+                    before: old_surfaces,
+                    after: new_surfaces,
+                });
+            }
+        } else if old != new {
+            diff.retagged += 1;
+        }
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::SourceEncoding;
+
+    const CORPUS: &str = "今日は良い天気です。\n吾輩は猫である。\n\nアプリを作る。\n";
+
+    fn source() -> DictionarySource {
+        DictionarySource::from_dir("testdata/mini-ipadic", SourceEncoding::Utf8).unwrap()
+    }
+
+    fn analyzer(source: &DictionarySource) -> MorphAnalyzer {
+        MorphAnalyzer::from_dictionary(source.build().unwrap())
+    }
+
+    #[test]
+    fn test_drop_features() {
+        let full = source();
+        let options = TrimOptions { drop_features: true, min_frequency: None };
+        let (trimmed, stats) = full.trim(&options, &HashMap::new());
+        assert_eq!((stats.entries_before, stats.entries_after, stats.removed_rare), (15, 15, 0));
+        assert!(trimmed.lexicon.contains("良い,0,0,1000,形容詞,自立,*,*,*,*,良い,ヨイ\n"));
+        assert!(trimmed.lexicon.len() < full.lexicon.len());
+
+        // 読み・品詞・原形は変わらない
+        let diff = segmentation_diff(&analyzer(&full), &analyzer(&trimmed), CORPUS, 5).unwrap();
+        assert_eq!(diff.sentences, 3);
+        assert_eq!((diff.changed, diff.retagged), (0, 0));
+    }
+
+    #[test]
+    fn test_duplicates_keep_lowest_cost() {
+        let mut full = source();
+        full.lexicon.push_str("猫,0,0,900,名詞,一般,*,*,*,*,猫,ネコ,ネコ゛\n");
+        let options = TrimOptions { drop_features: true, min_frequency: None };
+        let (trimmed, stats) = full.trim(&options, &HashMap::new());
+        assert_eq!(stats.removed_duplicates, 1);
+        assert!(trimmed.lexicon.contains("猫,0,0,900,"));
+        assert!(!trimmed.lexicon.contains("猫,0,0,1000,"));
+    }
+
+    #[test]
+    fn test_remove_rare_entries() {
+        let full = source();
+        let before = analyzer(&full);
+        let frequencies = word_frequencies(&before, "吾輩は猫である。\n猫です。").unwrap();
+        assert_eq!(frequencies["猫"], 2);
+
+        let options = TrimOptions { drop_features: false, min_frequency: Some(1) };
+        let (trimmed, stats) = full.trim(&options, &frequencies);
+        // 今日・天気・アプリ・良い・作る は出現しないので除く（付属語と記号は残す）
+        assert_eq!(stats.removed_rare, 5);
+        assert!(trimmed.lexicon.contains("を,0,0,1000,"));
+
+        let diff = segmentation_diff(&before, &analyzer(&trimmed), CORPUS, 1).unwrap();
+        assert_eq!(diff.changed, 2);
+        assert_eq!(diff.examples.len(), 1);
+        assert_eq!(diff.examples[0].sentence, "今日は良い天気です。");
+        assert!((diff.changed_rate() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
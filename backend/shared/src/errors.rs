@@ -2,6 +2,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ReadMakerError {
+    #[cfg(feature = "server")]
     #[error("データベースエラー: {0}")]
     Database(#[from] sqlx::Error),
     
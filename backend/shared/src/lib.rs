@@ -1,9 +1,11 @@
 pub mod types;
 pub mod utils;
 pub mod errors;
+#[cfg(feature = "server")]
 pub mod auth;
 
 pub use types::*;
 pub use utils::*;
 pub use errors::*;
+#[cfg(feature = "server")]
 pub use auth::*;
\ No newline at end of file
@@ -0,0 +1,27 @@
+-- ReadMaker App - User Library
+-- Migration: 003_create_library.sql
+
+-- Books imported by users (EPUB, Aozora Bunko, web articles)
+CREATE TABLE library_books (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title VARCHAR(500) NOT NULL,
+    author VARCHAR(255),
+    source_format VARCHAR(20) NOT NULL, -- plain, aozora, epub, html
+    content JSONB NOT NULL, -- structured document (chapters, paragraphs, ruby)
+    char_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT library_books_char_count_positive CHECK (char_count >= 0)
+);
+
+CREATE INDEX idx_library_books_user_id ON library_books(user_id);
+CREATE INDEX idx_library_books_user_created ON library_books(user_id, created_at DESC);
+
+-- Trigger to automatically update updated_at for library_books
+CREATE TRIGGER update_library_books_updated_at 
+    BEFORE UPDATE ON library_books 
+    FOR EACH ROW 
+    EXECUTE FUNCTION update_updated_at_column();
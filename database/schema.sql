@@ -141,6 +141,26 @@ CREATE TABLE user_achievements (
     UNIQUE(user_id, achievement_type, achievement_value)
 );
 
+-- ==============================================
+-- LIBRARY TABLES
+-- ==============================================
+
+-- Books imported by users (EPUB, Aozora Bunko, web articles)
+CREATE TABLE library_books (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title VARCHAR(500) NOT NULL,
+    author VARCHAR(255),
+    source_format VARCHAR(20) NOT NULL, -- plain, aozora, epub, html
+    content JSONB NOT NULL, -- structured document (chapters, paragraphs, ruby)
+    char_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT library_books_char_count_positive CHECK (char_count >= 0)
+);
+
 -- ==============================================
 -- INDEXES FOR PERFORMANCE
 -- ==============================================
@@ -165,6 +185,10 @@ CREATE INDEX idx_user_reading_preferences_user_id ON user_reading_preferences(us
 CREATE INDEX idx_user_achievements_user_id ON user_achievements(user_id);
 CREATE INDEX idx_user_achievements_type ON user_achievements(achievement_type);
 
+-- Library indexes
+CREATE INDEX idx_library_books_user_id ON library_books(user_id);
+CREATE INDEX idx_library_books_user_created ON library_books(user_id, created_at DESC);
+
 -- ==============================================
 -- FUNCTIONS AND TRIGGERS
 -- ==============================================
@@ -199,6 +223,11 @@ CREATE TRIGGER update_user_reading_preferences_updated_at
     FOR EACH ROW 
     EXECUTE FUNCTION update_updated_at_column();
 
+CREATE TRIGGER update_library_books_updated_at 
+    BEFORE UPDATE ON library_books 
+    FOR EACH ROW 
+    EXECUTE FUNCTION update_updated_at_column();
+
 -- Function to create default user profile
 CREATE OR REPLACE FUNCTION create_default_user_profile()
 RETURNS TRIGGER AS $$